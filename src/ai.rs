@@ -1,8 +1,27 @@
+use anyhow::{anyhow, bail, Result};
+use bson::oid::ObjectId;
+use chrono::Utc;
+use dioxus::prelude::ServerFnError;
+use dioxus_logger::tracing;
+use gems::chat::Chat;
+use gems::imagen::ImageGen;
+use gems::models::Model;
+use gems::requests::{Candidate, Content, GeminiRequest, GenerationConfig, Part};
+use gems::responses::ImagenResponse;
 use gems::traits::CTrait;
+use gems::utils::extract_image_or_text;
 use gems::Client;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use std::env;
+use std::str::FromStr;
+use std::time::Instant;
 use tokio::sync::{Mutex, OnceCell};
 
+use crate::db::get_client;
+use crate::server::ai_call::model::{AiCall, CallOutcome};
+
 static AI: OnceCell<Mutex<Client>> = OnceCell::const_new();
 
 async fn init_ai_with_model(model: String) -> &'static Mutex<Client> {
@@ -23,3 +42,199 @@ async fn init_ai_with_model(model: String) -> &'static Mutex<Client> {
 pub async fn get_ai(model: String) -> &'static Mutex<Client> {
     init_ai_with_model(model).await
 }
+
+/// The model a request names, for `ChatBuilder::model`. Check it against
+/// the user's plan with `check_model` first.
+pub fn chat_model(name: &str) -> Result<Model, ServerFnError> {
+    Model::from_str(name).map_err(|_| ServerFnError::new(format!("Unknown model {}", name)))
+}
+
+/// `gems::responses::GeminiResponse` with the `usageMetadata` it leaves out.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateResponse {
+    candidates: Option<Vec<Candidate>>,
+    usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u64,
+    #[serde(default)]
+    candidates_token_count: u64,
+}
+
+/// Posts `request_body` to `generateContent` on `model`. The endpoint comes
+/// from the client's model rather than the body's, and the shared client
+/// keeps whichever model it was built with, so each call uses a copy
+/// pointed at the model it asked for.
+async fn generate_content<T: DeserializeOwned>(
+    client: &Client,
+    model: Model,
+    request_body: &GeminiRequest,
+) -> Result<T> {
+    let mut client = client.clone();
+    client.set_model(model);
+
+    let response = client
+        .request(Method::POST, "generateContent")?
+        .json(request_body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        bail!(
+            "Gemini returned {}: {}",
+            response.status(),
+            response.text().await?
+        );
+    }
+    Ok(response.json().await?)
+}
+
+/// The same request as `client.chat().generate`. gems only deserializes the
+/// candidates, dropping the token usage the API reports alongside them, so
+/// this talks to `generateContent` directly. Keep it in step with
+/// `gems::chat::Chats::generate` until gems exposes the usage.
+async fn generate_with_usage(client: &Client, params: Chat) -> Result<(String, (u64, u64))> {
+    let request_body = GeminiRequest {
+        model: params.model.to_string(),
+        contents: vec![Content {
+            parts: params.messages.iter().map(|msg| msg.to_part()).collect(),
+        }],
+        system_instruction: params.system.as_ref().map(|messages| Content {
+            parts: messages.iter().map(|msg| msg.to_part()).collect(),
+        }),
+        config: None,
+    };
+    let json: GenerateResponse = generate_content(client, params.model, &request_body).await?;
+
+    let usage = json.usage_metadata.unwrap_or_default();
+    let candidates = json
+        .candidates
+        .ok_or_else(|| anyhow!("Missing candidates"))?;
+    match candidates
+        .first()
+        .and_then(|candidate| candidate.content.parts.first())
+    {
+        Some(Part::Text { text }) => Ok((
+            text.clone(),
+            (usage.prompt_token_count, usage.candidates_token_count),
+        )),
+        _ => Err(anyhow!("Expected text response")),
+    }
+}
+
+/// The same request as `client.images().generate`, which likewise drops the
+/// usage. Image responses report a total rather than a candidates count.
+async fn generate_image_with_usage(
+    client: &Client,
+    params: ImageGen,
+) -> Result<(Vec<u8>, (u64, u64))> {
+    let request_body = GeminiRequest {
+        model: params.model.to_string(),
+        contents: vec![Content {
+            parts: vec![params.input.to_part()],
+        }],
+        system_instruction: params.system.as_ref().map(|messages| Content {
+            parts: messages.iter().map(|msg| msg.to_part()).collect(),
+        }),
+        config: Some(GenerationConfig {
+            response_modalities: vec!["Text".into(), "Image".into()],
+        }),
+    };
+    let json: ImagenResponse = generate_content(client, params.model, &request_body).await?;
+
+    let (input_tokens, total_tokens) = json
+        .usage_metadata
+        .map(|usage| {
+            (
+                usage.prompt_token_count.unwrap_or(0).max(0) as u64,
+                usage.total_token_count.unwrap_or(0).max(0) as u64,
+            )
+        })
+        .unwrap_or_default();
+    let candidate = json
+        .candidates
+        .ok_or_else(|| anyhow!("Missing candidates"))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No candidate response"))?;
+    let bytes = extract_image_or_text(&candidate.content.parts)?;
+    Ok((
+        bytes,
+        (input_tokens, total_tokens.saturating_sub(input_tokens)),
+    ))
+}
+
+/// Generates a chat completion and records the call in the `ai_calls`
+/// ledger, whether it succeeds or not.
+pub async fn generate(
+    client: &Client,
+    user: Option<ObjectId>,
+    template: &str,
+    params: Chat,
+) -> Result<String> {
+    let model = params.model.to_string();
+    let started = Instant::now();
+    let result = generate_with_usage(client, params).await;
+    record(user, template, model, started, &result).await;
+    result.map(|(text, _)| text)
+}
+
+/// Generates an image and records the call like `generate`.
+pub async fn generate_image(
+    client: &Client,
+    user: Option<ObjectId>,
+    template: &str,
+    params: ImageGen,
+) -> Result<Vec<u8>> {
+    let model = params.model.to_string();
+    let started = Instant::now();
+    let result = generate_image_with_usage(client, params).await;
+    record(user, template, model, started, &result).await;
+    result.map(|(bytes, _)| bytes)
+}
+
+/// Writes a finished call, with its `(input, output)` token counts, to the
+/// ledger.
+async fn record<T>(
+    user: Option<ObjectId>,
+    template: &str,
+    model: String,
+    started: Instant,
+    result: &Result<(T, (u64, u64))>,
+) {
+    let (input_tokens, output_tokens) = match result {
+        Ok((_, usage)) => *usage,
+        Err(_) => (0, 0),
+    };
+    let call = AiCall {
+        id: ObjectId::new(),
+        user,
+        model,
+        template: template.to_string(),
+        input_tokens,
+        output_tokens,
+        latency_ms: started.elapsed().as_millis() as u64,
+        outcome: if result.is_ok() {
+            CallOutcome::Success
+        } else {
+            CallOutcome::Failure
+        },
+        error: result.as_ref().err().map(|err| err.to_string()),
+        created_at: Utc::now(),
+    };
+    insert_call(call).await;
+}
+
+/// A lost ledger entry shouldn't fail the request that was already served.
+async fn insert_call(call: AiCall) {
+    let db = get_client()
+        .await
+        .database(&env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    if let Err(err) = db.collection::<AiCall>("ai_calls").insert_one(call).await {
+        tracing::warn!("recording AI call failed: {}", err);
+    }
+}
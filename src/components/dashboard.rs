@@ -4,4 +4,5 @@ pub(crate) mod chat;
 pub(crate) mod fields;
 pub(crate) mod navbar;
 pub(crate) mod profile;
+pub(crate) mod quota;
 pub(crate) mod sidebar;
@@ -53,6 +53,9 @@ pub fn AnalyticsPage() -> Element {
                 MetricCard { title: "Projected Growth", value: format!("{:.2}%", analytics().predictions.projected_growth), index: 4 }
                 MetricCard { title: "Avg Gen Time", value: format!("{:.2}s", analytics().ai_usage.avg_gen_time), index: 5 }
                 MetricCard { title: "Success Rate", value: format!("{:.2}%", analytics().ai_usage.success_rate), index: 6 }
+                MetricCard { title: "AI Calls", value: analytics().ai_usage.total_ai_calls.to_string(), index: 7 }
+                MetricCard { title: "AI Chapters", value: analytics().ai_usage.total_ai_chapters.to_string(), index: 8 }
+                MetricCard { title: "Tokens Used", value: analytics().ai_usage.total_tokens.to_string(), index: 9 }
             }
         }
     }
@@ -1,4 +1,18 @@
+pub(crate) mod annotations;
+pub(crate) mod audiobook;
+pub(crate) mod cover;
 pub(crate) mod create;
 pub(crate) mod edit;
+pub(crate) mod editions;
+pub(crate) mod export;
+pub(crate) mod frontmatter;
+pub(crate) mod illustrate;
 pub(crate) mod list;
+pub(crate) mod notebook;
+pub(crate) mod organize;
+pub(crate) mod outline;
+pub(crate) mod quiz;
 pub(crate) mod read;
+pub(crate) mod share;
+pub(crate) mod study;
+pub(crate) mod trash;
@@ -0,0 +1,297 @@
+use crate::server::annotation::controller::{
+    create_annotation, delete_annotation, list_annotations,
+};
+use crate::server::annotation::model::{
+    Annotation, AnnotationKind, TextSelector, HIGHLIGHT_COLORS, QUOTE_CONTEXT_LEN,
+};
+use crate::server::annotation::request::{
+    AnnotationRequest, CreateAnnotationRequest, ListAnnotationsRequest,
+};
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+use serde::Serialize;
+
+/// The element `ReadBookPanel` renders chapter HTML into.
+pub const CHAPTER_BODY_ID: &str = "chapter-body";
+
+pub fn highlight_css(color: &str) -> &'static str {
+    match color {
+        "green" => "rgba(134, 239, 172, 0.6)",
+        "blue" => "rgba(147, 197, 253, 0.6)",
+        "pink" => "rgba(249, 168, 212, 0.6)",
+        _ => "rgba(253, 224, 71, 0.6)",
+    }
+}
+
+#[derive(Serialize)]
+struct PaintRange {
+    id: String,
+    start: usize,
+    end: usize,
+    color: &'static str,
+}
+
+/// Removes earlier highlight marks and returns the chapter's plain text, which
+/// is what selector offsets are measured against.
+fn clear_marks_js() -> String {
+    format!(
+        r#"const root = document.getElementById("{CHAPTER_BODY_ID}");
+        if (!root) return "";
+        root.querySelectorAll("mark[data-annotation]").forEach((mark) => mark.replaceWith(...mark.childNodes));
+        root.normalize();
+        return root.textContent;"#
+    )
+}
+
+/// Wraps each range of the chapter text in a `<mark>`, splitting text nodes
+/// where a range starts or ends inside one.
+fn paint_js(ranges: &[PaintRange]) -> String {
+    format!(
+        r#"const root = document.getElementById("{CHAPTER_BODY_ID}");
+        if (root) {{
+            for (const range of {ranges}) {{
+                const walker = document.createTreeWalker(root, NodeFilter.SHOW_TEXT);
+                const targets = [];
+                let offset = 0;
+                while (walker.nextNode() && offset < range.end) {{
+                    const node = walker.currentNode;
+                    const from = Math.max(range.start - offset, 0);
+                    const to = Math.min(range.end - offset, node.length);
+                    if (from < to) targets.push([node, from, to]);
+                    offset += node.length;
+                }}
+                for (const [node, from, to] of targets) {{
+                    let target = from > 0 ? node.splitText(from) : node;
+                    if (to - from < target.length) target.splitText(to - from);
+                    const mark = document.createElement("mark");
+                    mark.dataset.annotation = range.id;
+                    mark.style.backgroundColor = range.color;
+                    target.parentNode.insertBefore(mark, target);
+                    mark.appendChild(target);
+                }}
+            }}
+        }}"#,
+        ranges = serde_json::to_string(ranges).unwrap_or_else(|_| "[]".to_string())
+    )
+}
+
+/// Describes the current selection inside the chapter as a quote and
+/// position selector, or `null` when nothing in the chapter is selected.
+fn selection_js() -> String {
+    format!(
+        r#"const root = document.getElementById("{CHAPTER_BODY_ID}");
+        const selection = window.getSelection();
+        if (!root || !selection || selection.rangeCount === 0 || selection.isCollapsed) return null;
+        const range = selection.getRangeAt(0);
+        if (!root.contains(range.commonAncestorContainer)) return null;
+        const before = document.createRange();
+        before.selectNodeContents(root);
+        before.setEnd(range.startContainer, range.startOffset);
+        const start = before.toString().length;
+        const exact = range.toString();
+        const end = start + exact.length;
+        const text = root.textContent;
+        return {{
+            quote: {{
+                exact,
+                prefix: text.slice(Math.max(0, start - {QUOTE_CONTEXT_LEN}), start),
+                suffix: text.slice(end, end + {QUOTE_CONTEXT_LEN}),
+            }},
+            position: {{ start, end }},
+        }};"#
+    )
+}
+
+#[component]
+pub fn ChapterAnnotations(chapter_id: String) -> Element {
+    let mut annotations = use_signal(Vec::<Annotation>::new);
+    let mut color = use_signal(|| HIGHLIGHT_COLORS[0].to_string());
+    let mut note = use_signal(String::new);
+    let mut error_message = use_signal(|| None::<String>);
+
+    // Track the prop in a signal so the resource below refetches on navigation.
+    let mut current_chapter = use_signal(|| chapter_id.clone());
+    use_effect(use_reactive!(|(chapter_id,)| {
+        if *current_chapter.peek() != chapter_id {
+            current_chapter.set(chapter_id);
+        }
+    }));
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        match list_annotations(ListAnnotationsRequest {
+            token,
+            chapter_id: Some(current_chapter()),
+            ..Default::default()
+        })
+        .await
+        {
+            Ok(response) => annotations.set(
+                response
+                    .data
+                    .into_iter()
+                    .map(|entry| entry.annotation)
+                    .collect(),
+            ),
+            Err(_) => annotations.set(Vec::new()),
+        }
+    });
+
+    // Re-anchor and repaint whenever the annotations change; the chapter HTML
+    // is replaced wholesale on navigation, taking old marks with it.
+    use_effect(move || {
+        let current = annotations();
+        spawn(async move {
+            let text = document::eval(&clear_marks_js())
+                .join::<String>()
+                .await
+                .unwrap_or_default();
+            let ranges = current
+                .iter()
+                .filter_map(|annotation| {
+                    let (start, end) = annotation.selector.as_ref()?.anchor(&text)?;
+                    Some(PaintRange {
+                        id: annotation.id.to_hex(),
+                        start,
+                        end,
+                        color: highlight_css(annotation.color.as_deref().unwrap_or_default()),
+                    })
+                })
+                .collect::<Vec<PaintRange>>();
+            let _ = document::eval(&paint_js(&ranges)).await;
+        });
+    });
+
+    let handle_create = move |kind: AnnotationKind| {
+        spawn(async move {
+            let selector = document::eval(&selection_js())
+                .join::<Option<TextSelector>>()
+                .await
+                .unwrap_or_default();
+            if selector.is_none() && kind == AnnotationKind::Highlight {
+                error_message.set(Some("Select some text to highlight first.".to_string()));
+                return;
+            }
+
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match create_annotation(CreateAnnotationRequest {
+                token,
+                chapter_id: current_chapter(),
+                kind,
+                selector,
+                color: if kind == AnnotationKind::Bookmark {
+                    None
+                } else {
+                    Some(color())
+                },
+                note: Some(note()).filter(|note| !note.trim().is_empty()),
+            })
+            .await
+            {
+                Ok(response) => {
+                    annotations.write().push(response.data);
+                    note.set(String::new());
+                    error_message.set(None);
+                }
+                Err(err) => error_message.set(Some(format!("Error saving annotation: {:?}", err))),
+            }
+        });
+    };
+
+    let handle_delete = move |annotation_id: String| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            if delete_annotation(AnnotationRequest {
+                token,
+                annotation_id: annotation_id.clone(),
+            })
+            .await
+            .is_ok()
+            {
+                annotations
+                    .write()
+                    .retain(|annotation| annotation.id.to_hex() != annotation_id);
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "mt-8 p-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-4",
+            h3 { class: "text-lg font-semibold", "Highlights & Notes" }
+            if let Some(error) = error_message() {
+                p { class: "text-red-600 text-sm", "{error}" }
+            }
+
+            div { class: "flex flex-wrap items-center gap-2",
+                for name in HIGHLIGHT_COLORS {
+                    button {
+                        key: "{name}",
+                        class: format!(
+                            "w-6 h-6 rounded-full border-2 {}",
+                            if color() == name { "border-gray-900 dark:border-white" } else { "border-transparent" }
+                        ),
+                        style: "background-color: {highlight_css(name)}",
+                        title: "{name}",
+                        onclick: move |_| color.set(name.to_string()),
+                    }
+                }
+                button {
+                    class: "bg-yellow-400 text-gray-900 px-3 py-1 rounded",
+                    onclick: move |_| handle_create(AnnotationKind::Highlight),
+                    i { class: "fas fa-highlighter mr-1" }
+                    "Highlight selection"
+                }
+                button {
+                    class: "border border-blue-500 text-blue-500 px-3 py-1 rounded",
+                    onclick: move |_| handle_create(AnnotationKind::Bookmark),
+                    i { class: "fas fa-bookmark mr-1" }
+                    "Bookmark"
+                }
+            }
+            div { class: "flex gap-2",
+                textarea {
+                    class: "flex-1 p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                    rows: "2",
+                    placeholder: "Write a margin note about the selected text...",
+                    value: "{note}",
+                    oninput: move |e| note.set(e.value()),
+                }
+                button {
+                    class: "bg-blue-500 text-white px-3 py-1 rounded dark:bg-blue-600",
+                    onclick: move |_| handle_create(AnnotationKind::Note),
+                    "Add note"
+                }
+            }
+
+            ul { class: "space-y-2",
+                for annotation in annotations() {
+                    li {
+                        key: "{annotation.id}",
+                        class: "flex items-start justify-between gap-2 p-2 rounded border-l-4",
+                        style: "border-color: {highlight_css(annotation.color.as_deref().unwrap_or_default())}",
+                        div { class: "text-sm",
+                            match annotation.kind {
+                                AnnotationKind::Bookmark => rsx! { p { class: "font-semibold", i { class: "fas fa-bookmark mr-1" } "Bookmark" } },
+                                _ => rsx! {},
+                            }
+                            if let Some(selector) = annotation.selector.clone() {
+                                blockquote { class: "italic text-gray-600 dark:text-gray-300", "“{selector.quote.exact}”" }
+                            }
+                            if let Some(text) = annotation.note.clone() {
+                                p { class: "mt-1", "{text}" }
+                            }
+                        }
+                        button {
+                            class: "text-red-500 hover:underline text-sm",
+                            onclick: {
+                                let annotation_id = annotation.id.to_hex();
+                                move |_| handle_delete(annotation_id.clone())
+                            },
+                            i { class: "fas fa-trash" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::server::audiobook::controller::{generate_audiobook, get_audiobook};
+use crate::server::audiobook::model::AudioStatus;
+use crate::server::audiobook::request::GenerateAudiobookRequest;
+use crate::server::audiobook::response::AudiobookView;
+use crate::server::book::request::BookRequest;
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+#[component]
+pub fn AudiobookPanel(book_id: String) -> Element {
+    let book_id = use_signal(|| book_id);
+    let mut audiobook = use_signal(AudiobookView::default);
+    let refresh = use_signal(|| 0u32);
+    let mut starting = use_signal(|| false);
+    let mut message = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        let _ = refresh();
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = get_audiobook(BookRequest {
+            token,
+            book_id: book_id(),
+        })
+        .await
+        {
+            audiobook.set(response.data);
+        }
+    });
+
+    let in_progress = audiobook().tracks.iter().any(|track| {
+        matches!(track.status, AudioStatus::Pending | AudioStatus::Generating)
+    });
+
+    // Tracks are narrated in the background; poll until none is left queued.
+    client! {
+        const POLL_INTERVAL_SECS: u64 = 5;
+        let mut refresh = refresh;
+        use_hook(|| {
+            let mut eval = document::eval(&format!(
+                "setInterval(() => dioxus.send(true), {})",
+                POLL_INTERVAL_SECS * 1000
+            ));
+            spawn(async move {
+                while eval.recv::<bool>().await.is_ok() {
+                    let running = audiobook.peek().tracks.iter().any(|track| {
+                        matches!(track.status, AudioStatus::Pending | AudioStatus::Generating)
+                    });
+                    if running {
+                        refresh.set(refresh() + 1);
+                    }
+                }
+            });
+        });
+    }
+
+    let handle_generate = move |_| {
+        starting.set(true);
+        message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match generate_audiobook(GenerateAudiobookRequest {
+                token,
+                book_id: book_id(),
+            })
+            .await
+            {
+                Ok(response) => audiobook.set(response.data),
+                Err(err) => message.set(Some(format!("Error generating audiobook: {:?}", err))),
+            }
+            starting.set(false);
+        });
+    };
+
+    let ready = audiobook()
+        .tracks
+        .iter()
+        .filter(|track| track.status == AudioStatus::Ready)
+        .count();
+
+    rsx! {
+        div { class: "p-4 mb-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-4",
+            div { class: "flex flex-wrap items-center justify-between gap-4",
+                h3 { class: "text-lg font-semibold", "Audiobook" }
+                button {
+                    class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                    disabled: starting() || in_progress,
+                    onclick: handle_generate,
+                    if in_progress {
+                        "Narrating {ready}/{audiobook().tracks.len()}..."
+                    } else if audiobook().tracks.is_empty() {
+                        "Generate audiobook"
+                    } else {
+                        "Regenerate audiobook"
+                    }
+                }
+            }
+            if let Some(message) = message() {
+                p { class: "text-sm text-red-600", "{message}" }
+            }
+            if let Some(url) = audiobook().bundle_url {
+                a {
+                    class: "inline-block text-blue-500 hover:underline",
+                    href: "{url}",
+                    download: "audiobook.zip",
+                    i { class: "fas fa-file-archive mr-1" }
+                    "Download bundle ({format_duration(audiobook().total_duration_ms)})"
+                }
+            }
+            if !audiobook().tracks.is_empty() {
+                ol { class: "space-y-2",
+                    for track in audiobook().tracks {
+                        li {
+                            key: "{track.chapter_id}",
+                            class: "flex flex-wrap items-center gap-4",
+                            span { class: "w-16 text-sm text-gray-500", "{format_duration(track.start_ms)}" }
+                            span { class: "flex-1", "{track.position + 1}. {track.title}" }
+                            match track.status {
+                                AudioStatus::Ready => rsx! {
+                                    audio {
+                                        controls: true,
+                                        preload: "none",
+                                        src: track.url.clone().unwrap_or_default(),
+                                    }
+                                },
+                                AudioStatus::Failed => rsx! {
+                                    span { class: "text-sm text-red-600",
+                                        "Failed: {track.error.clone().unwrap_or_default()}"
+                                    }
+                                },
+                                AudioStatus::Generating => rsx! {
+                                    span { class: "text-sm text-blue-500", "Narrating..." }
+                                },
+                                AudioStatus::Pending => rsx! {
+                                    span { class: "text-sm text-gray-500", "Queued" }
+                                },
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
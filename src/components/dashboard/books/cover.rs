@@ -0,0 +1,223 @@
+use crate::server::book::controller::get_book_for_user;
+use crate::server::book::request::{BookRequest, GetBookForUserRequest};
+use crate::server::cover::controller::{
+    choose_cover, get_cover_candidates, paint_covers, upload_cover,
+};
+use crate::server::cover::model::CoverCredit;
+use crate::server::cover::request::{ChooseCoverRequest, UploadCoverRequest};
+use crate::server::cover::response::CoverCandidate;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// "Photo by … on Unsplash", linked as the Unsplash guidelines ask.
+#[component]
+pub fn CoverCreditLine(credit: CoverCredit) -> Element {
+    rsx! {
+        p { class: "text-xs text-center mt-1 text-gray-500",
+            "Photo by "
+            a {
+                class: "underline",
+                href: "{credit.profile_url}",
+                target: "_blank",
+                rel: "noopener",
+                "{credit.photographer}"
+            }
+            " on "
+            a {
+                class: "underline",
+                href: "{credit.photo_url}",
+                target: "_blank",
+                rel: "noopener",
+                "Unsplash"
+            }
+        }
+    }
+}
+
+#[component]
+pub fn CoverPicker(book_id: String) -> Element {
+    let book_id = use_signal(|| book_id);
+    let mut cover = use_signal(|| None::<String>);
+    let mut credit = use_signal(|| None::<CoverCredit>);
+    let mut candidates = use_signal(Vec::<CoverCandidate>::new);
+    let mut busy = use_signal(|| false);
+    let mut message = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = get_book_for_user(GetBookForUserRequest {
+            token,
+            book_id: book_id(),
+        })
+        .await
+        {
+            cover.set(response.data.cover);
+            credit.set(response.data.cover_credit);
+        }
+    });
+
+    let handle_search = move |_| {
+        busy.set(true);
+        message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match get_cover_candidates(BookRequest {
+                token,
+                book_id: book_id(),
+            })
+            .await
+            {
+                Ok(response) => candidates.set(response.data),
+                Err(err) => message.set(Some(format!("Error finding covers: {:?}", err))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let handle_paint = move |_| {
+        busy.set(true);
+        message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match paint_covers(BookRequest {
+                token,
+                book_id: book_id(),
+            })
+            .await
+            {
+                Ok(response) => candidates.write().extend(response.data),
+                Err(err) => message.set(Some(format!("Error painting covers: {:?}", err))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let mut handle_choose = move |url: String| {
+        busy.set(true);
+        message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match choose_cover(ChooseCoverRequest {
+                token,
+                book_id: book_id(),
+                url,
+            })
+            .await
+            {
+                Ok(response) => {
+                    cover.set(Some(response.data.url));
+                    credit.set(response.data.credit);
+                    candidates.set(Vec::new());
+                }
+                Err(err) => message.set(Some(format!("Error choosing cover: {:?}", err))),
+            }
+            busy.set(false);
+        });
+    };
+
+    let handle_upload = move |evt: FormEvent| async move {
+        let Some(files) = evt.files() else {
+            return;
+        };
+        let Some(name) = files.files().into_iter().next() else {
+            return;
+        };
+        let Some(bytes) = files.read_file(&name).await else {
+            message.set(Some("Could not read the file.".to_string()));
+            return;
+        };
+        busy.set(true);
+        message.set(None);
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        match upload_cover(UploadCoverRequest {
+            token,
+            book_id: book_id(),
+            data: STANDARD.encode(&bytes),
+        })
+        .await
+        {
+            Ok(response) => {
+                cover.set(Some(response.data.url));
+                credit.set(response.data.credit);
+                candidates.set(Vec::new());
+            }
+            Err(err) => message.set(Some(format!("Error uploading cover: {:?}", err))),
+        }
+        busy.set(false);
+    };
+
+    rsx! {
+        div { class: "p-4 mb-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-4",
+            div { class: "flex flex-wrap items-center justify-between gap-4",
+                h3 { class: "text-lg font-semibold", "Cover" }
+                div { class: "flex flex-wrap items-center gap-2",
+                    button {
+                        class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                        disabled: busy(),
+                        onclick: handle_search,
+                        if busy() { "Working..." } else { "Suggest covers" }
+                    }
+                    button {
+                        class: "border px-4 py-2 rounded",
+                        disabled: busy(),
+                        onclick: handle_paint,
+                        "Paint with AI"
+                    }
+                    label { class: "cursor-pointer border px-4 py-2 rounded",
+                        "Upload image"
+                        input {
+                            r#type: "file",
+                            accept: "image/png,image/jpeg,image/webp",
+                            class: "hidden",
+                            disabled: busy(),
+                            onchange: handle_upload,
+                        }
+                    }
+                }
+            }
+            if let Some(message) = message() {
+                p { class: "text-sm text-red-600", "{message}" }
+            }
+            div { class: "flex flex-wrap gap-4",
+                if let Some(url) = cover() {
+                    div { class: "w-32",
+                        img {
+                            src: "{url}",
+                            alt: "Current cover",
+                            class: "w-32 h-48 object-cover rounded-md ring-2 ring-blue-500",
+                        }
+                        p { class: "text-xs text-center mt-1 text-gray-500", "Current" }
+                        if let Some(credit) = credit() {
+                            CoverCreditLine { credit }
+                        }
+                    }
+                }
+                for candidate in candidates() {
+                    div { key: "{candidate.url}", class: "w-32",
+                        button {
+                            disabled: busy(),
+                            onclick: {
+                                let url = candidate.url.clone();
+                                move |_| handle_choose(url.clone())
+                            },
+                            img {
+                                src: "{candidate.url}",
+                                alt: "{candidate.source.label()} cover",
+                                class: "w-32 h-48 object-cover rounded-md hover:opacity-80",
+                            }
+                        }
+                        if let Some(credit) = candidate.credit.clone() {
+                            CoverCreditLine { credit }
+                        } else {
+                            p { class: "text-xs text-center mt-1 text-gray-500",
+                                "{candidate.source.label()}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
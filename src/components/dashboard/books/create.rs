@@ -1,6 +1,7 @@
 use crate::components::dashboard::books::list::CachedBooksData;
 use crate::components::dashboard::books::list::CACHE_KEY;
 use crate::components::dashboard::fields::select::SelectField;
+use crate::components::dashboard::quota::{use_quota, QuotaSummary};
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
 use crate::components::toast::manager::ToastManager;
@@ -36,6 +37,7 @@ pub fn CreateBookPanel(user_token: Signal<String>) -> Element {
     let maxlen_valid = use_signal(|| true);
     let chapters_valid = use_signal(|| true);
     let mut loading = use_signal(|| false);
+    let mut quota = use_quota();
     let _form_error = use_signal(|| None::<String>);
 
     let mut toasts_manager = use_context::<Signal<ToastManager>>();
@@ -77,6 +79,7 @@ pub fn CreateBookPanel(user_token: Signal<String>) -> Element {
                     .await
                     {
                         Ok(response) => {
+                            quota.write().books_this_month += 1;
                             let mut cached_data = LocalStorage::get::<CachedBooksData>(CACHE_KEY)
                                 .unwrap_or(CachedBooksData {
                                     data: Vec::new(),
@@ -108,6 +111,7 @@ pub fn CreateBookPanel(user_token: Signal<String>) -> Element {
                             );
                             for chapter in response.data.chapters {
                                 match generate_chapter_content(GenerateChapterContentRequest {
+                                    token: user_token(),
                                     chapter_title: chapter.title,
                                     book_title: title(),
                                     main_topic: chapter.html,
@@ -182,6 +186,7 @@ pub fn CreateBookPanel(user_token: Signal<String>) -> Element {
     rsx! {
         div { class: "p-4 dark:bg-gray-800 dark:text-white bg-white text-gray-900",
             h2 { class: "text-xl font-semibold mb-4", "Generate" }
+            QuotaSummary { quota }
             form { class: "space-y-4",
                 onsubmit: handle_submit,
                 Input {
@@ -224,19 +229,7 @@ pub fn CreateBookPanel(user_token: Signal<String>) -> Element {
                 }
                 SelectField {
                     label: "Model",
-                    options: vec![
-                        "gemini-1.5-flash",
-                        "gemini-2.5-pro-preview-03-25",
-                        "gemini-2.0-flash",
-                        "gemini-2.0-flash-lite",
-                        "gemini-1.5-flash-8b",
-                        "gemini-1.5-pro",
-                        "text-embedding-004",
-                        "imagen-3.0-generate-002",
-                        "veo-2.0-generate-001",
-                        "gemini-2.0-flash-live-001",
-                        "gemini-2.0-flash-exp-image-generation",
-                    ],
+                    options: quota().plan.limits().models.to_vec(),
                     selected: model,
                 }
                 Input {
@@ -1,3 +1,5 @@
+use crate::components::dashboard::books::cover::CoverPicker;
+use crate::components::dashboard::books::frontmatter::FrontMatterEditor;
 use crate::server::auth::controller::about_me;
 use crate::server::book::controller::{
     extend_text, regenerate_text, summarize_text, update_book_content,
@@ -101,6 +103,8 @@ pub fn EditBookContentPanel(book_id: String) -> Element {
             if let Some(error) = error_message() {
                 p { class: "text-red-600", "{error}" }
             }
+            CoverPicker { book_id: book_id() }
+            FrontMatterEditor { book_id: book_id() }
 
             if let Some(book) = book() {
                 div {
@@ -0,0 +1,170 @@
+use crate::components::dashboard::books::list::CACHE_KEY;
+use crate::router::Route;
+use crate::server::book::model::TranslationStatus;
+use crate::server::book::request::BookRequest;
+use crate::server::edition::controller::{get_book_editions, translate_book};
+use crate::server::edition::request::TranslateBookRequest;
+use crate::server::edition::response::Edition;
+use dioxus::prelude::*;
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
+
+#[component]
+pub fn EditionSwitcher(book_id: String, chapter_position: Option<u32>) -> Element {
+    let navigator = use_navigator();
+    let book_id = use_signal(|| book_id);
+    let mut editions = use_signal(Vec::<Edition>::new);
+    let mut refresh = use_signal(|| 0u32);
+    let mut language = use_signal(String::new);
+    let mut translating = use_signal(|| false);
+    let mut message = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        let _ = refresh();
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = get_book_editions(BookRequest {
+            token,
+            book_id: book_id(),
+        })
+        .await
+        {
+            editions.set(response.data);
+        }
+    });
+
+    // Editions being translated fill in chapter by chapter; poll until done.
+    client! {
+        const POLL_INTERVAL_SECS: u64 = 10;
+        use_hook(|| {
+            let mut eval = document::eval(&format!(
+                "setInterval(() => dioxus.send(true), {})",
+                POLL_INTERVAL_SECS * 1000
+            ));
+            spawn(async move {
+                while eval.recv::<bool>().await.is_ok() {
+                    let running = editions.peek().iter().any(|edition| {
+                        edition
+                            .translation
+                            .as_ref()
+                            .is_some_and(|progress| progress.status == TranslationStatus::Running)
+                    });
+                    if running {
+                        refresh.set(refresh() + 1);
+                    }
+                }
+            });
+        });
+    }
+
+    let handle_translate = move |_| {
+        translating.set(true);
+        message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match translate_book(TranslateBookRequest {
+                token,
+                book_id: book_id(),
+                language: language(),
+                model: "gemini-2.0-flash".to_string(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    LocalStorage::delete(CACHE_KEY);
+                    language.set(String::new());
+                    message.set(Some(format!(
+                        "Translating into {}...",
+                        response.data.language.unwrap_or_default()
+                    )));
+                    refresh.set(refresh() + 1);
+                }
+                Err(err) => message.set(Some(format!("Error starting translation: {:?}", err))),
+            }
+            translating.set(false);
+        });
+    };
+
+    let handle_switch = move |e: Event<FormData>| {
+        let target = e.value();
+        let Some(edition) = editions().into_iter().find(|edition| edition.book_id == target) else {
+            return;
+        };
+        // Open the same chapter in the other edition when it has one.
+        let chapter = chapter_position
+            .and_then(|position| edition.chapter_ids.get(position as usize).cloned())
+            .unwrap_or_default();
+        navigator.push(Route::ReadBook {
+            id: edition.book_id,
+            chapter,
+        });
+    };
+
+    let current = editions()
+        .into_iter()
+        .find(|edition| edition.book_id == book_id());
+
+    rsx! {
+        div { class: "p-4 mb-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-2",
+            div { class: "flex flex-wrap items-end gap-4",
+                if editions().len() > 1 {
+                    div {
+                        label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Edition" }
+                        select {
+                            class: "mt-1 p-2 border rounded-md shadow-sm dark:bg-gray-900 dark:border-gray-700 border-gray-300",
+                            value: "{book_id}",
+                            onchange: handle_switch,
+                            for edition in editions() {
+                                option {
+                                    value: "{edition.book_id}",
+                                    selected: edition.book_id == book_id(),
+                                    if edition.is_original {
+                                        "{edition.language} (original)"
+                                    } else {
+                                        "{edition.language}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div {
+                    label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Translate into" }
+                    input {
+                        class: "mt-1 p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                        placeholder: "French",
+                        value: "{language}",
+                        oninput: move |e| language.set(e.value()),
+                    }
+                }
+                button {
+                    class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                    disabled: translating() || language().trim().is_empty(),
+                    onclick: handle_translate,
+                    if translating() { "Starting..." } else { "Translate" }
+                }
+            }
+            if let Some(message) = message() {
+                p { class: "text-sm text-gray-500", "{message}" }
+            }
+            for edition in editions().into_iter().filter(|edition| edition.translation.is_some()) {
+                if let Some(progress) = edition.translation.clone() {
+                    match progress.status {
+                        TranslationStatus::Running => rsx! {
+                            p { key: "{edition.book_id}", class: "text-sm text-blue-500",
+                                "{edition.language}: translating {progress.translated}/{progress.total} chapters..."
+                            }
+                        },
+                        TranslationStatus::Failed => rsx! {
+                            p { key: "{edition.book_id}", class: "text-sm text-red-600",
+                                "{edition.language}: translation failed. {progress.error.clone().unwrap_or_default()}"
+                            }
+                        },
+                        TranslationStatus::Completed => rsx! {},
+                    }
+                }
+            }
+            if current.is_some_and(|edition| !edition.is_original) {
+                p { class: "text-xs text-gray-500", "Machine-translated edition." }
+            }
+        }
+    }
+}
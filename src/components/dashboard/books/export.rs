@@ -0,0 +1,142 @@
+use crate::components::dashboard::books::read::CHAPTERS_CACHE_KEY;
+use crate::components::dashboard::quota::use_quota;
+use crate::server::backmatter::controller::{export_bibtex, generate_back_matter};
+use crate::server::backmatter::request::GenerateBackMatterRequest;
+use crate::server::book::model::Chapter;
+use crate::server::book::request::BookRequest;
+use crate::server::export::controller::export_book;
+use crate::server::export::request::{ExportBookRequest, ExportFormat};
+use dioxus::prelude::*;
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
+
+/// Hands `content` to the browser as a file download.
+pub fn download_file(filename: &str, mime_type: &str, content: &[u8]) {
+    let content = serde_json::to_string(content).unwrap_or_default();
+    let filename = serde_json::to_string(filename).unwrap_or_default();
+    let mime_type = serde_json::to_string(mime_type).unwrap_or_default();
+    let _ = document::eval(&format!(
+        r#"const blob = new Blob([new Uint8Array({content})], {{ type: {mime_type} }});
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = {filename};
+        link.click();
+        URL.revokeObjectURL(link.href);"#
+    ));
+}
+
+#[component]
+pub fn ExportPanel(book_id: String, chapters: Signal<Vec<Chapter>>) -> Element {
+    let book_id = use_signal(|| book_id);
+    let mut generating = use_signal(|| false);
+    let mut message = use_signal(|| None::<String>);
+
+    let quota = use_quota();
+
+    let has_back_matter = chapters().iter().any(|chapter| chapter.kind.is_back_matter());
+    let allowed = |format: ExportFormat| quota().plan.limits().export_formats.contains(&format);
+
+    let handle_generate = move |_| {
+        generating.set(true);
+        message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match generate_back_matter(GenerateBackMatterRequest {
+                token,
+                book_id: book_id(),
+                model: "gemini-2.0-flash".to_string(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    LocalStorage::delete(CHAPTERS_CACHE_KEY);
+                    let mut chapters = chapters;
+                    chapters.set(response.data);
+                    message.set(Some("Back matter generated.".to_string()));
+                }
+                Err(err) => message.set(Some(format!("Error generating back matter: {:?}", err))),
+            }
+            generating.set(false);
+        });
+    };
+
+    let handle_export = move |format: ExportFormat| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match export_book(ExportBookRequest {
+                token,
+                book_id: book_id(),
+                format,
+            })
+            .await
+            {
+                Ok(response) => {
+                    let file = response.data;
+                    download_file(&file.filename, &file.mime_type, &file.content);
+                }
+                Err(err) => message.set(Some(format!("Error exporting book: {:?}", err))),
+            }
+        });
+    };
+
+    let handle_bibtex = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match export_bibtex(BookRequest {
+                token,
+                book_id: book_id(),
+            })
+            .await
+            {
+                Ok(response) => download_file("references.bib", "application/x-bibtex", response.data.as_bytes()),
+                Err(err) => message.set(Some(format!("Error exporting BibTeX: {:?}", err))),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "p-4 mb-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-4",
+            h3 { class: "text-lg font-semibold", "Back matter & export" }
+            if let Some(message) = message() {
+                p { class: "text-sm text-gray-500", "{message}" }
+            }
+            div { class: "flex flex-wrap gap-2",
+                button {
+                    class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                    disabled: generating(),
+                    onclick: handle_generate,
+                    if generating() {
+                        "Generating..."
+                    } else if has_back_matter {
+                        "Regenerate glossary, index & bibliography"
+                    } else {
+                        "Generate glossary, index & bibliography"
+                    }
+                }
+                button {
+                    class: "border border-blue-500 text-blue-500 px-4 py-2 rounded disabled:opacity-50",
+                    disabled: !allowed(ExportFormat::Markdown),
+                    title: if !allowed(ExportFormat::Markdown) { "Not included in your plan" } else { "" },
+                    onclick: move |_| handle_export(ExportFormat::Markdown),
+                    i { class: "fas fa-file-download mr-1" }
+                    "Markdown"
+                }
+                button {
+                    class: "border border-blue-500 text-blue-500 px-4 py-2 rounded disabled:opacity-50",
+                    disabled: !allowed(ExportFormat::Html),
+                    title: if !allowed(ExportFormat::Html) { "Not included in your plan" } else { "" },
+                    onclick: move |_| handle_export(ExportFormat::Html),
+                    i { class: "fas fa-file-download mr-1" }
+                    "HTML / PDF"
+                }
+                if has_back_matter {
+                    button {
+                        class: "border border-blue-500 text-blue-500 px-4 py-2 rounded",
+                        onclick: handle_bibtex,
+                        i { class: "fas fa-file-download mr-1" }
+                        "BibTeX"
+                    }
+                }
+            }
+        }
+    }
+}
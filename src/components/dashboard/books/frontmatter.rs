@@ -0,0 +1,145 @@
+use crate::server::book::controller::{generate_front_matter, get_book_for_user, set_front_matter};
+use crate::server::book::model::{Book, FrontMatterEntry, FrontMatterKind};
+use crate::server::book::request::{
+    GenerateFrontMatterRequest, GetBookForUserRequest, SetFrontMatterRequest,
+};
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+use std::collections::HashMap;
+
+fn placeholder(kind: FrontMatterKind) -> &'static str {
+    match kind {
+        FrontMatterKind::Dedication => "For ...",
+        FrontMatterKind::Preface => "Why you wrote this book and who it is for.",
+        FrontMatterKind::Acknowledgements => "The people who helped along the way.",
+        FrontMatterKind::Introduction => {
+            "What the reader will learn and how the book is organized."
+        }
+        FrontMatterKind::AuthorBio => "A short biography, written in the third person.",
+        FrontMatterKind::Blurb => "The pitch printed on the back cover.",
+    }
+}
+
+#[component]
+pub fn FrontMatterEditor(book_id: String) -> Element {
+    let book_id = use_signal(|| book_id);
+    let mut texts = use_signal(HashMap::<FrontMatterKind, String>::new);
+    let mut saving = use_signal(|| false);
+    let mut generating = use_signal(|| None::<FrontMatterKind>);
+    let mut message = use_signal(|| None::<String>);
+
+    let mut load = move |book: Book| {
+        texts.set(
+            book.front_matter
+                .into_iter()
+                .map(|entry| (entry.kind, entry.text))
+                .collect(),
+        );
+    };
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        match get_book_for_user(GetBookForUserRequest {
+            token,
+            book_id: book_id(),
+        })
+        .await
+        {
+            Ok(response) => load(response.data),
+            Err(err) => message.set(Some(format!("Error loading front matter: {:?}", err))),
+        }
+    });
+
+    let handle_save = move |_| {
+        saving.set(true);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match set_front_matter(SetFrontMatterRequest {
+                token,
+                book_id: book_id(),
+                entries: texts()
+                    .into_iter()
+                    .map(|(kind, text)| FrontMatterEntry { kind, text })
+                    .collect(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    load(response.data);
+                    message.set(Some("Front matter saved.".to_string()));
+                }
+                Err(err) => message.set(Some(format!("Error saving front matter: {:?}", err))),
+            }
+            saving.set(false);
+        });
+    };
+
+    let mut handle_generate = move |kind: FrontMatterKind| {
+        generating.set(Some(kind));
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match generate_front_matter(GenerateFrontMatterRequest {
+                token,
+                book_id: book_id(),
+                kind,
+                model: "gemini-2.0-flash".to_string(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    texts.write().insert(kind, response.data);
+                    message.set(Some(format!(
+                        "{} drafted. Review it, then save.",
+                        kind.title()
+                    )));
+                }
+                Err(err) => message.set(Some(format!(
+                    "Error generating {}: {:?}",
+                    kind.title(),
+                    err
+                ))),
+            }
+            generating.set(None);
+        });
+    };
+
+    rsx! {
+        div { class: "p-4 mb-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-4",
+            h3 { class: "text-lg font-semibold", "Front matter" }
+            p { class: "text-sm text-gray-500",
+                "Included in Markdown and HTML exports. Leave a field empty to omit it, or generate a draft from your chapters."
+            }
+            if let Some(message) = message() {
+                p { class: "text-sm text-gray-500", "{message}" }
+            }
+            for kind in FrontMatterKind::ALL {
+                div { key: "{kind.slug()}",
+                    div { class: "flex items-center justify-between",
+                        label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "{kind.title()}" }
+                        button {
+                            class: "text-sm text-blue-500 hover:underline disabled:opacity-50",
+                            disabled: generating().is_some(),
+                            onclick: move |_| handle_generate(kind),
+                            if generating() == Some(kind) { "Generating..." } else { "Generate" }
+                        }
+                    }
+                    textarea {
+                        class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                        rows: if kind == FrontMatterKind::Dedication { "2" } else { "5" },
+                        placeholder: placeholder(kind),
+                        value: texts().get(&kind).cloned().unwrap_or_default(),
+                        oninput: move |e| {
+                            texts.write().insert(kind, e.value());
+                        },
+                    }
+                }
+            }
+            button {
+                class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                disabled: saving(),
+                onclick: handle_save,
+                if saving() { "Saving..." } else { "Save front matter" }
+            }
+        }
+    }
+}
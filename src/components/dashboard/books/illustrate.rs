@@ -0,0 +1,109 @@
+use crate::components::dashboard::books::read::CHAPTERS_CACHE_KEY;
+use crate::server::book::model::Chapter;
+use crate::server::illustration::controller::{
+    illustrate_chapter, placeholder_count, section_headings,
+};
+use crate::server::illustration::request::IllustrateChapterRequest;
+use dioxus::prelude::*;
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
+
+/// Paints illustrations into the open chapter, either where the model left
+/// placeholders or after a section heading the user picks.
+#[component]
+pub fn ChapterIllustrator(
+    chapters: Signal<Vec<Chapter>>,
+    selected_chapter: Signal<Option<Chapter>>,
+) -> Element {
+    let mut section = use_signal(|| 0usize);
+    let mut busy = use_signal(|| false);
+    let mut message = use_signal(|| None::<String>);
+
+    let Some(chapter) = selected_chapter() else {
+        return rsx! {};
+    };
+    if chapter.html.trim().is_empty() {
+        return rsx! {};
+    }
+    let headings = section_headings(&chapter.html);
+    let placeholders = placeholder_count(&chapter.html);
+    let last_section = headings.len().saturating_sub(1);
+
+    let mut run = move |section: Option<u32>| {
+        let Some(chapter) = selected_chapter() else {
+            return;
+        };
+        busy.set(true);
+        message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match illustrate_chapter(IllustrateChapterRequest {
+                token,
+                chapter_id: chapter.id.to_hex(),
+                section,
+                model: "gemini-2.0-flash".to_string(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    LocalStorage::delete(CHAPTERS_CACHE_KEY);
+                    let updated = Chapter {
+                        html: response.data.html,
+                        ..chapter
+                    };
+                    if let Some(entry) = chapters.write().iter_mut().find(|c| c.id == updated.id) {
+                        *entry = updated.clone();
+                    }
+                    selected_chapter.set(Some(updated));
+                    message.set(Some(if response.data.remaining > 0 {
+                        format!(
+                            "Added {} illustrations; {} placeholders left.",
+                            response.data.added, response.data.remaining
+                        )
+                    } else {
+                        format!("Added {} illustrations.", response.data.added)
+                    }));
+                }
+                Err(err) => message.set(Some(format!("Error illustrating chapter: {:?}", err))),
+            }
+            busy.set(false);
+        });
+    };
+
+    rsx! {
+        div { class: "mt-6 p-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-2",
+            h3 { class: "font-semibold", "Illustrations" }
+            div { class: "flex flex-wrap items-center gap-2",
+                if placeholders > 0 {
+                    button {
+                        class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                        disabled: busy(),
+                        onclick: move |_| run(None),
+                        "Fill {placeholders} image placeholders"
+                    }
+                }
+                if !headings.is_empty() {
+                    select {
+                        class: "p-2 rounded border dark:bg-gray-700",
+                        value: "{section}",
+                        onchange: move |evt| section.set(evt.value().parse().unwrap_or(0)),
+                        for (index, heading) in headings.iter().enumerate() {
+                            option { value: "{index}", "{heading}" }
+                        }
+                    }
+                    button {
+                        class: "border border-blue-500 text-blue-500 px-4 py-2 rounded hover:bg-blue-500 hover:text-white",
+                        disabled: busy(),
+                        onclick: move |_| run(Some(section().min(last_section) as u32)),
+                        "Illustrate this section"
+                    }
+                }
+                if busy() {
+                    span { class: "text-sm text-gray-500", "Painting..." }
+                }
+            }
+            if let Some(message) = message() {
+                p { class: "text-sm text-gray-500", "{message}" }
+            }
+        }
+    }
+}
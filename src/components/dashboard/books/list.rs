@@ -1,14 +1,21 @@
 use crate::components::dashboard::analytics::AnalyticsPage;
+use crate::components::dashboard::fields::select::SelectField;
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
 use crate::router::Route;
-use crate::server::book::controller::get_books_for_user;
+use crate::server::book::controller::{delete_book, get_library_facets, list_books};
 use crate::server::book::model::Book;
-use crate::server::book::request::GetBooksForUserRequest;
-use chrono::Utc;
+use crate::server::book::response::LibraryFacets;
+use crate::server::progress::controller::get_library_progress;
+use crate::server::progress::request::LibraryProgressRequest;
+use crate::server::progress::response::BookProgress;
+use crate::server::book::request::{
+    BookRequest, BookSort, GetBooksForUserRequest, ListBooksRequest, SortOrder,
+};
 use dioxus::prelude::*;
 use gloo_storage::{LocalStorage, Storage};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize)]
 pub struct CachedBooksData {
@@ -19,71 +26,149 @@ pub struct CachedBooksData {
 pub const CACHE_KEY: &str = "books_cache";
 pub const CACHE_TIMEOUT: i64 = 2 * 60 * 60;
 
+const PAGE_SIZE: u32 = 12;
+
+fn sort_option(label: &str) -> (BookSort, SortOrder) {
+    match label {
+        "Oldest" => (BookSort::Created, SortOrder::Asc),
+        "Recently Updated" => (BookSort::Updated, SortOrder::Desc),
+        "Title A-Z" => (BookSort::Title, SortOrder::Asc),
+        "Title Z-A" => (BookSort::Title, SortOrder::Desc),
+        "Completed First" => (BookSort::Completion, SortOrder::Desc),
+        "In Progress First" => (BookSort::Completion, SortOrder::Asc),
+        _ => (BookSort::Created, SortOrder::Desc),
+    }
+}
+
 #[component]
 pub fn BooksPanel(user_token: Signal<String>) -> Element {
-    let mut books = use_signal(Vec::new);
-    let mut displayed_books = use_signal(Vec::new);
+    let mut books = use_signal(Vec::<Book>::new);
+    let mut next_cursor = use_signal(|| None::<String>);
     let mut loading = use_signal(|| true);
+    let mut request_id = use_signal(|| 0u64);
     let mut search_query = use_signal(String::new);
+    let sort = use_signal(|| "Newest".to_string());
+    let status = use_signal(|| "All".to_string());
+    let mut book_type = use_signal(String::new);
+    let mut tag = use_signal(String::new);
+    let mut collection = use_signal(String::new);
+    let mut series = use_signal(String::new);
+    let mut facets = use_signal(LibraryFacets::default);
+    let mut progress = use_signal(HashMap::<String, BookProgress>::new);
 
     let _ = use_resource(move || async move {
-        let now = Utc::now().timestamp();
-
-        if let Ok(cached_data) = LocalStorage::get::<CachedBooksData>(CACHE_KEY) {
-            if now - cached_data.timestamp < CACHE_TIMEOUT {
-                loading.set(false);
-                books.set(cached_data.data.clone());
-                displayed_books.set(cached_data.data);
-                return;
-            }
+        if user_token().is_empty() {
+            return;
         }
-
-        match get_books_for_user(GetBooksForUserRequest {
+        if let Ok(response) = get_library_facets(GetBooksForUserRequest {
             token: user_token(),
         })
         .await
         {
-            Ok(response) => {
-                let cached_data = CachedBooksData {
-                    data: response.data.clone(),
-                    timestamp: now,
-                };
-                let _ = LocalStorage::set(CACHE_KEY, &cached_data);
-
-                loading.set(false);
-                books.set(response.data.clone());
-                displayed_books.set(response.data);
-            }
-            Err(_) => {
-                loading.set(false);
-            }
+            facets.set(response.data);
+        }
+        if let Ok(response) = get_library_progress(LibraryProgressRequest {
+            token: user_token(),
+        })
+        .await
+        {
+            progress.set(
+                response
+                    .data
+                    .into_iter()
+                    .map(|entry| (entry.book_id.clone(), entry))
+                    .collect(),
+            );
         }
     });
 
-    let mut filter_books = move || {
-        let query = search_query().to_lowercase();
+    // Only the first page resets the list; later pages append to it. Responses
+    // from superseded requests are dropped so fast typing can't mix results.
+    let mut fetch_page = move |reset: bool| {
+        let cursor = if reset {
+            None
+        } else {
+            match next_cursor.peek().clone() {
+                Some(cursor) => Some(cursor),
+                None => return,
+            }
+        };
+
+        // A series is read in order, so it overrides the chosen sort.
+        let (sort, order) = if series.peek().is_empty() {
+            sort_option(&sort.peek())
+        } else {
+            (BookSort::SeriesOrder, SortOrder::Asc)
+        };
+        let completed = match status.peek().as_str() {
+            "Completed" => Some(true),
+            "In Progress" => Some(false),
+            _ => None,
+        };
+        let request = ListBooksRequest {
+            token: user_token.peek().clone(),
+            cursor,
+            limit: Some(PAGE_SIZE),
+            sort,
+            order,
+            completed,
+            book_type: Some(book_type.peek().clone()).filter(|t| !t.is_empty()),
+            tag: Some(tag.peek().clone()).filter(|t| !t.is_empty()),
+            collection: Some(collection.peek().clone()).filter(|c| !c.is_empty()),
+            series: Some(series.peek().clone()).filter(|s| !s.is_empty()),
+            query: Some(search_query.peek().clone()).filter(|q| !q.trim().is_empty()),
+        };
 
-        let filtered_books = books()
-            .iter()
-            .filter(|book| {
-                let matches_query = if query.is_empty() {
-                    true
+        let id = *request_id.peek() + 1;
+        request_id.set(id);
+        loading.set(true);
+
+        spawn(async move {
+            let response = list_books(request).await;
+            if *request_id.peek() != id {
+                return;
+            }
+            if let Ok(response) = response {
+                if reset {
+                    books.set(response.data.books);
                 } else {
-                    let title_matches = book.title.to_lowercase().contains(&query);
-                    let subtitle_matches = book
-                        .subtitle
-                        .as_deref()
-                        .map(|s| s.to_lowercase().contains(&query))
-                        .unwrap_or(false);
-                    title_matches || subtitle_matches
-                };
+                    books.write().extend(response.data.books);
+                }
+                next_cursor.set(response.data.next_cursor);
+            }
+            loading.set(false);
+        });
+    };
 
-                matches_query
-            })
-            .cloned()
-            .collect::<Vec<_>>();
+    use_effect(move || {
+        let token = user_token();
+        let _ = (
+            search_query(),
+            sort(),
+            status(),
+            book_type(),
+            tag(),
+            collection(),
+            series(),
+        );
+        if !token.is_empty() {
+            fetch_page(true);
+        }
+    });
 
-        displayed_books.set(filtered_books);
+    let handle_delete = move |book_id: String| {
+        spawn(async move {
+            if delete_book(BookRequest {
+                token: user_token(),
+                book_id: book_id.clone(),
+            })
+            .await
+            .is_ok()
+            {
+                LocalStorage::delete(CACHE_KEY);
+                books.write().retain(|book| book.id.to_hex() != book_id);
+            }
+        });
     };
 
     rsx! {
@@ -91,7 +176,7 @@ pub fn BooksPanel(user_token: Signal<String>) -> Element {
             AnalyticsPage {}
             div {
                 div {
-                    class: "w-full md:w-1/3 pb-4 mb-4 md:mb-0 flex flex-col gap-8",
+                    class: "w-full pb-4 mb-4 grid grid-cols-1 md:grid-cols-3 lg:grid-cols-6 gap-4 items-end",
 
                     div {
                         h3 { class: "text-2xl font-bold mb-4", "Search" }
@@ -99,20 +184,105 @@ pub fn BooksPanel(user_token: Signal<String>) -> Element {
                             class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
                             placeholder: "Search by title...",
                             value: "{search_query()}",
-                            oninput: move |e| {
-                                search_query.set(e.value());
-                                filter_books();
-                            },
+                            oninput: move |e| search_query.set(e.value()),
+                        }
+                    }
+                    SelectField {
+                        label: "Sort by",
+                        options: vec![
+                            "Newest",
+                            "Oldest",
+                            "Recently Updated",
+                            "Title A-Z",
+                            "Title Z-A",
+                            "Completed First",
+                            "In Progress First",
+                        ],
+                        selected: sort,
+                    }
+                    SelectField {
+                        label: "Status",
+                        options: vec!["All", "Completed", "In Progress"],
+                        selected: status,
+                    }
+                    div {
+                        label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Book type" }
+                        select {
+                            class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900 dark:border-gray-700 border-gray-300",
+                            value: "{book_type}",
+                            oninput: move |e| book_type.set(e.value()),
+                            option { value: "", "All" }
+                            for kind in facets().book_types {
+                                option { value: "{kind}", "{kind}" }
+                            }
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Collection" }
+                        select {
+                            class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900 dark:border-gray-700 border-gray-300",
+                            value: "{collection}",
+                            oninput: move |e| collection.set(e.value()),
+                            option { value: "", "All" }
+                            for name in facets().collections {
+                                option { value: "{name}", "{name}" }
+                            }
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Series" }
+                        select {
+                            class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900 dark:border-gray-700 border-gray-300",
+                            value: "{series}",
+                            oninput: move |e| series.set(e.value()),
+                            option { value: "", "All" }
+                            for name in facets().series {
+                                option { value: "{name}", "{name}" }
+                            }
+                        }
+                    }
+                }
+                if !facets().tags.is_empty() {
+                    div { class: "flex flex-wrap gap-2 mb-4",
+                        for name in facets().tags {
+                            button {
+                                key: "{name}",
+                                class: format!(
+                                    "px-3 py-1 rounded-full text-sm border {}",
+                                    if tag() == name { "bg-blue-500 text-white border-blue-500" } else { "border-gray-300 dark:border-gray-600" }
+                                ),
+                                onclick: {
+                                    let name = name.clone();
+                                    move |_| {
+                                        if tag() == name {
+                                            tag.set(String::new());
+                                        } else {
+                                            tag.set(name.clone());
+                                        }
+                                    }
+                                },
+                                "#{name}"
+                            }
                         }
                     }
                 }
                 h2 { class: "text-xl font-semibold mb-4", "All Books" }
-                if displayed_books.len() > 0 {
+                if books.len() > 0 {
                     div {
                         class: "grid grid-cols-1 sm:grid-cols-2 md:grid-cols-3 lg:grid-cols-4 gap-6",
-                        for book in displayed_books() {
+                        for (book, book_progress) in books().into_iter().map(|book| {
+                            let book_progress = progress().get(&book.id.to_hex()).cloned();
+                            (book, book_progress)
+                        }) {
                             Link {
-                                to: Route::ReadBook { id: book.id.to_string() },
+                                key: "{book.id}",
+                                to: Route::ReadBook {
+                                    id: book.id.to_string(),
+                                    chapter: book_progress
+                                        .as_ref()
+                                        .and_then(|p| p.last_chapter_id.clone())
+                                        .unwrap_or_default(),
+                                },
                                 class: "p-4 shadow rounded-lg dark:bg-gray-700 bg-gray-100",
                                 img {
                                     src: book.cover.as_deref().unwrap_or("/path/to/default-cover.jpg"),
@@ -138,9 +308,62 @@ pub fn BooksPanel(user_token: Signal<String>) -> Element {
                                     class: "mt-2 text-sm text-gray-700",
                                     "{book.title.chars().take(30).collect::<String>()}..."
                                 }
+                                if let Some(book_progress) = book_progress {
+                                    div { class: "mt-2",
+                                        div { class: "w-full h-2 rounded-full bg-gray-300 dark:bg-gray-600",
+                                            div {
+                                                class: "h-2 rounded-full bg-blue-500",
+                                                style: "width: {book_progress.percent_complete.round()}%",
+                                            }
+                                        }
+                                        p { class: "mt-1 text-xs text-blue-500",
+                                            if book_progress.percent_complete >= 100.0 {
+                                                "Finished"
+                                            } else {
+                                                "Continue reading · {book_progress.percent_complete.round()}% complete"
+                                            }
+                                        }
+                                    }
+                                }
+                                if let Some(entry) = book.series.clone() {
+                                    p { class: "mt-1 text-xs text-blue-500", "{entry.name} #{entry.order}" }
+                                }
+                                if !book.tags.is_empty() {
+                                    div { class: "flex flex-wrap gap-1 mt-1",
+                                        for name in book.tags.clone() {
+                                            span { class: "text-xs px-2 rounded-full bg-gray-200 dark:bg-gray-600", "#{name}" }
+                                        }
+                                    }
+                                }
+                                button {
+                                    class: "mt-2 text-sm text-red-500 hover:underline",
+                                    onclick: {
+                                        let book_id = book.id.to_hex();
+                                        move |e: Event<MouseData>| {
+                                            e.prevent_default();
+                                            e.stop_propagation();
+                                            handle_delete(book_id.clone());
+                                        }
+                                    },
+                                    i { class: "fas fa-trash mr-1" }
+                                    "Move to Trash"
+                                }
                             }
                         }
                     }
+                    if next_cursor().is_some() {
+                        button {
+                            class: "w-full mt-6 p-2 rounded-lg text-blue-500 hover:underline",
+                            disabled: loading(),
+                            onvisible: move |e| {
+                                if e.is_intersecting().unwrap_or(false) && !loading() {
+                                    fetch_page(false);
+                                }
+                            },
+                            onclick: move |_| fetch_page(false),
+                            if loading() { "Loading more books..." } else { "Load more" }
+                        }
+                    }
                 } else {
                     p {
                         class: "flex items-center space-x-2 px-4 py-2 rounded",
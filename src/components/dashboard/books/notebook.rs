@@ -0,0 +1,123 @@
+use crate::components::dashboard::books::annotations::highlight_css;
+use crate::components::dashboard::books::export::download_file;
+use crate::components::spinner::Spinner;
+use crate::components::spinner::SpinnerSize;
+use crate::router::Route;
+use crate::server::annotation::controller::{export_annotations_markdown, list_annotations};
+use crate::server::annotation::model::AnnotationKind;
+use crate::server::annotation::request::ListAnnotationsRequest;
+use crate::server::annotation::response::NotebookEntry;
+use dioxus::prelude::*;
+
+#[component]
+pub fn NotebookPanel(user_token: Signal<String>) -> Element {
+    let mut entries = use_signal(Vec::<NotebookEntry>::new);
+    let mut loading = use_signal(|| true);
+    let mut book_filter = use_signal(String::new);
+    let mut error_message = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        if user_token().is_empty() {
+            return;
+        }
+        match list_annotations(ListAnnotationsRequest {
+            token: user_token(),
+            ..Default::default()
+        })
+        .await
+        {
+            Ok(response) => entries.set(response.data),
+            Err(err) => error_message.set(Some(format!("Error loading notebook: {:?}", err))),
+        }
+        loading.set(false);
+    });
+
+    let mut books = entries()
+        .into_iter()
+        .map(|entry| (entry.annotation.book_id.to_hex(), entry.book_title))
+        .collect::<Vec<(String, String)>>();
+    books.dedup();
+
+    let handle_export = move |_| {
+        spawn(async move {
+            match export_annotations_markdown(ListAnnotationsRequest {
+                token: user_token(),
+                book_id: Some(book_filter()).filter(|id| !id.is_empty()),
+                ..Default::default()
+            })
+            .await
+            {
+                Ok(response) => download_file("notebook.md", "text/markdown", response.data.as_bytes()),
+                Err(err) => error_message.set(Some(format!("Error exporting notebook: {:?}", err))),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "p-4 dark:bg-gray-800 dark:text-white bg-white text-gray-900 space-y-4",
+            div { class: "flex flex-wrap items-end justify-between gap-4",
+                h2 { class: "text-xl font-semibold", "Notebook" }
+                div { class: "flex items-end gap-2",
+                    select {
+                        class: "p-2 border rounded-md shadow-sm dark:bg-gray-900 dark:border-gray-700 border-gray-300",
+                        value: "{book_filter}",
+                        oninput: move |e| book_filter.set(e.value()),
+                        option { value: "", "All books" }
+                        for (id, title) in books {
+                            option { value: "{id}", "{title}" }
+                        }
+                    }
+                    button {
+                        class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                        onclick: handle_export,
+                        i { class: "fas fa-file-download mr-1" }
+                        "Export Markdown"
+                    }
+                }
+            }
+            if let Some(error) = error_message() {
+                p { class: "text-red-600", "{error}" }
+            }
+
+            if loading() {
+                p { class: "flex items-center space-x-2 px-4 py-2 rounded",
+                    Spinner {
+                        aria_label: "Loading spinner".to_string(),
+                        size: SpinnerSize::Md,
+                        dark_mode: true,
+                    }
+                    span { "Loading notebook..." }
+                }
+            } else if entries().is_empty() {
+                p { class: "text-gray-500", "Highlights, notes and bookmarks you make while reading will show up here." }
+            } else {
+                ul { class: "space-y-3",
+                    for entry in entries().into_iter().filter(|entry| book_filter().is_empty() || entry.annotation.book_id.to_hex() == book_filter()) {
+                        li {
+                            key: "{entry.annotation.id}",
+                            class: "p-4 rounded-lg border-l-4 shadow dark:bg-gray-700 bg-gray-100",
+                            style: "border-color: {highlight_css(entry.annotation.color.as_deref().unwrap_or_default())}",
+                            Link {
+                                to: Route::ReadBook {
+                                    id: entry.annotation.book_id.to_hex(),
+                                    chapter: entry.annotation.chapter_id.to_hex(),
+                                },
+                                class: "text-sm text-blue-500 hover:underline",
+                                "{entry.book_title} · {entry.chapter_position + 1}. {entry.chapter_title}"
+                            }
+                            if entry.annotation.kind == AnnotationKind::Bookmark {
+                                p { class: "mt-1 font-semibold", i { class: "fas fa-bookmark mr-1" } "Bookmark" }
+                            }
+                            if let Some(selector) = entry.annotation.selector.clone() {
+                                blockquote { class: "mt-2 italic text-gray-600 dark:text-gray-300", "“{selector.quote.exact}”" }
+                            }
+                            if let Some(note) = entry.annotation.note.clone() {
+                                p { class: "mt-2", "{note}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
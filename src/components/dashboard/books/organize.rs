@@ -0,0 +1,142 @@
+use crate::components::dashboard::books::list::CACHE_KEY;
+use crate::server::book::controller::{get_book_for_user, set_book_labels, set_book_series};
+use crate::server::book::model::Book;
+use crate::server::book::request::{
+    GetBookForUserRequest, SetBookLabelsRequest, SetBookSeriesRequest,
+};
+use dioxus::prelude::*;
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
+
+fn split_labels(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .collect()
+}
+
+#[component]
+pub fn OrganizePanel(book_id: String) -> Element {
+    let book_id = use_signal(|| book_id);
+    let mut tags = use_signal(String::new);
+    let mut collections = use_signal(String::new);
+    let mut series = use_signal(String::new);
+    let mut order = use_signal(String::new);
+    let mut message = use_signal(|| None::<String>);
+
+    let mut load = move |book: Book| {
+        tags.set(book.tags.join(", "));
+        collections.set(book.collections.join(", "));
+        match book.series {
+            Some(entry) => {
+                series.set(entry.name);
+                order.set(entry.order.to_string());
+            }
+            None => {
+                series.set(String::new());
+                order.set(String::new());
+            }
+        }
+    };
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = get_book_for_user(GetBookForUserRequest {
+            token,
+            book_id: book_id(),
+        })
+        .await
+        {
+            load(response.data);
+        }
+    });
+
+    let handle_save = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            let labels = set_book_labels(SetBookLabelsRequest {
+                token: token.clone(),
+                book_id: book_id(),
+                tags: split_labels(&tags()),
+                collections: split_labels(&collections()),
+            })
+            .await;
+            let result = match labels {
+                Ok(_) => {
+                    set_book_series(SetBookSeriesRequest {
+                        token,
+                        book_id: book_id(),
+                        series: Some(series()).filter(|name| !name.trim().is_empty()),
+                        order: order().trim().parse().ok(),
+                    })
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(response) => {
+                    LocalStorage::delete(CACHE_KEY);
+                    load(response.data);
+                    message.set(Some("Saved.".to_string()));
+                }
+                Err(err) => message.set(Some(format!("Error saving labels: {:?}", err))),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "p-4 mb-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-4",
+            h3 { class: "text-lg font-semibold", "Organize" }
+            if let Some(message) = message() {
+                p { class: "text-sm text-gray-500", "{message}" }
+            }
+
+            div { class: "grid grid-cols-1 md:grid-cols-4 gap-4 items-end",
+                div {
+                    label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Tags" }
+                    input {
+                        class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                        placeholder: "rust, beginner",
+                        value: "{tags}",
+                        oninput: move |e| tags.set(e.value()),
+                    }
+                }
+                div {
+                    label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Collections" }
+                    input {
+                        class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                        placeholder: "Work, Favorites",
+                        value: "{collections}",
+                        oninput: move |e| collections.set(e.value()),
+                    }
+                }
+                div {
+                    label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Series" }
+                    input {
+                        class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                        placeholder: "None",
+                        value: "{series}",
+                        oninput: move |e| series.set(e.value()),
+                    }
+                }
+                div {
+                    label { class: "block text-sm font-medium dark:text-gray-300 text-gray-700", "Position in series" }
+                    input {
+                        class: "mt-1 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                        r#type: "number",
+                        min: "1",
+                        placeholder: "Last",
+                        value: "{order}",
+                        oninput: move |e| order.set(e.value()),
+                    }
+                }
+            }
+            button {
+                class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                onclick: handle_save,
+                "Save"
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::components::dashboard::books::read::CHAPTERS_CACHE_KEY;
+use crate::components::spinner::Spinner;
+use crate::components::spinner::SpinnerSize;
+use crate::server::book::controller::{
+    delete_chapter, insert_chapter, merge_chapters, reorder_chapters, split_chapter,
+};
+use crate::server::book::model::Chapter;
+use crate::server::book::request::{
+    ChapterRequest, InsertChapterRequest, ReorderChaptersRequest, SplitChapterRequest,
+};
+use crate::server::common::response::SuccessResponse;
+use dioxus::prelude::*;
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
+
+#[component]
+pub fn OutlineEditor(
+    book_id: String,
+    chapters: Signal<Vec<Chapter>>,
+    selected_chapter: Signal<Option<Chapter>>,
+) -> Element {
+    let book_id = use_signal(|| book_id);
+    let mut dragged = use_signal(|| None::<usize>);
+    let mut new_title = use_signal(String::new);
+    let mut generate = use_signal(|| false);
+    let mut busy = use_signal(|| false);
+    let mut error_message = use_signal(|| None::<String>);
+
+    let mut apply = move |result: Result<SuccessResponse<Vec<Chapter>>, ServerFnError>| {
+        busy.set(false);
+        match result {
+            Ok(response) => {
+                LocalStorage::delete(CHAPTERS_CACHE_KEY);
+                let still_selected = selected_chapter()
+                    .and_then(|current| response.data.iter().find(|c| c.id == current.id).cloned());
+                selected_chapter.set(still_selected.or_else(|| response.data.first().cloned()));
+                chapters.set(response.data);
+                error_message.set(None);
+            }
+            Err(err) => error_message.set(Some(format!("Outline update failed: {:?}", err))),
+        }
+    };
+
+    let mut handle_drop = move |target: usize| {
+        let Some(source) = dragged() else {
+            return;
+        };
+        dragged.set(None);
+        if source == target {
+            return;
+        }
+
+        let mut reordered = chapters();
+        let chapter = reordered.remove(source);
+        reordered.insert(target, chapter);
+        let chapter_ids = reordered.iter().map(|c| c.id.to_hex()).collect();
+        chapters.set(reordered);
+
+        busy.set(true);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            apply(
+                reorder_chapters(ReorderChaptersRequest {
+                    token,
+                    book_id: book_id(),
+                    chapter_ids,
+                })
+                .await,
+            );
+        });
+    };
+
+    let handle_insert = move |_| {
+        if new_title().trim().is_empty() {
+            error_message.set(Some("Chapter title can't be blank!".to_string()));
+            return;
+        }
+
+        busy.set(true);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            let position = match selected_chapter() {
+                Some(chapter) => chapter.position + 1,
+                None => chapters().len() as u32,
+            };
+            apply(
+                insert_chapter(InsertChapterRequest {
+                    token,
+                    book_id: book_id(),
+                    position,
+                    title: new_title(),
+                    generate: generate(),
+                    model: "gemini-2.0-flash".to_string(),
+                })
+                .await,
+            );
+            new_title.set(String::new());
+        });
+    };
+
+    let mut run_chapter_action = move |action: &'static str, chapter_id: String| {
+        busy.set(true);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            let result = match action {
+                "split" => {
+                    split_chapter(SplitChapterRequest {
+                        token,
+                        chapter_id,
+                        html_offset: None,
+                        new_title: None,
+                    })
+                    .await
+                }
+                "merge" => merge_chapters(ChapterRequest { token, chapter_id }).await,
+                "delete" => delete_chapter(ChapterRequest { token, chapter_id }).await,
+                _ => Err(ServerFnError::new("Invalid action")),
+            };
+            apply(result);
+        });
+    };
+
+    let count = chapters().len();
+
+    rsx! {
+        div { class: "space-y-4",
+            if let Some(error) = error_message() {
+                p { class: "text-red-600 text-sm", "{error}" }
+            }
+
+            ul { class: "space-y-2",
+                for (index, chapter) in chapters().into_iter().enumerate() {
+                    li {
+                        key: "{chapter.id}",
+                        class: format!("flex items-center justify-between p-2 rounded-lg border cursor-move {}",
+                            if dragged() == Some(index) { "opacity-50 border-blue-500" } else { "border-gray-300 dark:border-gray-700" }),
+                        draggable: "true",
+                        ondragstart: move |_| dragged.set(Some(index)),
+                        ondragover: move |e: Event<DragData>| e.prevent_default(),
+                        ondrop: move |e: Event<DragData>| {
+                            e.prevent_default();
+                            handle_drop(index);
+                        },
+                        span { class: "truncate", "{index + 1}. {chapter.title}" }
+                        div { class: "flex space-x-2 text-sm",
+                            button {
+                                class: "text-blue-500 hover:underline",
+                                title: "Split into two chapters",
+                                disabled: busy(),
+                                onclick: {
+                                    let chapter_id = chapter.id.to_hex();
+                                    move |_| run_chapter_action("split", chapter_id.clone())
+                                },
+                                i { class: "fas fa-cut" }
+                            }
+                            if index + 1 < count {
+                                button {
+                                    class: "text-green-500 hover:underline",
+                                    title: "Merge with next chapter",
+                                    disabled: busy(),
+                                    onclick: {
+                                        let chapter_id = chapter.id.to_hex();
+                                        move |_| run_chapter_action("merge", chapter_id.clone())
+                                    },
+                                    i { class: "fas fa-compress-alt" }
+                                }
+                            }
+                            button {
+                                class: "text-red-500 hover:underline",
+                                title: "Delete chapter",
+                                disabled: busy(),
+                                onclick: {
+                                    let chapter_id = chapter.id.to_hex();
+                                    move |_| run_chapter_action("delete", chapter_id.clone())
+                                },
+                                i { class: "fas fa-trash" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "space-y-2",
+                input {
+                    class: "block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                    placeholder: "New chapter title...",
+                    value: "{new_title}",
+                    oninput: move |e| new_title.set(e.value()),
+                }
+                label { class: "flex items-center space-x-2 text-sm",
+                    input {
+                        r#type: "checkbox",
+                        checked: generate(),
+                        onchange: move |e| generate.set(e.checked()),
+                    }
+                    span { "Generate content with AI" }
+                }
+                button {
+                    class: "flex items-center space-x-2 bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                    disabled: busy(),
+                    onclick: handle_insert,
+                    if busy() {
+                        Spinner {
+                            aria_label: "Loading spinner".to_string(),
+                            size: SpinnerSize::Sm,
+                            dark_mode: true,
+                        }
+                        span { "Updating..." }
+                    } else {
+                        span { "Insert after selected" }
+                    }
+                }
+            }
+        }
+    }
+}
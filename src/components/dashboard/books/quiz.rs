@@ -0,0 +1,261 @@
+use crate::components::spinner::Spinner;
+use crate::components::spinner::SpinnerSize;
+use crate::server::quiz::controller::{generate_chapter_quiz, get_chapter_quiz, submit_quiz_attempt};
+use crate::server::quiz::model::QuestionKind;
+use crate::server::quiz::request::{
+    ChapterQuizRequest, GenerateQuizRequest, QuizAnswer, SubmitQuizRequest,
+};
+use crate::server::quiz::response::{QuizResult, QuizView};
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+use std::collections::{HashMap, HashSet};
+
+#[component]
+pub fn ChapterQuiz(chapter_id: String) -> Element {
+    let mut quiz = use_signal(|| None::<QuizView>);
+    let mut answers = use_signal(HashMap::<String, String>::new);
+    let mut result = use_signal(|| None::<QuizResult>);
+    let mut flipped = use_signal(HashSet::<String>::new);
+    let mut loading = use_signal(|| true);
+    let mut generating = use_signal(|| false);
+    let mut error_message = use_signal(|| None::<String>);
+
+    let mut current_chapter = use_signal(|| chapter_id.clone());
+    use_effect(use_reactive!(|(chapter_id,)| {
+        if *current_chapter.peek() != chapter_id {
+            current_chapter.set(chapter_id);
+        }
+    }));
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        loading.set(true);
+        answers.set(HashMap::new());
+        result.set(None);
+        match get_chapter_quiz(ChapterQuizRequest {
+            token,
+            chapter_id: current_chapter(),
+        })
+        .await
+        {
+            Ok(response) => quiz.set(response.data),
+            Err(err) => error_message.set(Some(format!("Error loading quiz: {:?}", err))),
+        }
+        loading.set(false);
+    });
+
+    let handle_generate = move |_| {
+        generating.set(true);
+        error_message.set(None);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match generate_chapter_quiz(GenerateQuizRequest {
+                token,
+                chapter_id: current_chapter(),
+                model: "gemini-2.0-flash".to_string(),
+                multiple_choice: None,
+                short_answer: None,
+                flashcards: None,
+            })
+            .await
+            {
+                Ok(response) => {
+                    answers.set(HashMap::new());
+                    result.set(None);
+                    quiz.set(Some(response.data));
+                }
+                Err(err) => error_message.set(Some(format!("Error generating quiz: {:?}", err))),
+            }
+            generating.set(false);
+        });
+    };
+
+    let handle_submit = move |_| {
+        let Some(current) = quiz() else {
+            return;
+        };
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match submit_quiz_attempt(SubmitQuizRequest {
+                token,
+                quiz_id: current.id.clone(),
+                answers: answers()
+                    .into_iter()
+                    .map(|(question_id, response)| QuizAnswer {
+                        question_id,
+                        response,
+                    })
+                    .collect(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    if let Some(quiz) = quiz.write().as_mut() {
+                        quiz.attempts += 1;
+                        quiz.best_score = quiz.best_score.max(Some(response.data.score));
+                    }
+                    result.set(Some(response.data));
+                }
+                Err(err) => error_message.set(Some(format!("Error submitting quiz: {:?}", err))),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "space-y-6",
+            if let Some(error) = error_message() {
+                p { class: "text-red-600 text-sm", "{error}" }
+            }
+
+            if loading() {
+                p { class: "flex items-center space-x-2",
+                    Spinner {
+                        aria_label: "Loading spinner".to_string(),
+                        size: SpinnerSize::Md,
+                        dark_mode: true,
+                    }
+                    span { "Loading quiz..." }
+                }
+            } else if let Some(current) = quiz() {
+                div { class: "flex flex-wrap items-center justify-between gap-4",
+                    p { class: "text-sm text-gray-500",
+                        "{current.attempts} attempt(s)"
+                        if let Some(best) = current.best_score {
+                            " · best score {best}/{current.questions.len()}"
+                        }
+                    }
+                    button {
+                        class: "text-sm text-blue-500 hover:underline",
+                        disabled: generating(),
+                        onclick: handle_generate,
+                        if generating() { "Regenerating..." } else { "Regenerate quiz" }
+                    }
+                }
+
+                ol { class: "space-y-6 list-decimal list-inside",
+                    for question in current.questions.clone() {
+                        li {
+                            key: "{question.id}",
+                            class: "space-y-2",
+                            span { class: "font-semibold", "{question.prompt}" }
+                            match question.kind {
+                                QuestionKind::MultipleChoice => rsx! {
+                                    div { class: "space-y-1 ml-4",
+                                        for (index, option) in question.options.clone().into_iter().enumerate() {
+                                            label { class: "flex items-center space-x-2",
+                                                input {
+                                                    r#type: "radio",
+                                                    name: "{question.id}",
+                                                    disabled: result().is_some(),
+                                                    checked: answers().get(&question.id) == Some(&index.to_string()),
+                                                    onchange: {
+                                                        let question_id = question.id.clone();
+                                                        move |_| {
+                                                            answers.write().insert(question_id.clone(), index.to_string());
+                                                        }
+                                                    },
+                                                }
+                                                span { "{option}" }
+                                            }
+                                        }
+                                    }
+                                },
+                                QuestionKind::ShortAnswer => rsx! {
+                                    input {
+                                        class: "ml-4 block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900",
+                                        placeholder: "Your answer...",
+                                        disabled: result().is_some(),
+                                        value: answers().get(&question.id).cloned().unwrap_or_default(),
+                                        oninput: {
+                                            let question_id = question.id.clone();
+                                            move |e: Event<FormData>| {
+                                                answers.write().insert(question_id.clone(), e.value());
+                                            }
+                                        },
+                                    }
+                                },
+                            }
+                            if let Some(graded) = result().and_then(|r| r.results.into_iter().find(|q| q.question_id == question.id)) {
+                                div {
+                                    class: format!(
+                                        "ml-4 p-2 rounded text-sm {}",
+                                        if graded.correct { "bg-green-100 text-green-800" } else { "bg-red-100 text-red-800" }
+                                    ),
+                                    p { class: "font-semibold",
+                                        if graded.correct { "Correct" } else { "Incorrect — answer: {graded.correct_answer}" }
+                                    }
+                                    p { "{graded.explanation}" }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if let Some(graded) = result() {
+                    div { class: "flex items-center gap-4",
+                        p { class: "text-lg font-semibold", "Score: {graded.score}/{graded.total}" }
+                        button {
+                            class: "border border-blue-500 text-blue-500 px-4 py-2 rounded",
+                            onclick: move |_| {
+                                answers.set(HashMap::new());
+                                result.set(None);
+                            },
+                            "Try again"
+                        }
+                    }
+                } else {
+                    button {
+                        class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                        onclick: handle_submit,
+                        "Submit answers"
+                    }
+                }
+
+                if !current.flashcards.is_empty() {
+                    h3 { class: "text-lg font-semibold", "Flashcards" }
+                    div { class: "grid grid-cols-1 sm:grid-cols-2 gap-4",
+                        for card in current.flashcards.clone() {
+                            button {
+                                key: "{card.id}",
+                                class: "p-4 min-h-24 rounded-lg shadow text-left dark:bg-gray-700 bg-gray-100",
+                                onclick: {
+                                    let card_id = card.id.clone();
+                                    move |_| {
+                                        let mut flipped = flipped.write();
+                                        if !flipped.remove(&card_id) {
+                                            flipped.insert(card_id.clone());
+                                        }
+                                    }
+                                },
+                                if flipped().contains(&card.id) {
+                                    p { class: "text-blue-500", "{card.back}" }
+                                } else {
+                                    p { class: "font-semibold", "{card.front}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            } else {
+                div { class: "space-y-2",
+                    p { "No quiz for this chapter yet." }
+                    button {
+                        class: "flex items-center space-x-2 bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                        disabled: generating(),
+                        onclick: handle_generate,
+                        if generating() {
+                            Spinner {
+                                aria_label: "Loading spinner".to_string(),
+                                size: SpinnerSize::Sm,
+                                dark_mode: true,
+                            }
+                            span { "Generating quiz..." }
+                        } else {
+                            span { "Generate quiz & flashcards" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,12 +1,25 @@
+use crate::components::dashboard::books::annotations::{ChapterAnnotations, CHAPTER_BODY_ID};
+use crate::components::dashboard::books::audiobook::AudiobookPanel;
+use crate::components::dashboard::books::editions::EditionSwitcher;
+use crate::components::dashboard::books::export::ExportPanel;
+use crate::components::dashboard::books::illustrate::ChapterIllustrator;
+use crate::components::dashboard::books::organize::OrganizePanel;
+use crate::components::dashboard::books::outline::OutlineEditor;
+use crate::components::dashboard::books::quiz::ChapterQuiz;
+use crate::components::dashboard::books::share::SharePanel;
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
 use crate::server::book::controller::get_chapters_for_book;
 use crate::server::book::model::Chapter;
 use crate::server::book::request::GetChaptersContentRequest;
+use crate::server::progress::controller::{get_book_progress, update_reading_progress};
+use crate::server::progress::model::ReadingProgress;
+use crate::server::progress::request::{BookProgressRequest, UpdateProgressRequest};
 use chrono::Utc;
 use dioxus::prelude::*;
-use gloo_storage::{LocalStorage, Storage};
+use gloo_storage::{LocalStorage, SessionStorage, Storage};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize)]
 pub struct CachedChaptersData {
@@ -18,14 +31,37 @@ pub struct CachedChaptersData {
 pub const CHAPTERS_CACHE_KEY: &str = "chapters_cache";
 pub const CHAPTERS_CACHE_TIMEOUT: i64 = 2 * 60 * 60;
 
+/// How often the reader reports scroll position and reading time.
+const PROGRESS_INTERVAL_SECS: i64 = 15;
+
+/// Evaluates to the fraction of the open chapter scrolled through. Chapters
+/// too short to scroll count as fully read.
+const SCROLL_FRACTION_JS: &str = r#"(() => {
+    const el = document.getElementById("reader-content");
+    if (el && el.scrollHeight > el.clientHeight) {
+        return el.scrollTop / (el.scrollHeight - el.clientHeight);
+    }
+    const doc = document.documentElement;
+    const max = doc.scrollHeight - doc.clientHeight;
+    return max > 0 ? doc.scrollTop / max : 1;
+})()"#;
+
 #[component]
-pub fn ReadBookPanel(book_id: String) -> Element {
+pub fn ReadBookPanel(book_id: String, #[props(default)] chapter_id: String) -> Element {
     let mut selected_chapter = use_signal(|| None::<Chapter>);
     let mut chapters = use_signal(Vec::<Chapter>::new);
     let mut loading = use_signal(|| true);
+    let mut editing_outline = use_signal(|| false);
+    let mut quiz_mode = use_signal(|| false);
+    let mut progress = use_signal(HashMap::<String, ReadingProgress>::new);
+    let last_report = use_signal(|| Utc::now().timestamp());
+    let share_book_id = book_id.clone();
+    let progress_book_id = book_id.clone();
+    let requested_chapter = chapter_id.clone();
 
     use_effect(move || {
         let book_id_cloned = book_id.clone();
+        let requested_chapter = requested_chapter.clone();
         spawn(async move {
             let now = Utc::now().timestamp();
 
@@ -35,14 +71,13 @@ pub fn ReadBookPanel(book_id: String) -> Element {
                 {
                     loading.set(false);
                     chapters.set(cached_data.data.clone());
-                    if let Some(first_chapter) = cached_data.data.first() {
-                        selected_chapter.set(Some(first_chapter.clone()));
-                    }
+                    selected_chapter.set(initial_chapter(&cached_data.data, &requested_chapter));
                     return;
                 }
             }
 
             if let Ok(response) = get_chapters_for_book(GetChaptersContentRequest {
+                token: SessionStorage::get("jwt").unwrap_or_default(),
                 book_id: book_id_cloned.clone(),
             })
             .await
@@ -57,48 +92,191 @@ pub fn ReadBookPanel(book_id: String) -> Element {
                 };
                 let _ = LocalStorage::set(CHAPTERS_CACHE_KEY, &cached_data);
 
-                if let Some(first_chapter) = response.data.first() {
-                    selected_chapter.set(Some(first_chapter.clone()));
-                }
+                selected_chapter.set(initial_chapter(&response.data, &requested_chapter));
             } else {
                 loading.set(true);
             }
         });
     });
 
+    // Search results link to a chapter of the book that may already be open.
+    use_effect(use_reactive!(|(chapter_id,)| {
+        if let Some(chapter) = chapters
+            .peek()
+            .iter()
+            .find(|chapter| chapter.id.to_hex() == chapter_id)
+        {
+            selected_chapter.set(Some(chapter.clone()));
+        }
+    }));
+
+    let _ = use_resource(move || {
+        let book_id = progress_book_id.clone();
+        async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            if token.is_empty() || book_id.is_empty() {
+                return;
+            }
+            if let Ok(response) = get_book_progress(BookProgressRequest { token, book_id }).await {
+                progress.set(
+                    response
+                        .data
+                        .into_iter()
+                        .map(|entry| (entry.chapter_id.to_hex(), entry))
+                        .collect(),
+                );
+            }
+        }
+    });
+
+    // Reports where the reader is in `chapter` along with the time spent
+    // since the last report.
+    let report_progress = move |chapter: Chapter, scroll_position: f64| {
+        let mut last_report = last_report;
+        let mut progress = progress;
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if token.is_empty() {
+            return;
+        }
+        let now = Utc::now().timestamp();
+        let seconds = (now - *last_report.peek()).clamp(0, PROGRESS_INTERVAL_SECS) as u64;
+        last_report.set(now);
+
+        spawn(async move {
+            if let Ok(response) = update_reading_progress(UpdateProgressRequest {
+                token,
+                book_id: chapter.book_id.to_hex(),
+                chapter_id: chapter.id.to_hex(),
+                scroll_position,
+                seconds,
+                completed: None,
+            })
+            .await
+            {
+                progress
+                    .write()
+                    .insert(chapter.id.to_hex(), response.data);
+            }
+        });
+    };
+
+    client! {
+        use_hook(|| {
+            let mut eval = document::eval(&format!(
+                r#"setInterval(() => {{
+                    if (document.visibilityState === "visible") {{
+                        dioxus.send({SCROLL_FRACTION_JS});
+                    }}
+                }}, {})"#,
+                PROGRESS_INTERVAL_SECS * 1000
+            ));
+            spawn(async move {
+                while let Ok(scroll_position) = eval.recv::<f64>().await {
+                    if let Some(chapter) = selected_chapter.peek().clone() {
+                        report_progress(chapter, scroll_position);
+                    }
+                }
+            });
+        });
+    }
+
+    // Put the reader back where they left off in the newly opened chapter.
+    use_effect(move || {
+        let Some(chapter) = selected_chapter() else {
+            return;
+        };
+        let scroll_position = progress
+            .peek()
+            .get(&chapter.id.to_hex())
+            .map(|entry| entry.scroll_position)
+            .unwrap_or(0.0);
+        document::eval(&format!(
+            r#"requestAnimationFrame(() => {{
+                const el = document.getElementById("reader-content");
+                if (el && el.scrollHeight > el.clientHeight) {{
+                    el.scrollTop = {scroll_position} * (el.scrollHeight - el.clientHeight);
+                }} else {{
+                    const doc = document.documentElement;
+                    doc.scrollTop = {scroll_position} * (doc.scrollHeight - doc.clientHeight);
+                }}
+            }})"#
+        ));
+    });
+
     let mut handle_chapter_click = {
         let mut selected_chapter = selected_chapter.clone();
         move |chapter: Chapter| {
+            if let Some(previous) = selected_chapter.peek().clone() {
+                spawn(async move {
+                    let scroll_position = document::eval(&format!("return {SCROLL_FRACTION_JS};"))
+                        .join::<f64>()
+                        .await
+                        .unwrap_or(0.0);
+                    report_progress(previous, scroll_position);
+                });
+            }
             selected_chapter.set(Some(chapter));
         }
     };
 
     rsx! {
+        if !share_book_id.is_empty() {
+            EditionSwitcher {
+                book_id: share_book_id.clone(),
+                chapter_position: selected_chapter().map(|chapter| chapter.position),
+            }
+            SharePanel { book_id: share_book_id.clone() }
+            OrganizePanel { book_id: share_book_id.clone() }
+            ExportPanel { book_id: share_book_id.clone(), chapters }
+            AudiobookPanel { book_id: share_book_id.clone() }
+        }
         div {
             class: "flex h-full dark:bg-gray-900 dark:text-white bg-white text-gray-900",
 
             div {
                 class: "md:w-1/3 lg:w-1/4 sm:w-1/6 p-4 border-r border-blue-300",
-                ul {
-                    class: "space-y-4",
-                    for (index, chapter) in chapters().into_iter().enumerate() {
-                        li {
-                            class: format!("flex items-center p-3 rounded-lg cursor-pointer {}",
-                                if chapter.id == selected_chapter().unwrap().id {
-                                    "bg-gray-500 text-white font-semibold"
-                                } else {
-                                    "hover:bg-gray-200 dark:hover:bg-dark-800"
-                                }),
-                            onclick: move |_| handle_chapter_click(chapter.clone()),
-                            div {
-                                class: "w-8 h-8 flex items-center justify-center rounded-full border-2 border-blue-500 mr-4",
-                                "{index + 1}"
-                            },
-
-                            div {
-                                class: "flex-1 hidden sm:block",
-                                h4 { class: "text-lg", "{chapter.title}" }
-                                p { class: "text-sm text-blue-500", "{chapter.estimated_duration} minutes" }
+                if !share_book_id.is_empty() && !chapters().is_empty() {
+                    button {
+                        class: "w-full mb-4 p-2 rounded-lg border border-blue-500 text-blue-500 hover:bg-blue-500 hover:text-white",
+                        onclick: move |_| editing_outline.set(!editing_outline()),
+                        if editing_outline() { "Done" } else { "Edit Outline" }
+                    }
+                }
+                if editing_outline() {
+                    OutlineEditor {
+                        book_id: share_book_id.clone(),
+                        chapters,
+                        selected_chapter,
+                    }
+                } else {
+                    ul {
+                        class: "space-y-4",
+                        for (index, chapter) in chapters().into_iter().enumerate() {
+                            li {
+                                class: format!("flex items-center p-3 rounded-lg cursor-pointer {}",
+                                    if chapter.id == selected_chapter().unwrap().id {
+                                        "bg-gray-500 text-white font-semibold"
+                                    } else {
+                                        "hover:bg-gray-200 dark:hover:bg-dark-800"
+                                    }),
+                                onclick: move |_| handle_chapter_click(chapter.clone()),
+                                div {
+                                    class: "w-8 h-8 flex items-center justify-center rounded-full border-2 border-blue-500 mr-4",
+                                    "{index + 1}"
+                                },
+
+                                div {
+                                    class: "flex-1 hidden sm:block",
+                                    h4 { class: "text-lg", "{chapter.title}" }
+                                    p { class: "text-sm text-blue-500", "{chapter.estimated_duration} minutes" }
+                                    if let Some(entry) = progress().get(&chapter.id.to_hex()) {
+                                        if entry.completed {
+                                            p { class: "text-xs text-green-600", i { class: "fas fa-check mr-1" } "Read" }
+                                        } else {
+                                            p { class: "text-xs text-gray-400", "{(entry.scroll_position * 100.0).round()}% read" }
+                                        }
+                                    }
+                                }
                             }
                         }
                     }
@@ -106,13 +284,32 @@ pub fn ReadBookPanel(book_id: String) -> Element {
             }
 
             div {
+                id: "reader-content",
                 class: "flex-1 p-6 overflow-y-auto",
                 if let Some(chapter) = selected_chapter() {
-                    h2 { class: "text-2xl font-bold mb-4", "{chapter.title}" }
+                    div { class: "flex items-start justify-between gap-4",
+                        h2 { class: "text-2xl font-bold mb-4", "{chapter.title}" }
+                        if !share_book_id.is_empty() {
+                            button {
+                                class: "shrink-0 p-2 rounded-lg border border-blue-500 text-blue-500 hover:bg-blue-500 hover:text-white",
+                                onclick: move |_| quiz_mode.set(!quiz_mode()),
+                                if quiz_mode() { "Back to reading" } else { "Quiz mode" }
+                            }
+                        }
+                    }
                     p { class: "text-sm text-blue-500 mb-6", "{chapter.estimated_duration} minutes" }
-                    div {
-                        class: "prose dark:prose-invert",
-                        dangerous_inner_html: if chapter.html.is_empty() {chapter.markdown} else {chapter.html},
+                    if quiz_mode() {
+                        ChapterQuiz { chapter_id: chapter.id.to_hex() }
+                    } else {
+                        div {
+                            id: CHAPTER_BODY_ID,
+                            class: "prose dark:prose-invert",
+                            dangerous_inner_html: if chapter.html.is_empty() {chapter.markdown} else {chapter.html},
+                        }
+                        if !share_book_id.is_empty() {
+                            ChapterIllustrator { chapters, selected_chapter }
+                            ChapterAnnotations { chapter_id: chapter.id.to_hex() }
+                        }
                     }
                 } else {
                     p {
@@ -138,3 +335,11 @@ pub fn ReadBookPanel(book_id: String) -> Element {
         }
     }
 }
+
+fn initial_chapter(chapters: &[Chapter], chapter_id: &str) -> Option<Chapter> {
+    chapters
+        .iter()
+        .find(|chapter| chapter.id.to_hex() == chapter_id)
+        .or_else(|| chapters.first())
+        .cloned()
+}
@@ -0,0 +1,169 @@
+use crate::components::dashboard::fields::select::SelectField;
+use crate::server::book::controller::{
+    create_share_link, get_book_for_user, publish_book, revoke_share_link,
+};
+use crate::server::book::request::{
+    CreateShareLinkRequest, GetBookForUserRequest, PublishBookRequest, RevokeShareLinkRequest,
+};
+use dioxus::prelude::*;
+use gloo_storage::SessionStorage;
+use gloo_storage::Storage;
+
+#[component]
+pub fn SharePanel(book_id: String) -> Element {
+    let book_id = use_signal(|| book_id);
+    let mut published = use_signal(|| false);
+    let mut slug = use_signal(|| None::<String>);
+    let mut share_token = use_signal(|| None::<String>);
+    let mut expires_at = use_signal(|| None::<String>);
+    let expiry = use_signal(|| "7".to_string());
+    let mut error_message = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = get_book_for_user(GetBookForUserRequest {
+            token,
+            book_id: book_id(),
+        })
+        .await
+        {
+            published.set(response.data.published);
+            slug.set(response.data.slug);
+            share_token.set(response.data.share_token);
+            expires_at.set(
+                response
+                    .data
+                    .share_expires_at
+                    .map(|date| date.format("%B %d, %Y").to_string()),
+            );
+        }
+    });
+
+    let handle_publish = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match publish_book(PublishBookRequest {
+                token,
+                book_id: book_id(),
+                published: !published(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    published.set(response.data.published);
+                    slug.set(Some(response.data.slug));
+                }
+                Err(err) => error_message.set(Some(format!("Error publishing book: {:?}", err))),
+            }
+        });
+    };
+
+    let handle_create_link = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match create_share_link(CreateShareLinkRequest {
+                token,
+                book_id: book_id(),
+                expires_in_days: expiry().parse().ok(),
+            })
+            .await
+            {
+                Ok(response) => {
+                    slug.set(Some(response.data.slug));
+                    share_token.set(response.data.share_token);
+                    expires_at.set(
+                        response
+                            .data
+                            .expires_at
+                            .map(|date| date.format("%B %d, %Y").to_string()),
+                    );
+                }
+                Err(err) => {
+                    error_message.set(Some(format!("Error creating share link: {:?}", err)))
+                }
+            }
+        });
+    };
+
+    let handle_revoke = move |_| {
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match revoke_share_link(RevokeShareLinkRequest {
+                token,
+                book_id: book_id(),
+            })
+            .await
+            {
+                Ok(_) => {
+                    share_token.set(None);
+                    expires_at.set(None);
+                }
+                Err(err) => {
+                    error_message.set(Some(format!("Error revoking share link: {:?}", err)))
+                }
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "p-4 mb-4 rounded-lg dark:bg-gray-800 bg-gray-100 space-y-4",
+            h3 { class: "text-lg font-semibold", "Share" }
+            if let Some(error) = error_message() {
+                p { class: "text-red-600", "{error}" }
+            }
+
+            div { class: "flex flex-wrap items-center gap-4",
+                button {
+                    class: "bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                    onclick: handle_publish,
+                    if published() { "Unpublish" } else { "Publish" }
+                }
+                if published() {
+                    if let Some(slug) = slug() {
+                        a {
+                            href: "/b/{slug}",
+                            target: "_blank",
+                            class: "text-blue-500 hover:underline",
+                            "/b/{slug}"
+                        }
+                    }
+                }
+            }
+
+            div { class: "flex flex-wrap items-end gap-4",
+                SelectField {
+                    label: "Link expires after (days)",
+                    options: vec!["1", "7", "30", "never"],
+                    selected: expiry,
+                }
+                button {
+                    class: "bg-green-500 text-white px-4 py-2 rounded",
+                    onclick: handle_create_link,
+                    if share_token().is_some() { "Regenerate Link" } else { "Create Link" }
+                }
+                if share_token().is_some() {
+                    button {
+                        class: "bg-red-500 text-white px-4 py-2 rounded",
+                        onclick: handle_revoke,
+                        "Revoke Link"
+                    }
+                }
+            }
+
+            if let (Some(slug), Some(token)) = (slug(), share_token()) {
+                p { class: "text-sm",
+                    a {
+                        href: "/b/{slug}?token={token}",
+                        target: "_blank",
+                        class: "text-blue-500 hover:underline break-all",
+                        "/b/{slug}?token={token}"
+                    }
+                    match expires_at() {
+                        Some(date) => rsx! { span { class: "text-gray-500 ml-2", "(expires {date})" } },
+                        None => rsx! { span { class: "text-gray-500 ml-2", "(never expires)" } },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::components::spinner::Spinner;
+use crate::components::spinner::SpinnerSize;
+use crate::server::study::controller::{get_study_queue, review_card};
+use crate::server::study::request::{ReviewCardRequest, StudyQueueRequest};
+use crate::server::study::response::StudyQueue;
+use dioxus::prelude::*;
+
+/// Self-grading buttons and the SM-2 grade each one records.
+const GRADES: [(&str, u8, &str); 4] = [
+    ("Again", 1, "bg-red-500"),
+    ("Hard", 3, "bg-yellow-500"),
+    ("Good", 4, "bg-green-500"),
+    ("Easy", 5, "bg-blue-500"),
+];
+
+#[component]
+pub fn StudyPanel(user_token: Signal<String>) -> Element {
+    let mut queue = use_signal(StudyQueue::default);
+    let mut position = use_signal(|| 0usize);
+    let mut revealed = use_signal(|| false);
+    let mut reviewed = use_signal(|| 0usize);
+    let mut loading = use_signal(|| true);
+    let mut error_message = use_signal(|| None::<String>);
+
+    let mut load_queue = move || {
+        loading.set(true);
+        spawn(async move {
+            match get_study_queue(StudyQueueRequest {
+                token: user_token(),
+                limit: None,
+            })
+            .await
+            {
+                Ok(response) => {
+                    queue.set(response.data);
+                    position.set(0);
+                    revealed.set(false);
+                }
+                Err(err) => error_message.set(Some(format!("Error loading cards: {:?}", err))),
+            }
+            loading.set(false);
+        });
+    };
+
+    use_effect(move || {
+        if !user_token().is_empty() {
+            load_queue();
+        }
+    });
+
+    let handle_grade = move |grade: u8| {
+        let Some(card) = queue().cards.get(position()).cloned() else {
+            return;
+        };
+        spawn(async move {
+            match review_card(ReviewCardRequest {
+                token: user_token(),
+                quiz_id: card.quiz_id,
+                card_id: card.card_id,
+                grade,
+            })
+            .await
+            {
+                Ok(_) => {
+                    // Forgotten cards come back at the end of this session.
+                    if grade < 3 {
+                        let mut retry = queue().cards[position()].clone();
+                        retry.due = None;
+                        queue.write().cards.push(retry);
+                    }
+                    reviewed.set(reviewed() + 1);
+                    position.set(position() + 1);
+                    revealed.set(false);
+                }
+                Err(err) => error_message.set(Some(format!("Error saving review: {:?}", err))),
+            }
+        });
+    };
+
+    let current = queue().cards.get(position()).cloned();
+    let remaining = queue().cards.len().saturating_sub(position());
+
+    rsx! {
+        div { class: "p-4 dark:bg-gray-800 dark:text-white bg-white text-gray-900 space-y-4",
+            div { class: "flex flex-wrap items-center justify-between gap-4",
+                h2 { class: "text-xl font-semibold", "Study" }
+                p { class: "text-sm text-gray-500",
+                    "{queue().due_count} due · {queue().new_count} new · {reviewed} reviewed this session"
+                }
+            }
+            if let Some(error) = error_message() {
+                p { class: "text-red-600", "{error}" }
+            }
+
+            if loading() {
+                p { class: "flex items-center space-x-2",
+                    Spinner {
+                        aria_label: "Loading spinner".to_string(),
+                        size: SpinnerSize::Md,
+                        dark_mode: true,
+                    }
+                    span { "Loading cards..." }
+                }
+            } else if let Some(card) = current {
+                div { class: "max-w-2xl mx-auto space-y-4",
+                    p { class: "text-sm text-blue-500", "{card.book_title} · {card.chapter_title} · {remaining} left" }
+                    div { class: "p-8 min-h-48 rounded-lg shadow dark:bg-gray-700 bg-gray-100 space-y-6",
+                        p { class: "text-xl font-semibold", "{card.front}" }
+                        if revealed() {
+                            hr { class: "border-gray-300 dark:border-gray-600" }
+                            p { class: "text-lg", "{card.back}" }
+                        }
+                    }
+                    if revealed() {
+                        div { class: "grid grid-cols-4 gap-2",
+                            for (label, grade, color) in GRADES {
+                                button {
+                                    key: "{label}",
+                                    class: "{color} text-white px-4 py-2 rounded",
+                                    onclick: move |_| handle_grade(grade),
+                                    "{label}"
+                                }
+                            }
+                        }
+                    } else {
+                        button {
+                            class: "w-full bg-blue-500 text-white px-4 py-2 rounded dark:bg-blue-600",
+                            onclick: move |_| revealed.set(true),
+                            "Show answer"
+                        }
+                    }
+                }
+            } else {
+                div { class: "text-center space-y-2",
+                    p { "You're all caught up! Generate flashcards from a chapter's quiz mode to add more cards." }
+                    button {
+                        class: "text-blue-500 hover:underline",
+                        onclick: move |_| load_queue(),
+                        "Check again"
+                    }
+                }
+            }
+        }
+    }
+}
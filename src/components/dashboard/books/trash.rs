@@ -0,0 +1,122 @@
+use crate::components::dashboard::books::list::CACHE_KEY;
+use crate::components::spinner::Spinner;
+use crate::components::spinner::SpinnerSize;
+use crate::server::book::controller::{get_trash, purge_book, restore_book};
+use crate::server::book::model::{Book, TRASH_RETENTION_DAYS};
+use crate::server::book::request::{BookRequest, GetBooksForUserRequest};
+use chrono::{Duration, Utc};
+use dioxus::prelude::*;
+use gloo_storage::{LocalStorage, Storage};
+
+#[component]
+pub fn TrashPanel(user_token: Signal<String>) -> Element {
+    let mut books = use_signal(Vec::<Book>::new);
+    let mut loading = use_signal(|| true);
+    let mut error_message = use_signal(|| None::<String>);
+
+    let _ = use_resource(move || async move {
+        match get_trash(GetBooksForUserRequest {
+            token: user_token(),
+        })
+        .await
+        {
+            Ok(response) => {
+                loading.set(false);
+                books.set(response.data);
+            }
+            Err(_) => {
+                loading.set(false);
+            }
+        }
+    });
+
+    let handle_action = move |action: &'static str, book_id: String| {
+        spawn(async move {
+            let req = BookRequest {
+                token: user_token(),
+                book_id: book_id.clone(),
+            };
+            let result = match action {
+                "restore" => restore_book(req).await.map(|_| ()),
+                "purge" => purge_book(req).await.map(|_| ()),
+                _ => Err(ServerFnError::new("Invalid action")),
+            };
+
+            match result {
+                Ok(_) => {
+                    LocalStorage::delete(CACHE_KEY);
+                    books.set(
+                        books()
+                            .into_iter()
+                            .filter(|book| book.id.to_hex() != book_id)
+                            .collect(),
+                    );
+                }
+                Err(err) => error_message.set(Some(format!("Trash action failed: {:?}", err))),
+            }
+        });
+    };
+
+    rsx! {
+        div { class: "p-4 dark:bg-gray-800 dark:text-white bg-white text-gray-900",
+            h2 { class: "text-xl font-semibold mb-2", "Trash" }
+            p { class: "text-sm text-gray-500 mb-4",
+                "Books in the trash are deleted permanently after {TRASH_RETENTION_DAYS} days."
+            }
+            if let Some(error) = error_message() {
+                p { class: "text-red-600", "{error}" }
+            }
+
+            if books.len() > 0 {
+                ul { class: "space-y-3",
+                    for book in books() {
+                        li {
+                            key: "{book.id}",
+                            class: "flex items-center justify-between p-4 shadow rounded-lg dark:bg-gray-700 bg-gray-100",
+                            div {
+                                h3 { class: "text-lg font-bold", "{book.title}" }
+                                if let Some(deleted_at) = book.deleted_at {
+                                    p { class: "text-sm text-gray-500",
+                                        "Deleted {deleted_at.format(\"%B %d, %Y\")} · purged in {(deleted_at + Duration::days(TRASH_RETENTION_DAYS) - Utc::now()).num_days().max(0)} days"
+                                    }
+                                }
+                            }
+                            div { class: "flex space-x-2",
+                                button {
+                                    class: "bg-green-500 text-white px-4 py-2 rounded",
+                                    onclick: {
+                                        let book_id = book.id.to_hex();
+                                        move |_| handle_action("restore", book_id.clone())
+                                    },
+                                    "Restore"
+                                }
+                                button {
+                                    class: "bg-red-500 text-white px-4 py-2 rounded",
+                                    onclick: {
+                                        let book_id = book.id.to_hex();
+                                        move |_| handle_action("purge", book_id.clone())
+                                    },
+                                    "Delete Forever"
+                                }
+                            }
+                        }
+                    }
+                }
+            } else {
+                p {
+                    class: "flex items-center space-x-2 px-4 py-2 rounded",
+                    if loading() {
+                        Spinner {
+                            aria_label: "Loading spinner".to_string(),
+                            size: SpinnerSize::Md,
+                            dark_mode: true,
+                        }
+                        span { "Loading trash..." }
+                    } else {
+                        span { "Trash is empty." }
+                    }
+                }
+            }
+        }
+    }
+}
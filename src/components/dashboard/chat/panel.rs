@@ -4,6 +4,7 @@ use crate::components::dashboard::books::list::CACHE_TIMEOUT;
 use crate::components::dashboard::books::read::CachedChaptersData;
 use crate::components::dashboard::books::read::CHAPTERS_CACHE_KEY;
 use crate::components::dashboard::books::read::CHAPTERS_CACHE_TIMEOUT;
+use crate::components::dashboard::quota::use_quota;
 use crate::server::book::controller::get_books_for_user;
 use crate::server::book::controller::get_chapters_for_book;
 use crate::server::book::model::Book;
@@ -46,6 +47,7 @@ fn truncate(text: String, max_length: usize) -> String {
 pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>) -> Element {
     let mut messages = use_signal(Vec::<Message>::new);
     let mut input_query = use_signal(|| "".to_string());
+    let mut quota = use_quota();
     let mut selected_book = use_signal(|| None::<Book>);
     let mut selected_chapter = use_signal(|| None::<Chapter>);
     let mut chapters = use_signal(Vec::<Chapter>::new);
@@ -110,6 +112,7 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
             }
 
             if let Ok(response) = get_chapters_for_book(GetChaptersContentRequest {
+                token: user_token(),
                 book_id: book_id.clone(),
             })
             .await
@@ -205,6 +208,7 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
 
                     match response {
                         Ok(resp_message) => {
+                            quota.write().chat_messages_today += 1;
                             let mut current_messages = messages();
                             current_messages.push(resp_message.data);
                             thinking.set(false);
@@ -230,6 +234,7 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
                 spawn({
                     async move {
                         if let Ok(response) = get_chapters_for_book(GetChaptersContentRequest {
+                            token: user_token(),
                             book_id: book.id.to_string(),
                         })
                         .await
@@ -296,6 +301,9 @@ pub fn ChatPanel(conversation_id: Signal<ObjectId>, user_token: Signal<String>)
                     onclick: move |_| handle_send_query(),
                     "Send"
                 }
+                span { class: "text-xs text-gray-500 whitespace-nowrap",
+                    "{quota().chat_messages_remaining()} left today"
+                }
             }
 
             div {
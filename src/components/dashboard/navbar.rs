@@ -1,7 +1,11 @@
 use crate::components::spinner::Spinner;
 use crate::components::spinner::SpinnerSize;
+use crate::router::Route;
 use crate::server::auth::controller::about_me;
 use crate::server::auth::model::User;
+use crate::server::search::controller::search_chapters;
+use crate::server::search::request::SearchChaptersRequest;
+use crate::server::search::response::SearchHit;
 use crate::theme::ThemeToggle;
 use dioxus::prelude::*;
 use gloo_storage::Storage;
@@ -14,6 +18,10 @@ pub fn Navbar() -> Element {
     let navigator = use_navigator();
 
     let mut user_data = use_signal(|| None::<User>);
+    let mut search_query = use_signal(String::new);
+    let mut search_results = use_signal(Vec::<SearchHit>::new);
+    let mut searching = use_signal(|| false);
+    let mut show_results = use_signal(|| false);
 
     use_effect(move || {
         spawn(async move {
@@ -51,10 +59,79 @@ pub fn Navbar() -> Element {
         }
     };
 
+    let handle_search = move |e: Event<FormData>| {
+        e.prevent_default();
+        let query = search_query();
+        if query.trim().is_empty() {
+            return;
+        }
+
+        searching.set(true);
+        show_results.set(true);
+        spawn(async move {
+            let token: String = SessionStorage::get("jwt").unwrap_or_default();
+            match search_chapters(SearchChaptersRequest {
+                token,
+                query,
+                limit: None,
+            })
+            .await
+            {
+                Ok(response) => search_results.set(response.data),
+                Err(_) => search_results.set(Vec::new()),
+            }
+            searching.set(false);
+        });
+    };
+
     rsx! {
         div { class: "flex justify-between items-center mb-4 border-b shadow-sm p-2 dark:border-gray-700",
             h1 { class: "text-2xl font-semibold", "Dashboard" }
 
+            div { class: "relative flex-1 max-w-lg mx-4",
+                form { onsubmit: handle_search,
+                    input {
+                        class: "block w-full p-2 border rounded-md shadow-sm dark:bg-gray-900 dark:border-gray-700",
+                        r#type: "search",
+                        placeholder: "Search all chapters...",
+                        value: "{search_query}",
+                        oninput: move |e| search_query.set(e.value()),
+                        onfocus: move |_| show_results.set(!search_results().is_empty()),
+                    }
+                }
+                if show_results() {
+                    div { class: "absolute z-20 left-0 right-0 mt-2 max-h-96 overflow-y-auto shadow-lg rounded-lg dark:bg-gray-800 bg-white",
+                        if searching() {
+                            div { class: "flex items-center space-x-2 px-4 py-2",
+                                Spinner {
+                                    aria_label: "Loading spinner".to_string(),
+                                    size: SpinnerSize::Sm,
+                                    dark_mode: true,
+                                }
+                                span { "Searching..." }
+                            }
+                        } else if search_results().is_empty() {
+                            p { class: "px-4 py-2 text-sm text-gray-500", "No chapters match your search." }
+                        } else {
+                            for hit in search_results() {
+                                Link {
+                                    key: "{hit.chapter_id}",
+                                    to: Route::ReadBook { id: hit.book_id.clone(), chapter: hit.chapter_id.clone() },
+                                    class: "block px-4 py-2 border-b dark:border-gray-700 hover:bg-gray-100 dark:hover:bg-gray-700",
+                                    onclick: move |_| show_results.set(false),
+                                    p { class: "text-sm font-semibold", "{hit.chapter_title}" }
+                                    p { class: "text-xs text-blue-500", "{hit.book_title} · Chapter {hit.position + 1}" }
+                                    p {
+                                        class: "text-sm text-gray-600 dark:text-gray-300",
+                                        dangerous_inner_html: "{hit.snippet}",
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
             div { class: "flex items-center space-x-4",
                 ThemeToggle {}
 
@@ -1,5 +1,9 @@
 use crate::server::auth::model::User;
+use crate::server::entitlement::model::Plan;
+use crate::server::subscription::controller::open_billing_portal;
+use crate::server::subscription::request::BillingPortalRequest;
 use dioxus::prelude::*;
+use dioxus_logger::tracing;
 
 #[derive(Props, Clone, PartialEq)]
 pub struct ProfileDetailsProps {
@@ -9,6 +13,23 @@ pub struct ProfileDetailsProps {
 
 #[component]
 pub fn ProfileDetails(props: ProfileDetailsProps) -> Element {
+    let navigator = use_navigator();
+    let mut opening_portal = use_signal(|| false);
+
+    let open_portal = move |_| {
+        let token = props.user_token.clone();
+        opening_portal.set(true);
+        spawn(async move {
+            match open_billing_portal(BillingPortalRequest { token }).await {
+                Ok(response) => {
+                    navigator.push(response.data);
+                }
+                Err(err) => tracing::error!("Opening billing portal failed: {:?}", err),
+            }
+            opening_portal.set(false);
+        });
+    };
+
     rsx!(
         div { class: "grid grid-cols-2 gap-4 md:grid-cols-3",
             div { class: "flex items-center space-x-2",
@@ -24,8 +45,16 @@ pub fn ProfileDetails(props: ProfileDetailsProps) -> Element {
                 span { "{props.user.email}" }
             }
             div { class: "flex items-center space-x-2",
-                span { class: "font-bold", "Role:" }
-                span { "{props.user.role}" }
+                span { class: "font-bold", "Plan:" }
+                span { "{Plan::from_role(&props.user.role).label()}" }
+                if props.user.stripe_customer.is_some() {
+                    button {
+                        class: "text-sm text-blue-500 hover:underline disabled:opacity-50",
+                        disabled: opening_portal(),
+                        onclick: open_portal,
+                        "Manage billing"
+                    }
+                }
             }
             div { class: "flex items-center space-x-2",
                 span { class: "font-bold", "Verified:" }
@@ -0,0 +1,35 @@
+use crate::server::entitlement::controller::get_quota;
+use crate::server::entitlement::response::QuotaResponse;
+use dioxus::prelude::*;
+use gloo_storage::{SessionStorage, Storage};
+
+/// The signed-in user's plan and what they've used of it, loaded once.
+/// Callers bump the counters themselves after spending quota.
+pub fn use_quota() -> Signal<QuotaResponse> {
+    let mut quota = use_signal(QuotaResponse::default);
+    let _ = use_resource(move || async move {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if let Ok(response) = get_quota(token).await {
+            quota.set(response.data);
+        }
+    });
+    quota
+}
+
+#[component]
+pub fn QuotaSummary(quota: Signal<QuotaResponse>) -> Element {
+    let quota = quota();
+    let limits = quota.plan.limits();
+
+    rsx! {
+        div { class: "flex flex-wrap gap-x-4 gap-y-1 p-3 mb-4 rounded-lg text-sm dark:bg-gray-900 bg-gray-100",
+            span { class: "font-semibold", "{quota.plan.label()} plan" }
+            span { "{quota.books_remaining()} of {limits.books_per_month} books left this month" }
+            span { "up to {limits.chapters_per_book} chapters per book" }
+            span { "{quota.chat_messages_remaining()} of {limits.chat_messages_per_day} chat messages left today" }
+            if let Some(trial_end) = quota.trial_ends_at {
+                span { class: "text-blue-500", "free trial until {trial_end.format(\"%B %d, %Y\")}" }
+            }
+        }
+    }
+}
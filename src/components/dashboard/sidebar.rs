@@ -8,6 +8,9 @@ pub enum Tab {
     CreateBook,
     ReadBook,
     EditProfile,
+    Trash,
+    Notebook,
+    Study,
 }
 
 #[component]
@@ -70,6 +73,39 @@ pub fn Sidebar(active_tab: Signal<Tab>, navigate: bool) -> Element {
                 span { class: "hidden md:inline", "Read Book" }
             }
 
+            div { class: tab_style(Tab::Study),
+                onclick: move |_| {
+                    if navigate {
+                        navigator.push("/dashboard");
+                    }
+                    active_tab.set(Tab::Study);
+                },
+                i { class: "fas fa-graduation-cap text-2xl" },
+                span { class: "hidden md:inline", "Study" }
+            }
+
+            div { class: tab_style(Tab::Notebook),
+                onclick: move |_| {
+                    if navigate {
+                        navigator.push("/dashboard");
+                    }
+                    active_tab.set(Tab::Notebook);
+                },
+                i { class: "fas fa-sticky-note text-2xl" },
+                span { class: "hidden md:inline", "Notebook" }
+            }
+
+            div { class: tab_style(Tab::Trash),
+                onclick: move |_| {
+                    if navigate {
+                        navigator.push("/dashboard");
+                    }
+                    active_tab.set(Tab::Trash);
+                },
+                i { class: "fas fa-trash-alt text-2xl" },
+                span { class: "hidden md:inline", "Trash" }
+            }
+
             div { class: tab_style(Tab::EditProfile),
                 onclick: move |_| {
                     if navigate {
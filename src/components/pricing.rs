@@ -1,23 +1,70 @@
 use crate::components::common::header::Header;
 use crate::components::toast::manager::{ToastManager, ToastType};
+use crate::server::catalog::controller::get_plan_catalog;
+use crate::server::catalog::model::CatalogPlan;
+use crate::server::entitlement::model::Plan;
+use crate::server::export::request::ExportFormat;
 use crate::server::subscription::controller::start_stripe_payment;
+use crate::server::subscription::model::PaymentMethod;
 use crate::server::subscription::request::StripePaymentRequest;
 use chrono::Duration;
 use dioxus::prelude::*;
 use dioxus_logger::tracing;
 use gloo_storage::{SessionStorage, Storage};
 use i18nrs::dioxus::I18nContext;
-use serde_json::from_str;
-use serde_json::Value;
 
-#[derive(Props, Clone, PartialEq)]
-struct PricingOption {
-    title: String,
-    price: &'static str,
-    description: String,
-    features: Vec<String>,
-    highlight: bool,
-    plan_id: Option<&'static str>,
+/// Plans that ship with the app have translated text; ones added to the
+/// catalog later show what's stored with them.
+const TRANSLATED_PLANS: [&str; 3] = ["free", "monthly", "yearly"];
+
+fn plan_text(plan: &CatalogPlan, t: impl Fn(&str) -> String) -> (String, String) {
+    if TRANSLATED_PLANS.contains(&plan.id.as_str()) {
+        (
+            t(&format!("pricing.{}.title", plan.id)),
+            t(&format!("pricing.{}.description", plan.id)),
+        )
+    } else {
+        (plan.name.clone(), plan.description.clone())
+    }
+}
+
+fn price_text(plan: &CatalogPlan, t: impl Fn(&str) -> String) -> String {
+    match plan.interval {
+        Some(interval) => format!(
+            "{}/{}",
+            plan.amount_label(),
+            t(&format!("pricing.interval.{}", interval.label()))
+        ),
+        None => plan.amount_label(),
+    }
+}
+
+/// The bullets every plan gets from its entitlement limits, so they can't
+/// drift from what's enforced.
+fn limit_features(plan: Plan, t: impl Fn(&str) -> String) -> Vec<String> {
+    let limits = plan.limits();
+    let formats = limits
+        .export_formats
+        .iter()
+        .map(|format| match format {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Html => "HTML",
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let models = if limits.models.iter().any(|model| model.contains("pro")) {
+        "pricing.features.models_pro"
+    } else {
+        "pricing.features.models_flash"
+    };
+    vec![
+        t("pricing.features.books").replace("{count}", &limits.books_per_month.to_string()),
+        t("pricing.features.chapters").replace("{count}", &limits.chapters_per_book.to_string()),
+        t(models),
+        t("pricing.features.export").replace("{formats}", &formats),
+        t("pricing.features.chat").replace("{count}", &limits.chat_messages_per_day.to_string()),
+        t("pricing.features.images").replace("{count}", &limits.ai_images_per_day.to_string()),
+    ]
 }
 
 #[component]
@@ -26,54 +73,40 @@ pub fn Pricing() -> Element {
     let navigator = use_navigator();
     let mut toasts_manager = use_context::<Signal<ToastManager>>();
 
-    let get_features = |key: &str| -> Vec<String> {
-        let raw = i18n().t(key);
-        from_str::<Vec<String>>(&raw).unwrap_or_else(|_| {
-            tracing::warn!("Invalid array format for key '{}'", key);
-            vec![]
-        })
-    };
+    let mut plans = use_signal(Vec::<CatalogPlan>::new);
+    let mut coupon = use_signal(String::new);
+    let mut method = use_signal(PaymentMethod::default);
+    let _ = use_resource(move || async move {
+        match get_plan_catalog().await {
+            Ok(response) => plans.set(response.data),
+            Err(err) => tracing::error!("Loading plans failed: {:?}", err),
+        }
+    });
 
-    let pricing_options = vec![
-        PricingOption {
-            title: i18n().t("pricing.free.title"),
-            price: "$0",
-            description: i18n().t("pricing.free.description"),
-            features: get_features("pricing.free.features"),
-            highlight: false,
-            plan_id: None,
-        },
-        PricingOption {
-            title: i18n().t("pricing.monthly.title"),
-            price: "$2/month",
-            description: i18n().t("pricing.monthly.description"),
-            features: get_features("pricing.monthly.features"),
-            highlight: true,
-            plan_id: Some(env!("STRIPE_PRICE_ONE")),
-        },
-        PricingOption {
-            title: i18n().t("pricing.yearly.title"),
-            price: "$100/year",
-            description: i18n().t("pricing.yearly.description"),
-            features: get_features("pricing.yearly.features"),
-            highlight: false,
-            plan_id: Some(env!("STRIPE_PRICE_TWO")),
-        },
-    ];
+    let t = move |key: &str| i18n().t(key).to_string();
 
-    let handle_plan_selection = move |plan: (Option<&'static str>, String)| {
-        if let Some(plan_id) = plan.0 {
+    let handle_plan_selection = move |plan: CatalogPlan| {
+        let token: String = SessionStorage::get("jwt").unwrap_or_default();
+        if token.is_empty() {
+            navigator.push("/login");
+        } else if plan.price_id.is_none() && plan.paypal_plan_id.is_none() {
+            // Signed in users already have the free plan.
+            navigator.push("/dashboard");
+        } else {
             spawn({
-                let plan_title = plan.1;
+                let plan_title = plan.name;
                 async move {
                     match start_stripe_payment(StripePaymentRequest {
-                        plan_id: plan_id.to_string(),
+                        token,
+                        plan_id: plan.id,
+                        coupon: Some(coupon()),
+                        method: method(),
                     })
                     .await
                     {
                         Ok(response) => {
                             SessionStorage::set("stripe", response.data.clone()).unwrap();
-                            SessionStorage::set("method", "stripe").unwrap();
+                            SessionStorage::set("method", method().as_str()).unwrap();
                             SessionStorage::set("plan", &plan_title).unwrap();
                             toasts_manager.set(
                                 toasts_manager()
@@ -87,15 +120,39 @@ pub fn Pricing() -> Element {
                             );
                             navigator.push(response.data);
                         }
-                        Err(err) => tracing::error!("Stripe payment initiation failed: {:?}", err),
+                        Err(err) => {
+                            tracing::error!("Payment initiation failed: {:?}", err);
+                            toasts_manager.set(
+                                toasts_manager()
+                                    .add_toast(
+                                        "Error".into(),
+                                        i18n().t("pricing.toast.error").into(),
+                                        ToastType::Error,
+                                        Some(Duration::seconds(5)),
+                                    )
+                                    .clone(),
+                            );
+                        }
                     }
                 }
             });
-        } else {
-            navigator.push("/login");
         }
     };
 
+    let paypal = plans().iter().any(|plan| plan.paypal_plan_id.is_some());
+    let cards = plans()
+        .into_iter()
+        .map(|plan| {
+            let text = plan_text(&plan, t);
+            let price = price_text(&plan, t);
+            let features = limit_features(plan.plan, t)
+                .into_iter()
+                .chain(plan.features.iter().cloned())
+                .collect::<Vec<_>>();
+            (plan, text, price, features)
+        })
+        .collect::<Vec<_>>();
+
     rsx! {
         section {
             id: "pricing",
@@ -111,9 +168,28 @@ pub fn Pricing() -> Element {
                     title: i18n().t("pricing.header.title"),
                     subtitle: i18n().t("pricing.header.subtitle")
                 }
+                input {
+                    class: "mb-8 w-full max-w-xs px-3 py-2 border rounded-md text-center dark:bg-gray-800 dark:border-gray-600",
+                    r#type: "text",
+                    placeholder: i18n().t("pricing.coupon"),
+                    value: "{coupon}",
+                    oninput: move |e| coupon.set(e.value()),
+                }
+                if paypal {
+                    div { class: "mb-8 flex justify-center items-center gap-2",
+                        label { r#for: "payment-method", "{i18n().t(\"pricing.method.label\")}" }
+                        select {
+                            id: "payment-method",
+                            class: "px-3 py-2 border rounded-md dark:bg-gray-800 dark:border-gray-600",
+                            onchange: move |e| method.set(if e.value() == "paypal" { PaymentMethod::Paypal } else { PaymentMethod::Stripe }),
+                            option { value: "stripe", selected: method() == PaymentMethod::Stripe, "{i18n().t(\"pricing.method.stripe\")}" }
+                            option { value: "paypal", selected: method() == PaymentMethod::Paypal, "{i18n().t(\"pricing.method.paypal\")}" }
+                        }
+                    }
+                }
                 div { class: "grid grid-cols-1 md:grid-cols-3 gap-8",
 
-                    for option in pricing_options {
+                    for (option, (name, description), price, features) in cards {
                         div { class: format!("p-6 rounded-lg border {}",
                             if option.highlight { "border-blue-500 bg-blue-50 relative shadow-lg text-black" } else { "border-gray-200" }),
                             if option.highlight {
@@ -123,12 +199,17 @@ pub fn Pricing() -> Element {
                                 }
                             }
 
-                            h3 { class: "text-xl font-semibold", "{option.title}" },
-                            p { class: "text-3xl font-bold mt-2 mb-4", "{option.price}" },
-                            p { class: "mb-4 text-gray-600", "{option.description}" },
+                            h3 { class: "text-xl font-semibold", "{name}" },
+                            p { class: "text-3xl font-bold mt-2 mb-4", "{price}" },
+                            if option.trial_days > 0 {
+                                p { class: "mb-2 text-sm font-semibold text-blue-500",
+                                    {i18n().t("pricing.trial").replace("{days}", &option.trial_days.to_string())}
+                                }
+                            }
+                            p { class: "mb-4 text-gray-600", "{description}" },
 
                             ul { class: "text-left space-y-2",
-                                for feature in option.features.iter() {
+                                for feature in features {
                                     li { class: "flex items-center",
                                         span { class: "text-blue-500 mr-2", "✓" },
                                         "{feature}"
@@ -141,7 +222,7 @@ pub fn Pricing() -> Element {
                                     if option.highlight { "bg-blue-500 text-white hover:bg-blue-600" } else { "bg-gray-300 text-gray-700 hover:bg-gray-400" }),
                                 onclick: move |e: Event<MouseData>| {
                                     e.stop_propagation();
-                                    handle_plan_selection((option.plan_id, option.title.clone()));
+                                    handle_plan_selection(option.clone());
                                 },
                                 "{i18n().t(\"pricing.select\")}"
                             }
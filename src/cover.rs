@@ -0,0 +1,327 @@
+use anyhow::Result;
+use bson::oid::ObjectId;
+use dioxus_logger::tracing;
+use futures_util::future::{join_all, BoxFuture};
+use std::sync::Arc;
+
+use crate::image_model::{get_image_model, ImageModel};
+use crate::server::common::html::escape_html;
+use crate::server::cover::model::{CoverCredit, CoverSource};
+use crate::server::cover::response::CoverCandidate;
+use crate::storage;
+use crate::unsplash;
+
+/// What a provider knows about the book it designs a cover for.
+pub struct CoverQuery {
+    /// Whom AI-painted covers are billed to.
+    pub user: ObjectId,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub topic: String,
+    /// Storage prefix for candidate images.
+    pub prefix: String,
+    pub count: usize,
+}
+
+/// A source of cover images. Providers store the images they offer under
+/// the query's prefix, so every candidate is served from our media route.
+pub trait CoverProvider: Send + Sync {
+    fn source(&self) -> CoverSource;
+
+    fn covers<'a>(&'a self, query: &'a CoverQuery) -> BoxFuture<'a, Result<Vec<CoverCandidate>>>;
+}
+
+/// Stores a candidate image, with its credit in a JSON file next to it so
+/// it can be recovered when the candidate is chosen.
+async fn store_candidate(
+    query: &CoverQuery,
+    source: CoverSource,
+    bytes: &[u8],
+    extension: &str,
+    credit: Option<CoverCredit>,
+) -> Result<CoverCandidate> {
+    let name = storage::random_name();
+    if let Some(credit) = &credit {
+        storage::put(
+            &format!("{}/{}.json", query.prefix, name),
+            &serde_json::to_vec(credit)?,
+        )
+        .await?;
+    }
+    let url = storage::put(&format!("{}/{}.{}", query.prefix, name, extension), bytes).await?;
+    Ok(CoverCandidate {
+        source,
+        url,
+        credit,
+    })
+}
+
+/// The credit stored next to a candidate image, if it has one.
+pub async fn candidate_credit(key: &str) -> Option<CoverCredit> {
+    let (stem, _) = key.rsplit_once('.')?;
+    let json = storage::get(&format!("{}.json", stem)).await.ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Portrait photos from an Unsplash search on the book's topic, downloaded
+/// so the cover doesn't depend on a hotlink.
+pub struct UnsplashProvider {
+    pub access_key: String,
+}
+
+impl CoverProvider for UnsplashProvider {
+    fn source(&self) -> CoverSource {
+        CoverSource::Unsplash
+    }
+
+    fn covers<'a>(&'a self, query: &'a CoverQuery) -> BoxFuture<'a, Result<Vec<CoverCandidate>>> {
+        Box::pin(async move {
+            let photos =
+                unsplash::search_photos(&self.access_key, &query.topic, query.count).await?;
+            let downloads = join_all(photos.iter().map(|photo| async move {
+                let bytes = unsplash::download_image(&photo.image_url).await?;
+                let extension = storage::image_extension(&bytes).unwrap_or("jpg");
+                store_candidate(
+                    query,
+                    CoverSource::Unsplash,
+                    &bytes,
+                    extension,
+                    Some(photo.credit.clone()),
+                )
+                .await
+            }))
+            .await;
+
+            let mut candidates = Vec::new();
+            for download in downloads {
+                match download {
+                    Ok(candidate) => candidates.push(candidate),
+                    Err(err) => tracing::warn!("downloading an Unsplash photo failed: {}", err),
+                }
+            }
+            Ok(candidates)
+        })
+    }
+}
+
+/// Background, text and accent colors of a typographic cover.
+const PALETTES: &[(&str, &str, &str)] = &[
+    ("#1f2937", "#f9fafb", "#f59e0b"),
+    ("#0f766e", "#ecfeff", "#fde68a"),
+    ("#7c2d12", "#fff7ed", "#fdba74"),
+    ("#1e3a8a", "#eff6ff", "#93c5fd"),
+    ("#f5f5f4", "#1c1917", "#dc2626"),
+    ("#4c1d95", "#f5f3ff", "#c4b5fd"),
+];
+
+const COVER_WIDTH: u32 = 600;
+const COVER_HEIGHT: u32 = 900;
+
+/// Greedy word wrap on character counts; good enough for a display face.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+fn text_block(lines: &[String], x: u32, y: u32, size: u32, anchor: &str, style: &str) -> String {
+    let spans = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            format!(
+                r#"<tspan x="{}" dy="{}">{}</tspan>"#,
+                x,
+                if i == 0 { 0 } else { size * 6 / 5 },
+                escape_html(line)
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<text x="{}" y="{}" font-size="{}" text-anchor="{}" {}>{}</text>"#,
+        x, y, size, anchor, style, spans
+    )
+}
+
+/// An SVG cover with the title and subtitle set in type. `variant` picks the
+/// palette and alternates between a centered and a left-aligned layout.
+pub fn typographic_cover(title: &str, subtitle: Option<&str>, variant: usize) -> String {
+    let (background, foreground, accent) = PALETTES[variant % PALETTES.len()];
+    let (title_size, title_width) = if title.chars().count() > 48 {
+        (46, 20)
+    } else {
+        (62, 15)
+    };
+    let title_lines = wrap(title, title_width);
+    let subtitle_lines = subtitle.map(|text| wrap(text, 32)).unwrap_or_default();
+    let title_style = format!(
+        r#"fill="{}" font-family="Georgia, 'Times New Roman', serif" font-weight="bold""#,
+        foreground
+    );
+    let subtitle_style = format!(
+        r#"fill="{}" font-family="Helvetica, Arial, sans-serif""#,
+        accent
+    );
+    let title_height = title_lines.len() as u32 * title_size * 6 / 5;
+
+    let body = if variant % 2 == 0 {
+        let center = COVER_WIDTH / 2;
+        let top = (COVER_HEIGHT.saturating_sub(title_height) / 2)
+            .saturating_sub(40)
+            .max(title_size + 60);
+        format!(
+            r#"<rect x="40" y="40" width="{}" height="{}" fill="none" stroke="{accent}" stroke-width="3"/>{}<rect x="{}" y="{}" width="120" height="6" fill="{accent}"/>{}"#,
+            COVER_WIDTH - 80,
+            COVER_HEIGHT - 80,
+            text_block(
+                &title_lines,
+                center,
+                top,
+                title_size,
+                "middle",
+                &title_style
+            ),
+            center - 60,
+            top + title_height,
+            text_block(
+                &subtitle_lines,
+                center,
+                top + title_height + 60,
+                26,
+                "middle",
+                &subtitle_style
+            ),
+            accent = accent,
+        )
+    } else {
+        format!(
+            r#"<rect width="{}" height="220" fill="{accent}"/>{}<rect x="60" y="{}" width="6" height="{}" fill="{accent}"/>{}"#,
+            COVER_WIDTH,
+            text_block(&title_lines, 90, 320, title_size, "start", &title_style),
+            320 - title_size,
+            title_height,
+            text_block(
+                &subtitle_lines,
+                90,
+                340 + title_height + 30,
+                26,
+                "start",
+                &subtitle_style
+            ),
+            accent = accent,
+        )
+    };
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}"><rect width="{w}" height="{h}" fill="{}"/>{}</svg>"#,
+        background,
+        body,
+        w = COVER_WIDTH,
+        h = COVER_HEIGHT,
+    )
+}
+
+/// Renders typographic covers locally, so there is always something to pick.
+pub struct TypographicProvider;
+
+impl CoverProvider for TypographicProvider {
+    fn source(&self) -> CoverSource {
+        CoverSource::Typographic
+    }
+
+    fn covers<'a>(&'a self, query: &'a CoverQuery) -> BoxFuture<'a, Result<Vec<CoverCandidate>>> {
+        Box::pin(async move {
+            let mut candidates = Vec::with_capacity(query.count);
+            for variant in 0..query.count {
+                let svg = typographic_cover(&query.title, query.subtitle.as_deref(), variant);
+                candidates.push(
+                    store_candidate(query, CoverSource::Typographic, svg.as_bytes(), "svg", None)
+                        .await?,
+                );
+            }
+            Ok(candidates)
+        })
+    }
+}
+
+/// Cover art painted by an image model. Each image takes a while, so it
+/// produces at most `max_images` per request.
+pub struct AiImageProvider {
+    pub model: Arc<dyn ImageModel>,
+    pub max_images: usize,
+}
+
+impl CoverProvider for AiImageProvider {
+    fn source(&self) -> CoverSource {
+        CoverSource::Ai
+    }
+
+    fn covers<'a>(&'a self, query: &'a CoverQuery) -> BoxFuture<'a, Result<Vec<CoverCandidate>>> {
+        Box::pin(async move {
+            let prompt = format!(
+                "Paint the cover art for a book titled '{}'{} about {}. Portrait orientation, \
+                 a single strong image, no text, letters or logos.",
+                query.title,
+                query
+                    .subtitle
+                    .as_deref()
+                    .map(|subtitle| format!(" ({})", subtitle))
+                    .unwrap_or_default(),
+                query.topic,
+            );
+
+            let mut candidates = Vec::new();
+            for _ in 0..query.count.min(self.max_images) {
+                let image = self
+                    .model
+                    .generate(query.user, "cover_image", &prompt)
+                    .await?;
+                candidates.push(
+                    store_candidate(query, CoverSource::Ai, &image.bytes, image.extension, None)
+                        .await?,
+                );
+            }
+            Ok(candidates)
+        })
+    }
+}
+
+/// The providers named in `COVER_PROVIDERS` (comma separated, default
+/// `unsplash,typographic`). Falls back to the typographic one, which can't
+/// fail for lack of a network, when none of them is usable.
+pub fn get_cover_providers() -> Vec<Arc<dyn CoverProvider>> {
+    let names =
+        std::env::var("COVER_PROVIDERS").unwrap_or_else(|_| "unsplash,typographic".to_string());
+    let mut providers: Vec<Arc<dyn CoverProvider>> = Vec::new();
+    for name in names.split(',').map(str::trim) {
+        match name {
+            "unsplash" => match std::env::var("UNSPLASH_API_KEY") {
+                Ok(access_key) => providers.push(Arc::new(UnsplashProvider { access_key })),
+                Err(_) => tracing::warn!("UNSPLASH_API_KEY is not set"),
+            },
+            "typographic" => providers.push(Arc::new(TypographicProvider)),
+            _ => tracing::warn!("unknown cover provider {}", name),
+        }
+    }
+    if providers.is_empty() {
+        providers.push(Arc::new(TypographicProvider));
+    }
+    providers
+}
+
+/// Painted covers cost an image model call each, so they're only made when
+/// the user asks for them, never as part of the regular suggestions.
+pub fn get_ai_cover_provider() -> Arc<dyn CoverProvider> {
+    Arc::new(AiImageProvider {
+        model: get_image_model(),
+        max_images: 2,
+    })
+}
@@ -1,4 +1,9 @@
-use mongodb::{options::ClientOptions, Client};
+use bson::{doc, Document};
+use dioxus_logger::tracing;
+use mongodb::{
+    options::{ClientOptions, IndexOptions},
+    Client, Database, IndexModel,
+};
 use std::env;
 use tokio::sync::OnceCell;
 
@@ -28,3 +33,192 @@ async fn init_db() -> Client {
 pub async fn get_client() -> &'static Client {
     DB.get_or_init(init_db).await
 }
+
+fn index(keys: Document) -> IndexModel {
+    IndexModel::builder().keys(keys).build()
+}
+
+/// Creates one collection's indexes, logging a failure so the other
+/// collections still get theirs.
+async fn create_indexes(db: &Database, collection: &str, indexes: Vec<IndexModel>) {
+    if let Err(err) = db
+        .collection::<Document>(collection)
+        .create_indexes(indexes)
+        .await
+    {
+        tracing::error!("failed to create {} indexes: {}", collection, err);
+    }
+}
+
+/// Creates the indexes the library queries rely on. Safe to run on every start.
+pub async fn ensure_indexes() {
+    let db = get_client()
+        .await
+        .database(&env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    create_indexes(
+        &db,
+        "books",
+        vec![
+            index(doc! { "user": 1, "createdAt": -1, "_id": -1 }),
+            index(doc! { "user": 1, "updatedAt": -1, "_id": -1 }),
+            index(doc! { "user": 1, "title": 1, "_id": 1 }),
+            index(doc! { "user": 1, "completed": 1, "_id": 1 }),
+            index(doc! { "user": 1, "bookType": 1 }),
+            index(doc! { "user": 1, "tags": 1 }),
+            index(doc! { "user": 1, "collections": 1 }),
+            index(doc! { "user": 1, "series.name": 1, "series.order": 1, "_id": 1 }),
+            index(doc! { "user": 1, "editionOf": 1 }),
+            index(doc! { "deletedAt": 1 }),
+            index(doc! { "title": "text", "subtitle": "text", "mainTopic": "text" }),
+            // Partial rather than sparse: sparse indexes still hold explicit
+            // nulls, which would collide.
+            IndexModel::builder()
+                .keys(doc! { "slug": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .unique(true)
+                        .partial_filter_expression(doc! { "slug": { "$type": "string" } })
+                        .build(),
+                )
+                .build(),
+        ],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "chapters",
+        vec![
+            index(doc! { "book_id": 1, "position": 1 }),
+            IndexModel::builder()
+                .keys(doc! { "title": "text", "markdown": "text" })
+                .options(
+                    IndexOptions::builder()
+                        .weights(doc! { "title": 5, "markdown": 1 })
+                        .build(),
+                )
+                .build(),
+        ],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "reading_progress",
+        vec![
+            IndexModel::builder()
+                .keys(doc! { "user": 1, "chapter_id": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            index(doc! { "user": 1, "book_id": 1 }),
+            index(doc! { "user": 1, "updatedAt": -1 }),
+        ],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "annotations",
+        vec![index(doc! { "user": 1, "book_id": 1, "chapter_id": 1 })],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "quizzes",
+        vec![index(doc! { "user": 1, "chapter_id": 1 })],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "quiz_attempts",
+        vec![
+            index(doc! { "user": 1, "chapter_id": 1, "createdAt": -1 }),
+            index(doc! { "quiz_id": 1, "user": 1, "score": -1 }),
+        ],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "card_states",
+        vec![
+            IndexModel::builder()
+                .keys(doc! { "user": 1, "quiz_id": 1, "card_id": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            index(doc! { "user": 1, "due": 1 }),
+        ],
+    )
+    .await;
+
+    create_indexes(&db, "back_matter", vec![index(doc! { "book_id": 1 })]).await;
+
+    create_indexes(
+        &db,
+        "audio_tracks",
+        vec![
+            index(doc! { "book_id": 1, "position": 1 }),
+            index(doc! { "status": 1 }),
+        ],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "audiobooks",
+        vec![IndexModel::builder()
+            .keys(doc! { "book_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()],
+    )
+    .await;
+
+    // Unique, so a webhook and reconciliation racing on the same
+    // subscription can't both insert it.
+    create_indexes(
+        &db,
+        "subscriptions",
+        vec![
+            IndexModel::builder()
+                .keys(doc! { "subId": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            index(doc! { "user": 1, "active": 1 }),
+        ],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "subscription_audits",
+        vec![index(doc! { "user": 1, "createdAt": -1 })],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "plans",
+        vec![
+            index(doc! { "priceId": 1 }),
+            index(doc! { "paypalPlanId": 1 }),
+        ],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "ai_calls",
+        vec![index(doc! { "user": 1, "createdAt": -1 })],
+    )
+    .await;
+
+    create_indexes(
+        &db,
+        "book_creations",
+        vec![index(doc! { "user": 1, "createdAt": -1 })],
+    )
+    .await;
+}
@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use bson::oid::ObjectId;
+use futures_util::future::BoxFuture;
+use gems::imagen::ImageGenBuilder;
+use gems::messages::{Content, Message};
+use gems::models::Model;
+use std::sync::Arc;
+
+use crate::ai::{generate_image, get_ai};
+use crate::server::common::html::escape_html;
+use crate::storage;
+
+pub struct GeneratedImage {
+    pub bytes: Vec<u8>,
+    /// File extension matching the image format.
+    pub extension: &'static str,
+}
+
+/// A model turning a text prompt into a picture.
+pub trait ImageModel: Send + Sync {
+    /// Paints `prompt` for `user`; `template` names the call in the
+    /// `ai_calls` ledger.
+    fn generate<'a>(
+        &'a self,
+        user: ObjectId,
+        template: &'a str,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<GeneratedImage>>;
+}
+
+/// Gemini's image generation model.
+pub struct GeminiImageModel;
+
+impl ImageModel for GeminiImageModel {
+    fn generate<'a>(
+        &'a self,
+        user: ObjectId,
+        template: &'a str,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<GeneratedImage>> {
+        Box::pin(async move {
+            let client = get_ai(Model::Flash20.to_string())
+                .await
+                .lock()
+                .await
+                .clone();
+
+            let bytes = generate_image(
+                &client,
+                Some(user),
+                template,
+                ImageGenBuilder::default()
+                    .model(Model::FlashExpImage)
+                    .input(Message::User {
+                        content: Content::Text(prompt.to_string()),
+                        name: None,
+                    })
+                    .build()?,
+            )
+            .await?;
+            let extension = storage::image_extension(&bytes)
+                .ok_or_else(|| anyhow!("image model returned an unknown format"))?;
+            Ok(GeneratedImage { bytes, extension })
+        })
+    }
+}
+
+/// Draws a grey SVG card showing the prompt, so the illustration pipeline
+/// can run offline and without spending image quota.
+pub struct StubImageModel;
+
+impl ImageModel for StubImageModel {
+    fn generate<'a>(
+        &'a self,
+        _user: ObjectId,
+        _template: &'a str,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, Result<GeneratedImage>> {
+        Box::pin(async move {
+            let caption = prompt.chars().take(80).collect::<String>();
+            let svg = format!(
+                r##"<svg xmlns="http://www.w3.org/2000/svg" width="800" height="450" viewBox="0 0 800 450"><rect width="800" height="450" fill="#e5e7eb"/><text x="400" y="225" font-size="20" text-anchor="middle" fill="#4b5563" font-family="Helvetica, Arial, sans-serif">{}</text></svg>"##,
+                escape_html(&caption)
+            );
+            Ok(GeneratedImage {
+                bytes: svg.into_bytes(),
+                extension: "svg",
+            })
+        })
+    }
+}
+
+/// The model selected by `IMAGE_BACKEND`: `gemini` (default) or `stub`.
+pub fn get_image_model() -> Arc<dyn ImageModel> {
+    match std::env::var("IMAGE_BACKEND").unwrap_or_default().as_str() {
+        "stub" => Arc::new(StubImageModel),
+        _ => Arc::new(GeminiImageModel),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stub_draws_an_svg_captioned_with_the_prompt() {
+        let image = StubImageModel
+            .generate(
+                ObjectId::new(),
+                "illustration_image",
+                "A <crab> & a ferris wheel",
+            )
+            .await
+            .unwrap();
+        assert_eq!(image.extension, "svg");
+        let svg = String::from_utf8(image.bytes).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(">A &lt;crab&gt; &amp; a ferris wheel</text>"));
+    }
+
+    #[tokio::test]
+    async fn stub_caption_is_cut_to_eighty_characters() {
+        let prompt = "é".repeat(100);
+        let image = StubImageModel
+            .generate(ObjectId::new(), "illustration_image", &prompt)
+            .await
+            .unwrap();
+        let svg = String::from_utf8(image.bytes).unwrap();
+        assert!(svg.contains(&format!(">{}</text>", "é".repeat(80))));
+    }
+}
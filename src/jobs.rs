@@ -0,0 +1,92 @@
+use crate::reconcile::reconcile_subscriptions;
+use crate::server::audiobook::controller::fail_interrupted_narrations;
+use crate::server::book::controller::{backfill_chapter_positions, purge_expired_books};
+use crate::server::catalog::controller::sync_plan_catalog;
+use crate::server::edition::controller::fail_interrupted_translations;
+use crate::server::subscription::controller::check_ended_trials;
+use dioxus_logger::tracing;
+use std::time::Duration;
+
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const PLAN_SYNC_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const TRIAL_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub fn spawn_background_jobs() {
+    // Translation and narration tasks don't survive a restart; surface them
+    // as failed.
+    tokio::spawn(async {
+        match fail_interrupted_translations().await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("marked {} interrupted translations as failed", count),
+            Err(err) => tracing::error!("translation cleanup failed: {}", err),
+        }
+        match fail_interrupted_narrations().await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("marked {} interrupted narrations as failed", count),
+            Err(err) => tracing::error!("narration cleanup failed: {}", err),
+        }
+    });
+
+    tokio::spawn(async {
+        match backfill_chapter_positions().await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("gave positions to {} chapters", count),
+            Err(err) => tracing::error!("chapter position backfill failed: {}", err),
+        }
+    });
+
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(TRASH_PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_expired_books().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("purged {} expired books from trash", count),
+                Err(err) => tracing::error!("trash purge failed: {}", err),
+            }
+        }
+    });
+
+    // Prices edited in Stripe show up on the pricing page without a deploy.
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(PLAN_SYNC_INTERVAL);
+        loop {
+            interval.tick().await;
+            match sync_plan_catalog().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("updated {} plans from Stripe prices", count),
+                Err(err) => tracing::error!("plan catalog sync failed: {}", err),
+            }
+        }
+    });
+
+    // Catches up on webhooks that never arrived.
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match reconcile_subscriptions("schedule").await {
+                Ok(report) if report.corrected == 0 => {}
+                Ok(report) => tracing::info!(
+                    "reconciliation corrected {} of {} records",
+                    report.corrected,
+                    report.checked
+                ),
+                Err(err) => tracing::error!("reconciliation failed: {}", err),
+            }
+        }
+    });
+
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(TRIAL_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match check_ended_trials().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("rechecked {} ended trials", count),
+                Err(err) => tracing::error!("trial check failed: {}", err),
+            }
+        }
+    });
+}
@@ -2,15 +2,33 @@
 pub(crate) mod ai;
 pub mod components;
 #[cfg(feature = "server")]
+pub(crate) mod cover;
+#[cfg(feature = "server")]
 pub mod db;
 pub mod i18n;
+#[cfg(feature = "server")]
+pub(crate) mod image_model;
+#[cfg(feature = "server")]
+pub mod jobs;
 pub(crate) mod pages;
 #[cfg(feature = "server")]
 pub mod pay;
 #[cfg(feature = "server")]
+pub(crate) mod payment;
+#[cfg(feature = "server")]
+pub mod reconcile;
+#[cfg(feature = "server")]
 pub(crate) mod redis;
 pub mod router;
 pub(crate) mod server;
+#[cfg(feature = "server")]
+pub mod storage;
+#[cfg(all(test, feature = "server"))]
+pub(crate) mod testing;
 pub mod theme;
 #[cfg(feature = "server")]
+pub(crate) mod tts;
+#[cfg(feature = "server")]
 pub(crate) mod unsplash;
+#[cfg(feature = "server")]
+pub mod webhook;
@@ -37,12 +37,31 @@ fn main() {
         tokio::runtime::Runtime::new()
             .unwrap()
             .block_on(async move {
+                tokio::spawn(aibook::db::ensure_indexes());
+                aibook::jobs::spawn_background_jobs();
+
                 let cors = CorsLayer::new()
                     .allow_origin(Any)
                     .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
                     .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
 
                 let app = Router::new()
+                    .route(
+                        &format!("{}/*key", aibook::storage::MEDIA_ROUTE),
+                        axum::routing::get(aibook::storage::serve_media),
+                    )
+                    .route(
+                        aibook::webhook::STRIPE_WEBHOOK_ROUTE,
+                        axum::routing::post(aibook::webhook::stripe_webhook),
+                    )
+                    .route(
+                        aibook::webhook::PAYPAL_WEBHOOK_ROUTE,
+                        axum::routing::post(aibook::webhook::paypal_webhook),
+                    )
+                    .route(
+                        aibook::reconcile::RECONCILE_ROUTE,
+                        axum::routing::post(aibook::reconcile::reconcile_now),
+                    )
                     .layer(cors)
                     .serve_dioxus_application(ServeConfig::new().unwrap(), App);
 
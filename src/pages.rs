@@ -3,5 +3,6 @@ pub(crate) mod dashboard;
 pub(crate) mod home;
 pub(crate) mod login;
 pub(crate) mod profile;
+pub(crate) mod public;
 pub(crate) mod signup;
 pub(crate) mod success;
@@ -3,7 +3,10 @@
 use crate::components::dashboard::books::create::CreateBookPanel;
 use crate::components::dashboard::books::edit::EditBookContentPanel;
 use crate::components::dashboard::books::list::BooksPanel;
+use crate::components::dashboard::books::notebook::NotebookPanel;
 use crate::components::dashboard::books::read::ReadBookPanel;
+use crate::components::dashboard::books::study::StudyPanel;
+use crate::components::dashboard::books::trash::TrashPanel;
 use crate::components::dashboard::chat::ChatPanelPage;
 use crate::components::dashboard::navbar::Navbar;
 use crate::components::dashboard::profile::ProfilePagePanel;
@@ -16,7 +19,7 @@ use gloo_storage::SessionStorage;
 use gloo_storage::Storage;
 
 #[component]
-pub fn ReadBook(id: String) -> Element {
+pub fn ReadBook(id: String, chapter: String) -> Element {
     let active_tab = use_signal(|| Tab::ReadBook);
     let mut user_token = use_signal(|| "".to_string());
     let navigator = use_navigator();
@@ -28,9 +31,13 @@ pub fn ReadBook(id: String) -> Element {
             Tab::ReadBook => rsx! { ReadBookPanel { book_id: id } },
             Tab::EditProfile => rsx! { ProfilePagePanel {} },
             Tab::Chat => rsx! { ChatPanelPage { user_token, book_id: id} },
+            Tab::Trash => rsx! { TrashPanel { user_token } },
+            Tab::Notebook => rsx! { NotebookPanel { user_token } },
+            Tab::Study => rsx! { StudyPanel { user_token } },
         };
     } else {
-        current_tab = rsx! { ReadBookPanel { book_id: id } };
+        // Keyed so switching editions remounts the reader for the other book.
+        current_tab = rsx! { ReadBookPanel { key: "{id}", book_id: id, chapter_id: chapter } };
     }
 
     use_effect(move || {
@@ -80,6 +87,9 @@ pub fn EditBook(id: String) -> Element {
             Tab::ReadBook => rsx! { ReadBookPanel { book_id: id } },
             Tab::EditProfile => rsx! { ProfilePagePanel {} },
             Tab::Chat => rsx! { ChatPanelPage { user_token, book_id: id} },
+            Tab::Trash => rsx! { TrashPanel { user_token } },
+            Tab::Notebook => rsx! { NotebookPanel { user_token } },
+            Tab::Study => rsx! { StudyPanel { user_token } },
         };
     } else {
         current_tab = rsx! { EditBookContentPanel { book_id: id } };
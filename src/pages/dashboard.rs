@@ -1,6 +1,9 @@
 use crate::components::dashboard::books::create::CreateBookPanel;
 use crate::components::dashboard::books::list::BooksPanel;
+use crate::components::dashboard::books::notebook::NotebookPanel;
 use crate::components::dashboard::books::read::ReadBookPanel;
+use crate::components::dashboard::books::study::StudyPanel;
+use crate::components::dashboard::books::trash::TrashPanel;
 use crate::components::dashboard::chat::ChatPanelPage;
 use crate::components::dashboard::navbar::Navbar;
 use crate::components::dashboard::profile::ProfilePagePanel;
@@ -22,6 +25,9 @@ pub fn Dashboard() -> Element {
         Tab::ReadBook => rsx! { ReadBookPanel { book_id: "" } },
         Tab::EditProfile => rsx! { ProfilePagePanel {} },
         Tab::Chat => rsx! { ChatPanelPage { user_token, book_id: ""} },
+        Tab::Trash => rsx! { TrashPanel { user_token } },
+        Tab::Notebook => rsx! { NotebookPanel { user_token } },
+        Tab::Study => rsx! { StudyPanel { user_token } },
     };
 
     use_effect(move || {
@@ -2,7 +2,10 @@
 
 use crate::components::dashboard::books::create::CreateBookPanel;
 use crate::components::dashboard::books::list::BooksPanel;
+use crate::components::dashboard::books::notebook::NotebookPanel;
 use crate::components::dashboard::books::read::ReadBookPanel;
+use crate::components::dashboard::books::study::StudyPanel;
+use crate::components::dashboard::books::trash::TrashPanel;
 use crate::components::dashboard::chat::ChatPanelPage;
 use crate::components::dashboard::navbar::Navbar;
 use crate::components::dashboard::profile::ProfilePagePanel;
@@ -27,6 +30,9 @@ pub fn UserProfile(id: String) -> Element {
             Tab::ReadBook => rsx! { ReadBookPanel { book_id: id } },
             Tab::EditProfile => rsx! { ProfilePagePanel {} },
             Tab::Chat => rsx! { ChatPanelPage { user_token, book_id: id} },
+            Tab::Trash => rsx! { TrashPanel { user_token } },
+            Tab::Notebook => rsx! { NotebookPanel { user_token } },
+            Tab::Study => rsx! { StudyPanel { user_token } },
         };
     }
 
@@ -0,0 +1,89 @@
+use crate::components::dashboard::books::cover::CoverCreditLine;
+use crate::server::book::controller::get_public_book;
+use crate::server::book::request::GetPublicBookRequest;
+use dioxus::prelude::*;
+
+#[component]
+pub fn PublicBook(slug: String, token: String) -> Element {
+    let book = use_server_future(move || {
+        let req = GetPublicBookRequest {
+            slug: slug.clone(),
+            share_token: if token.is_empty() {
+                None
+            } else {
+                Some(token.clone())
+            },
+        };
+        async move { get_public_book(req).await }
+    })?;
+
+    let content = match &*book.read() {
+        Some(Ok(response)) => {
+            let book = response.data.clone();
+            rsx! {
+                header { class: "mb-10 text-center",
+                    if let Some(cover) = book.cover {
+                        img {
+                            src: "{cover}",
+                            alt: "Book cover",
+                            class: "w-full h-64 object-cover rounded-lg mb-6"
+                        }
+                    }
+                    if let Some(credit) = book.cover_credit {
+                        div { class: "-mt-4 mb-6",
+                            CoverCreditLine { credit }
+                        }
+                    }
+                    h1 { class: "text-4xl font-bold mb-2", "{book.title}" }
+                    if let Some(subtitle) = book.subtitle {
+                        p { class: "text-lg text-gray-500", "{subtitle}" }
+                    }
+                }
+
+                nav { class: "mb-10 p-4 rounded-lg dark:bg-gray-800 bg-gray-100",
+                    h2 { class: "text-xl font-semibold mb-4", "Table of Contents" }
+                    ol { class: "space-y-2 list-decimal list-inside",
+                        for chapter in book.chapters.iter() {
+                            li {
+                                a {
+                                    href: "#{chapter.anchor()}",
+                                    class: "text-blue-500 hover:underline",
+                                    "{chapter.title}"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for chapter in book.chapters.into_iter() {
+                    section {
+                        id: chapter.anchor(),
+                        class: "mb-12",
+                        h2 { class: "text-2xl font-bold mb-4", "{chapter.title}" }
+                        div {
+                            class: "prose dark:prose-invert max-w-none",
+                            dangerous_inner_html: chapter.html,
+                        }
+                    }
+                }
+            }
+        }
+        Some(Err(_)) => rsx! {
+            div { class: "text-center py-20",
+                h1 { class: "text-3xl font-bold mb-4", "Book not available" }
+                p { class: "text-gray-500", "This link is invalid, expired or has been revoked." }
+            }
+        },
+        None => rsx! {
+            p { class: "text-center py-20", "Loading book..." }
+        },
+    };
+
+    rsx! {
+        div { class: "min-h-screen dark:bg-gray-900 dark:text-white bg-white text-gray-900",
+            article { class: "max-w-3xl mx-auto px-4 py-12",
+                {content}
+            }
+        }
+    }
+}
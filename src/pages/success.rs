@@ -33,7 +33,7 @@ pub fn SuccessPage() -> Element {
 
                 p {
                     class: "text-lg font-light text-gray-200 max-w-md mx-auto",
-                    "Thank you for your payment. Your subscription is now active, and you can enjoy all the premium features we offer. A confirmation email has been sent to your registered email address."
+                    "Thank you for your payment. Your plan is upgraded as soon as Stripe confirms it, usually within a few seconds. A confirmation email has been sent to your registered email address."
                 },
 
                 a {
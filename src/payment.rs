@@ -0,0 +1,681 @@
+use anyhow::{anyhow, bail, Result};
+use axum::http::HeaderMap;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::BoxFuture;
+use mongodb::Database;
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use stripe::{
+    CheckoutSessionMode, CheckoutSessionPaymentMethodCollection, CreateCheckoutSession,
+    CreateCheckoutSessionDiscounts, CreateCheckoutSessionLineItems,
+    CreateCheckoutSessionSubscriptionData, CreateCheckoutSessionSubscriptionDataTrialSettings,
+    CreateCheckoutSessionSubscriptionDataTrialSettingsEndBehavior,
+    CreateCheckoutSessionSubscriptionDataTrialSettingsEndBehaviorMissingPaymentMethod as MissingPaymentMethod,
+    EventObject, EventType, Subscription as StripeSubscription, SubscriptionId, SubscriptionStatus,
+    Webhook,
+};
+
+use crate::pay::get_stripe;
+use crate::server::auth::model::User;
+use crate::server::catalog::model::CatalogPlan;
+use crate::server::subscription::controller::ensure_stripe_customer;
+use crate::server::subscription::model::PaymentMethod;
+
+/// What the user is about to pay for.
+pub struct Checkout<'a> {
+    pub user: &'a User,
+    pub plan: &'a CatalogPlan,
+    /// A discount picked before checkout, e.g. from a campaign link.
+    pub coupon: Option<String>,
+    pub success_url: String,
+    pub cancel_url: String,
+}
+
+/// A subscription as the provider currently sees it.
+#[derive(Debug, Clone)]
+pub struct ProviderSubscription {
+    pub id: String,
+    pub customer: String,
+    /// The prices or billing plans it's for, matched against the catalog.
+    pub prices: Vec<String>,
+    /// The provider's own status, e.g. Stripe's `past_due`.
+    pub status: String,
+    /// Whether the status pays for the plan.
+    pub active: bool,
+    pub trial_end: Option<DateTime<Utc>>,
+    /// The provider's full record as JSON.
+    pub raw: String,
+}
+
+/// A verified webhook delivery, reduced to the subscription it concerns.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WebhookEvent {
+    pub id: String,
+    pub kind: String,
+    pub subscription: Option<String>,
+    /// Our user id, when the provider echoes back what checkout was given.
+    pub user: Option<String>,
+    /// The payer's email, for checkouts without a user id.
+    pub email: Option<String>,
+}
+
+/// A way to take recurring payments. Subscriptions are always re-read with
+/// `subscription`, so webhooks only need to say which one changed.
+pub trait PaymentProvider: Send + Sync {
+    fn method(&self) -> PaymentMethod;
+
+    /// Starts paying for a plan and returns the URL to send the user to.
+    fn checkout<'a>(
+        &'a self,
+        db: &'a Database,
+        checkout: Checkout<'a>,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// Ends a subscription immediately.
+    fn cancel<'a>(&'a self, subscription: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    fn subscription<'a>(
+        &'a self,
+        subscription: &'a str,
+    ) -> BoxFuture<'a, Result<ProviderSubscription>>;
+
+    /// Checks a delivery's signature and reads it.
+    fn parse_webhook<'a>(
+        &'a self,
+        headers: &'a HeaderMap,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<WebhookEvent>>;
+}
+
+pub struct StripeProvider;
+
+/// Stripe keeps a subscription around while it retries a failed payment;
+/// the plan only applies while it's paid up.
+fn grants_access(status: SubscriptionStatus) -> bool {
+    matches!(
+        status,
+        SubscriptionStatus::Active | SubscriptionStatus::Trialing
+    )
+}
+
+fn stripe_subscription_id(id: &str) -> Result<SubscriptionId> {
+    SubscriptionId::from_str(id).map_err(|_| anyhow!("invalid Stripe subscription id {}", id))
+}
+
+pub(crate) fn from_stripe(stripe_sub: &StripeSubscription) -> Result<ProviderSubscription> {
+    Ok(ProviderSubscription {
+        id: stripe_sub.id.to_string(),
+        customer: stripe_sub.customer.id().to_string(),
+        prices: stripe_sub
+            .items
+            .data
+            .iter()
+            .filter_map(|item| item.price.as_ref())
+            .map(|price| price.id.to_string())
+            .collect(),
+        status: stripe_sub.status.as_str().to_string(),
+        active: grants_access(stripe_sub.status),
+        trial_end: stripe_sub
+            .trial_end
+            .and_then(|end| DateTime::from_timestamp(end, 0)),
+        raw: serde_json::to_string(stripe_sub)?,
+    })
+}
+
+/// Checks a Stripe delivery against the `Stripe-Signature` header and reads
+/// it.
+pub(crate) fn parse_stripe_event(
+    body: &str,
+    signature: &str,
+    secret: &str,
+) -> Result<WebhookEvent> {
+    let event = Webhook::construct_event(body, signature, secret)?;
+
+    let mut parsed = WebhookEvent {
+        id: event.id.to_string(),
+        // EventType displays as a JSON string.
+        kind: event.type_.to_string().trim_matches('"').to_string(),
+        ..Default::default()
+    };
+    match (event.type_, event.data.object) {
+        (EventType::CheckoutSessionCompleted, EventObject::CheckoutSession(session)) => {
+            if session.mode == CheckoutSessionMode::Subscription {
+                parsed.subscription = session.subscription.map(|sub| sub.id().to_string());
+                parsed.user = session.client_reference_id;
+                parsed.email = session.customer_details.and_then(|details| details.email);
+            }
+        }
+        (
+            EventType::CustomerSubscriptionUpdated | EventType::CustomerSubscriptionDeleted,
+            EventObject::Subscription(subscription),
+        ) => parsed.subscription = Some(subscription.id.to_string()),
+        (EventType::InvoicePaymentFailed, EventObject::Invoice(invoice)) => {
+            parsed.subscription = invoice.subscription.map(|sub| sub.id().to_string());
+        }
+        _ => {}
+    }
+    Ok(parsed)
+}
+
+/// Everything in a Stripe Checkout Session but the customer, which may take
+/// an API call to create.
+fn checkout_session<'a>(
+    checkout: &'a Checkout,
+    price: &str,
+    user_id: &'a str,
+) -> CreateCheckoutSession<'a> {
+    let mut session = CreateCheckoutSession::new();
+    // Lets the webhook find the user without trusting the payer's email.
+    session.client_reference_id = Some(user_id);
+    session.success_url = Some(&checkout.success_url);
+    session.cancel_url = Some(&checkout.cancel_url);
+    session.mode = Some(CheckoutSessionMode::Subscription);
+    session.line_items = vec![CreateCheckoutSessionLineItems {
+        price: Some(price.to_string()),
+        quantity: Some(1),
+        ..Default::default()
+    }]
+    .into();
+    // Stripe takes one or the other.
+    match &checkout.coupon {
+        Some(coupon) => {
+            session.discounts = Some(vec![CreateCheckoutSessionDiscounts {
+                coupon: Some(coupon.clone()),
+                ..Default::default()
+            }])
+        }
+        None => session.allow_promotion_codes = Some(true),
+    }
+    if checkout.plan.trial_days > 0 {
+        // Trials start without a card. One that ends without a card is
+        // canceled, and one whose first charge fails goes past due; both
+        // take the plan away.
+        session.payment_method_collection =
+            Some(CheckoutSessionPaymentMethodCollection::IfRequired);
+        session.subscription_data = Some(CreateCheckoutSessionSubscriptionData {
+            trial_period_days: Some(checkout.plan.trial_days),
+            trial_settings: Some(CreateCheckoutSessionSubscriptionDataTrialSettings {
+                end_behavior: CreateCheckoutSessionSubscriptionDataTrialSettingsEndBehavior {
+                    missing_payment_method: MissingPaymentMethod::Cancel,
+                },
+            }),
+            ..Default::default()
+        });
+    }
+    session
+}
+
+impl PaymentProvider for StripeProvider {
+    fn method(&self) -> PaymentMethod {
+        PaymentMethod::Stripe
+    }
+
+    fn checkout<'a>(
+        &'a self,
+        db: &'a Database,
+        checkout: Checkout<'a>,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let price = checkout
+                .plan
+                .price_id
+                .as_deref()
+                .ok_or_else(|| anyhow!("plan {} has no Stripe price", checkout.plan.id))?;
+            let stripe_client = get_stripe().await.lock().await;
+            let customer = ensure_stripe_customer(db, &stripe_client, checkout.user)
+                .await
+                .map_err(|err| anyhow!(err.to_string()))?;
+            let user_id = checkout.user.id.to_hex();
+
+            let mut session = checkout_session(&checkout, price, &user_id);
+            session.customer = Some(customer);
+            let session = stripe::CheckoutSession::create(&stripe_client, session).await?;
+            session
+                .url
+                .ok_or_else(|| anyhow!("Stripe returned a checkout session without a URL"))
+        })
+    }
+
+    fn cancel<'a>(&'a self, subscription: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let id = stripe_subscription_id(subscription)?;
+            let stripe_client = get_stripe().await.lock().await;
+            StripeSubscription::delete(&stripe_client, &id).await?;
+            Ok(())
+        })
+    }
+
+    fn subscription<'a>(
+        &'a self,
+        subscription: &'a str,
+    ) -> BoxFuture<'a, Result<ProviderSubscription>> {
+        Box::pin(async move {
+            let id = stripe_subscription_id(subscription)?;
+            let stripe_client = get_stripe().await.lock().await;
+            let stripe_sub = StripeSubscription::retrieve(&stripe_client, &id, &[]).await?;
+            from_stripe(&stripe_sub)
+        })
+    }
+
+    fn parse_webhook<'a>(
+        &'a self,
+        headers: &'a HeaderMap,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<WebhookEvent>> {
+        Box::pin(async move {
+            let signature = headers
+                .get("Stripe-Signature")
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| anyhow!("missing Stripe-Signature header"))?;
+            parse_stripe_event(body, signature, &setting("STRIPE_WEBHOOK_SECRET")?)
+        })
+    }
+}
+
+/// PayPal subscriptions through the REST API. Catalog plans sell through
+/// PayPal when they carry a `paypalPlanId`.
+pub struct PaypalProvider {
+    /// `https://api-m.paypal.com`, or the sandbox while testing.
+    pub api_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// The id PayPal gave our webhook, which signature checks are made for.
+    pub webhook_id: String,
+}
+
+#[derive(Deserialize)]
+struct PaypalToken {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct PaypalLink {
+    href: String,
+    rel: String,
+}
+
+#[derive(Deserialize)]
+struct PaypalSubscriber {
+    payer_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PaypalSubscription {
+    id: String,
+    status: String,
+    plan_id: Option<String>,
+    subscriber: Option<PaypalSubscriber>,
+    #[serde(default)]
+    links: Vec<PaypalLink>,
+}
+
+#[derive(Deserialize)]
+struct PaypalEvent {
+    id: String,
+    event_type: String,
+    resource: Value,
+}
+
+impl PaypalProvider {
+    async fn token(&self) -> Result<String> {
+        let response = reqwest::Client::new()
+            .post(format!("{}/v1/oauth2/token", self.api_url))
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body("grant_type=client_credentials")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("PayPal token request returned {}", response.status());
+        }
+        Ok(response.json::<PaypalToken>().await?.access_token)
+    }
+
+    /// Calls the API and returns its JSON, or `null` for empty replies.
+    async fn call(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let mut request = reqwest::Client::new()
+            .request(method, format!("{}{}", self.api_url, path))
+            .bearer_auth(self.token().await?);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            bail!("PayPal {} returned {}", path, response.status());
+        }
+        let bytes = response.bytes().await?;
+        if bytes.is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+impl PaymentProvider for PaypalProvider {
+    fn method(&self) -> PaymentMethod {
+        PaymentMethod::Paypal
+    }
+
+    fn checkout<'a>(
+        &'a self,
+        _db: &'a Database,
+        checkout: Checkout<'a>,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let plan_id = checkout
+                .plan
+                .paypal_plan_id
+                .as_deref()
+                .ok_or_else(|| anyhow!("plan {} has no PayPal plan", checkout.plan.id))?;
+            // Trials and discounts are part of the PayPal billing plan.
+            if checkout.coupon.is_some() {
+                bail!("PayPal checkouts don't take coupons");
+            }
+            let created = self
+                .call(
+                    Method::POST,
+                    "/v1/billing/subscriptions",
+                    Some(json!({
+                        "plan_id": plan_id,
+                        // Comes back on every webhook, like Stripe's client_reference_id.
+                        "custom_id": checkout.user.id.to_hex(),
+                        "subscriber": { "email_address": checkout.user.email },
+                        "application_context": {
+                            "return_url": checkout.success_url,
+                            "cancel_url": checkout.cancel_url,
+                            "user_action": "SUBSCRIBE_NOW",
+                        },
+                    })),
+                )
+                .await?;
+            serde_json::from_value::<PaypalSubscription>(created)?
+                .links
+                .into_iter()
+                .find(|link| link.rel == "approve")
+                .map(|link| link.href)
+                .ok_or_else(|| anyhow!("PayPal returned no approval link"))
+        })
+    }
+
+    fn cancel<'a>(&'a self, subscription: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.call(
+                Method::POST,
+                &format!("/v1/billing/subscriptions/{}/cancel", subscription),
+                Some(json!({ "reason": "Canceled by the subscriber" })),
+            )
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn subscription<'a>(
+        &'a self,
+        subscription: &'a str,
+    ) -> BoxFuture<'a, Result<ProviderSubscription>> {
+        Box::pin(async move {
+            let raw = self
+                .call(
+                    Method::GET,
+                    &format!("/v1/billing/subscriptions/{}", subscription),
+                    None,
+                )
+                .await?;
+            let paypal_sub = serde_json::from_value::<PaypalSubscription>(raw.clone())?;
+
+            Ok(ProviderSubscription {
+                id: paypal_sub.id,
+                customer: paypal_sub
+                    .subscriber
+                    .and_then(|subscriber| subscriber.payer_id)
+                    .unwrap_or_default(),
+                prices: paypal_sub.plan_id.into_iter().collect(),
+                active: paypal_sub.status == "ACTIVE",
+                trial_end: None,
+                status: paypal_sub.status.to_lowercase(),
+                raw: raw.to_string(),
+            })
+        })
+    }
+
+    fn parse_webhook<'a>(
+        &'a self,
+        headers: &'a HeaderMap,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<WebhookEvent>> {
+        Box::pin(async move {
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(String::from)
+                    .ok_or_else(|| anyhow!("missing {} header", name))
+            };
+            let verification = self
+                .call(
+                    Method::POST,
+                    "/v1/notifications/verify-webhook-signature",
+                    Some(json!({
+                        "auth_algo": header("PAYPAL-AUTH-ALGO")?,
+                        "cert_url": header("PAYPAL-CERT-URL")?,
+                        "transmission_id": header("PAYPAL-TRANSMISSION-ID")?,
+                        "transmission_sig": header("PAYPAL-TRANSMISSION-SIG")?,
+                        "transmission_time": header("PAYPAL-TRANSMISSION-TIME")?,
+                        "webhook_id": self.webhook_id,
+                        "webhook_event": serde_json::from_str::<Value>(body)?,
+                    })),
+                )
+                .await?;
+            if verification["verification_status"] != "SUCCESS" {
+                bail!("PayPal webhook signature didn't verify");
+            }
+
+            let event = serde_json::from_str::<PaypalEvent>(body)?;
+            let text = |value: &Value| value.as_str().map(String::from);
+            let subscription = if event.event_type.starts_with("BILLING.SUBSCRIPTION.") {
+                text(&event.resource["id"])
+            } else if event.event_type.starts_with("PAYMENT.SALE.") {
+                text(&event.resource["billing_agreement_id"])
+            } else {
+                None
+            };
+            Ok(WebhookEvent {
+                id: event.id,
+                kind: event.event_type,
+                subscription,
+                user: text(&event.resource["custom_id"]),
+                email: text(&event.resource["subscriber"]["email_address"]),
+            })
+        })
+    }
+}
+
+/// Keeps subscriptions in memory and approves every checkout at once, so
+/// the billing flow can be exercised without a provider account. Checkout
+/// returns to the success URL with `?subscription=` naming the new one, and
+/// webhooks are unsigned `WebhookEvent` JSON.
+#[derive(Default)]
+pub struct FakeProvider {
+    subscriptions: Mutex<HashMap<String, ProviderSubscription>>,
+}
+
+impl FakeProvider {
+    /// Moves a subscription to another status, as a provider would on its own.
+    pub fn set_status(&self, subscription: &str, status: &str, active: bool) -> Result<()> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let fake_sub = subscriptions
+            .get_mut(subscription)
+            .ok_or_else(|| anyhow!("no fake subscription {}", subscription))?;
+        fake_sub.status = status.to_string();
+        fake_sub.active = active;
+        Ok(())
+    }
+}
+
+impl PaymentProvider for FakeProvider {
+    fn method(&self) -> PaymentMethod {
+        PaymentMethod::Fake
+    }
+
+    fn checkout<'a>(
+        &'a self,
+        _db: &'a Database,
+        checkout: Checkout<'a>,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            let mut subscriptions = self.subscriptions.lock().unwrap();
+            let id = format!("fake_sub_{}", subscriptions.len() + 1);
+            let trial_days = checkout.plan.trial_days;
+            subscriptions.insert(
+                id.clone(),
+                ProviderSubscription {
+                    id: id.clone(),
+                    customer: format!("fake_cus_{}", checkout.user.id.to_hex()),
+                    prices: checkout.plan.price_id.iter().cloned().collect(),
+                    status: if trial_days > 0 { "trialing" } else { "active" }.into(),
+                    active: true,
+                    trial_end: (trial_days > 0)
+                        .then(|| Utc::now() + Duration::days(trial_days as i64)),
+                    raw: "{}".into(),
+                },
+            );
+            Ok(format!("{}?subscription={}", checkout.success_url, id))
+        })
+    }
+
+    fn cancel<'a>(&'a self, subscription: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.set_status(subscription, "canceled", false) })
+    }
+
+    fn subscription<'a>(
+        &'a self,
+        subscription: &'a str,
+    ) -> BoxFuture<'a, Result<ProviderSubscription>> {
+        Box::pin(async move {
+            self.subscriptions
+                .lock()
+                .unwrap()
+                .get(subscription)
+                .cloned()
+                .ok_or_else(|| anyhow!("no fake subscription {}", subscription))
+        })
+    }
+
+    fn parse_webhook<'a>(
+        &'a self,
+        _headers: &'a HeaderMap,
+        body: &'a str,
+    ) -> BoxFuture<'a, Result<WebhookEvent>> {
+        Box::pin(async move { Ok(serde_json::from_str(body)?) })
+    }
+}
+
+static FAKE: OnceLock<Arc<FakeProvider>> = OnceLock::new();
+
+/// The one fake provider, shared so its subscriptions outlive a request.
+pub fn fake_provider() -> Arc<FakeProvider> {
+    FAKE.get_or_init(Default::default).clone()
+}
+
+fn setting(name: &str) -> Result<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("{} is not set", name))
+}
+
+/// The provider behind a subscription's `method`, or an error when it
+/// isn't configured. The fake one only exists in debug builds.
+pub fn get_payment_provider(method: PaymentMethod) -> Result<Arc<dyn PaymentProvider>> {
+    match method {
+        PaymentMethod::Stripe => {
+            setting("STRIPE_SECRET_KEY")?;
+            Ok(Arc::new(StripeProvider))
+        }
+        PaymentMethod::Paypal => Ok(Arc::new(PaypalProvider {
+            api_url: std::env::var("PAYPAL_API_URL")
+                .unwrap_or_else(|_| "https://api-m.paypal.com".to_string()),
+            client_id: setting("PAYPAL_CLIENT_ID")?,
+            client_secret: setting("PAYPAL_CLIENT_SECRET")?,
+            webhook_id: setting("PAYPAL_WEBHOOK_ID")?,
+        })),
+        PaymentMethod::Fake if cfg!(debug_assertions) => Ok(fake_provider()),
+        PaymentMethod::Fake => bail!("fake payments are only available in debug builds"),
+    }
+}
+
+/// A subscription as whoever takes its payments sees it now.
+pub async fn provider_subscription(
+    method: PaymentMethod,
+    subscription: &str,
+) -> Result<ProviderSubscription> {
+    get_payment_provider(method)?
+        .subscription(subscription)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{monthly_plan, test_user, MONTHLY_PRICE};
+
+    fn checkout<'a>(user: &'a User, plan: &'a CatalogPlan, coupon: Option<&str>) -> Checkout<'a> {
+        Checkout {
+            user,
+            plan,
+            coupon: coupon.map(String::from),
+            success_url: "https://aibook.test/success".into(),
+            cancel_url: "https://aibook.test/failed".into(),
+        }
+    }
+
+    #[test]
+    fn coupons_replace_promotion_codes() {
+        let (user, plan) = (test_user(), monthly_plan(0));
+
+        let with_coupon = checkout(&user, &plan, Some("LAUNCH50"));
+        let session = checkout_session(&with_coupon, MONTHLY_PRICE, "user");
+        assert_eq!(session.allow_promotion_codes, None);
+        let discounts = session.discounts.unwrap();
+        assert_eq!(discounts.len(), 1);
+        assert_eq!(discounts[0].coupon.as_deref(), Some("LAUNCH50"));
+
+        let without = checkout(&user, &plan, None);
+        let session = checkout_session(&without, MONTHLY_PRICE, "user");
+        assert_eq!(session.allow_promotion_codes, Some(true));
+        assert!(session.discounts.is_none());
+    }
+
+    #[test]
+    fn trials_start_without_a_card() {
+        let user = test_user();
+
+        let plan = monthly_plan(14);
+        let trial = checkout(&user, &plan, None);
+        let session = checkout_session(&trial, MONTHLY_PRICE, "user");
+        assert!(matches!(
+            session.payment_method_collection,
+            Some(CheckoutSessionPaymentMethodCollection::IfRequired)
+        ));
+        let subscription_data = session.subscription_data.unwrap();
+        assert_eq!(subscription_data.trial_period_days, Some(14));
+        assert!(matches!(
+            subscription_data
+                .trial_settings
+                .unwrap()
+                .end_behavior
+                .missing_payment_method,
+            MissingPaymentMethod::Cancel
+        ));
+
+        let plan = monthly_plan(0);
+        let paid = checkout(&user, &plan, None);
+        let session = checkout_session(&paid, MONTHLY_PRICE, "user");
+        assert!(session.payment_method_collection.is_none());
+        assert!(session.subscription_data.is_none());
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::{anyhow, Result};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use bson::oid::ObjectId;
+use bson::{doc, Bson};
+use chrono::Utc;
+use dioxus_logger::tracing;
+use futures_util::TryStreamExt;
+use mongodb::Database;
+use serde::Serialize;
+use stripe::{ListSubscriptions, Subscription as StripeSubscription};
+
+use crate::db::get_client;
+use crate::pay::get_stripe;
+use crate::payment::{from_stripe, provider_subscription, ProviderSubscription};
+use crate::server::auth::model::User;
+use crate::server::entitlement::model::Plan;
+use crate::server::subscription::controller::{subscribed_plan, sync_subscription};
+use crate::server::subscription::model::{PaymentMethod, Subscription, SubscriptionAudit};
+
+pub const RECONCILE_ROUTE: &str = "/admin/reconcile";
+
+const PAGE_SIZE: u64 = 100;
+
+#[derive(Debug, Default, Serialize)]
+pub struct ReconcileReport {
+    /// Subscriptions and users compared.
+    pub checked: u64,
+    pub corrected: u64,
+}
+
+/// Runs a reconciliation now. Needs `Authorization: Bearer $ADMIN_TOKEN`,
+/// and doesn't exist while `ADMIN_TOKEN` is unset.
+pub async fn reconcile_now(headers: HeaderMap) -> Result<Json<ReconcileReport>, StatusCode> {
+    let Ok(token) = std::env::var("ADMIN_TOKEN") else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let authorized = headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| !token.is_empty() && given == token);
+    if !authorized {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match reconcile_subscriptions("admin").await {
+        Ok(report) => Ok(Json(report)),
+        Err(err) => {
+            tracing::error!("reconciliation failed: {}", err);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Brings `subscriptions` and `User.role` back in line with the payment
+/// providers, for when webhooks were lost. Every correction is recorded in
+/// `subscription_audits`.
+pub async fn reconcile_subscriptions(trigger: &str) -> Result<ReconcileReport> {
+    let db = get_client()
+        .await
+        .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let mut report = ReconcileReport::default();
+
+    check_active_subscriptions(&db, trigger, &mut report).await?;
+    check_stripe_subscriptions(&db, trigger, &mut report).await?;
+    check_roles(&db, trigger, &mut report).await?;
+    Ok(report)
+}
+
+async fn audit(
+    db: &Database,
+    trigger: &str,
+    user: ObjectId,
+    sub_id: Option<&str>,
+    field: &str,
+    from: &str,
+    to: &str,
+) -> Result<()> {
+    tracing::info!(
+        "reconciled {} of user {} from {} to {}",
+        field,
+        user,
+        from,
+        to
+    );
+    db.collection::<SubscriptionAudit>("subscription_audits")
+        .insert_one(SubscriptionAudit {
+            id: ObjectId::new(),
+            user,
+            sub_id: sub_id.map(String::from),
+            field: field.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            trigger: trigger.to_string(),
+            created_at: Utc::now(),
+        })
+        .await?;
+    Ok(())
+}
+
+/// Stores what the provider says about a subscription we already know,
+/// if that changes whether it's active.
+async fn correct_active(
+    db: &Database,
+    trigger: &str,
+    stored: &Subscription,
+    provider_sub: &ProviderSubscription,
+) -> Result<bool> {
+    if stored.active == provider_sub.active {
+        return Ok(false);
+    }
+    db.collection::<Subscription>("subscriptions")
+        .update_one(
+            doc! { "_id": stored.id },
+            doc! {
+                "$set": {
+                    "active": provider_sub.active,
+                    "status": &provider_sub.status,
+                    "updatedAt": Utc::now(),
+                }
+            },
+        )
+        .await?;
+    audit(
+        db,
+        trigger,
+        stored.user,
+        Some(&stored.sub_id),
+        "active",
+        &stored.active.to_string(),
+        &provider_sub.active.to_string(),
+    )
+    .await?;
+    Ok(true)
+}
+
+/// Subscriptions we think are active, checked with whoever takes their
+/// payments.
+async fn check_active_subscriptions(
+    db: &Database,
+    trigger: &str,
+    report: &mut ReconcileReport,
+) -> Result<()> {
+    let sub_collection = db.collection::<Subscription>("subscriptions");
+    let mut last: Option<ObjectId> = None;
+    loop {
+        let filter = match last {
+            Some(id) => doc! { "active": true, "_id": { "$gt": id } },
+            None => doc! { "active": true },
+        };
+        let page = sub_collection
+            .find(filter)
+            .sort(doc! { "_id": 1 })
+            .limit(PAGE_SIZE as i64)
+            .await?
+            .try_collect::<Vec<Subscription>>()
+            .await?;
+        let Some(tail) = page.last() else {
+            break;
+        };
+        last = Some(tail.id);
+
+        for stored in &page {
+            report.checked += 1;
+            let provider_sub = match provider_subscription(stored.method, &stored.sub_id).await {
+                Ok(provider_sub) => provider_sub,
+                // Leave it for the next run rather than cut off a paying user.
+                Err(err) => {
+                    tracing::warn!("couldn't check subscription {}: {}", stored.sub_id, err);
+                    continue;
+                }
+            };
+            if correct_active(db, trigger, stored, &provider_sub).await? {
+                report.corrected += 1;
+            }
+        }
+
+        if (page.len() as u64) < PAGE_SIZE {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Every Stripe subscription that isn't canceled, so ones we marked
+/// inactive or never recorded are picked up too.
+async fn check_stripe_subscriptions(
+    db: &Database,
+    trigger: &str,
+    report: &mut ReconcileReport,
+) -> Result<()> {
+    let sub_collection = db.collection::<Subscription>("subscriptions");
+    let users = db.collection::<User>("users");
+    let mut params = ListSubscriptions::new();
+    params.limit = Some(PAGE_SIZE);
+    loop {
+        let page = {
+            let stripe_client = get_stripe().await.lock().await;
+            StripeSubscription::list(&stripe_client, &params).await?
+        };
+
+        for stripe_sub in &page.data {
+            report.checked += 1;
+            // One odd subscription shouldn't stop the rest being checked.
+            let provider_sub = match from_stripe(stripe_sub) {
+                Ok(provider_sub) => provider_sub,
+                Err(err) => {
+                    tracing::warn!(
+                        "couldn't read Stripe subscription {}: {}",
+                        stripe_sub.id,
+                        err
+                    );
+                    continue;
+                }
+            };
+            if let Some(stored) = sub_collection
+                .find_one(doc! { "subId": &provider_sub.id })
+                .await?
+            {
+                if correct_active(db, trigger, &stored, &provider_sub).await? {
+                    report.corrected += 1;
+                }
+                continue;
+            }
+            if !provider_sub.active {
+                continue;
+            }
+
+            let Some(user) = users
+                .find_one(doc! { "stripeCustomer": &provider_sub.customer })
+                .await?
+            else {
+                tracing::warn!("Stripe subscription {} belongs to no user", provider_sub.id);
+                continue;
+            };
+            if let Err(err) =
+                sync_subscription(db, user.id, PaymentMethod::Stripe, &provider_sub).await
+            {
+                tracing::warn!(
+                    "couldn't record Stripe subscription {}: {}",
+                    provider_sub.id,
+                    err
+                );
+                continue;
+            }
+            audit(
+                db,
+                trigger,
+                user.id,
+                Some(&provider_sub.id),
+                "subscription",
+                "missing",
+                &provider_sub.status,
+            )
+            .await?;
+            report.corrected += 1;
+        }
+
+        match page.data.last() {
+            Some(tail) if page.has_more => params.starting_after = Some(tail.id.clone()),
+            _ => break,
+        }
+    }
+    Ok(())
+}
+
+/// Users on a paid plan, and users paying for one, get the role their
+/// active subscriptions call for.
+async fn check_roles(db: &Database, trigger: &str, report: &mut ReconcileReport) -> Result<()> {
+    let paying = db
+        .collection::<Subscription>("subscriptions")
+        .distinct("user", doc! { "active": true })
+        .await?;
+    let paid_roles = Plan::PAID
+        .iter()
+        .map(|plan| plan.role())
+        .collect::<Vec<_>>();
+
+    let user_collection = db.collection::<User>("users");
+    let mut users = user_collection
+        .find(doc! {
+            "$or": [
+                { "role": { "$in": paid_roles } },
+                { "_id": { "$in": paying.into_iter().collect::<Vec<Bson>>() } },
+            ]
+        })
+        .await?;
+    while let Some(user) = users.try_next().await? {
+        report.checked += 1;
+        let expected = subscribed_plan(db, user.id)
+            .await
+            .map_err(|err| anyhow!(err.to_string()))?;
+        if Plan::from_role(&user.role) == expected {
+            continue;
+        }
+
+        user_collection
+            .update_one(
+                doc! { "_id": user.id },
+                doc! { "$set": { "role": expected.role(), "updatedAt": Utc::now() } },
+            )
+            .await?;
+        audit(
+            db,
+            trigger,
+            user.id,
+            None,
+            "role",
+            &user.role,
+            expected.role(),
+        )
+        .await?;
+        report.corrected += 1;
+    }
+    Ok(())
+}
@@ -1,7 +1,6 @@
 use anyhow::Result;
 use dioxus::prelude::*;
-use http_api_isahc_client::IsahcClient;
-use redis::{AsyncCommands, Script};
+use redis::Script;
 use serde::Deserialize;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::{Mutex, OnceCell};
@@ -56,6 +55,72 @@ impl RateLimiter {
     }
 }
 
+/// What is left of a third-party API quota, as last reported by its response
+/// headers, shared by every server instance.
+pub struct ApiBudget {
+    client: redis::Client,
+    key: String,
+}
+
+impl ApiBudget {
+    pub fn new(redis_url: &str, name: &str) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+            key: format!("budget:{}", name),
+        })
+    }
+
+    /// Spends one request unless no more than `reserve` are left. An
+    /// unknown budget (nothing recorded yet, or expired) allows the call.
+    pub async fn take(&self, reserve: u64) -> Result<bool> {
+        static SCRIPT: &str = r#"
+            local remaining = tonumber(redis.call('GET', KEYS[1]) or '-1')
+            if remaining == -1 then
+                return 1
+            elseif remaining > tonumber(ARGV[1]) then
+                redis.call('DECR', KEYS[1])
+                return 1
+            else
+                return 0
+            end
+        "#;
+
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+
+        let allowed: i64 = Script::new(SCRIPT)
+            .key(&self.key)
+            .arg(reserve as i64)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(allowed == 1)
+    }
+
+    /// Overwrites the budget with the provider's own count. The window
+    /// starts with the first count recorded and isn't extended by later
+    /// ones, so the budget resets when the provider's quota does.
+    pub async fn record(&self, remaining: u64, window_secs: u64) -> Result<()> {
+        static SCRIPT: &str = r#"
+            if redis.call('TTL', KEYS[1]) < 0 then
+                redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+            else
+                redis.call('SET', KEYS[1], ARGV[1], 'KEEPTTL')
+            end
+        "#;
+
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+
+        let _: () = Script::new(SCRIPT)
+            .key(&self.key)
+            .arg(remaining as i64)
+            .arg(window_secs as i64)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
 static REDIS: OnceCell<Mutex<RateLimiter>> = OnceCell::const_new();
 
 async fn init_redis() -> &'static Mutex<RateLimiter> {
@@ -8,6 +8,7 @@ use crate::pages::dashboard::Dashboard;
 use crate::pages::home::Home;
 use crate::pages::login::Login;
 use crate::pages::profile::UserProfile;
+use crate::pages::public::PublicBook;
 use crate::pages::signup::Register;
 use crate::pages::success::SuccessPage;
 use dioxus::prelude::*;
@@ -24,8 +25,8 @@ pub enum Route {
     #[route("/signup")]
     Register {},
     #[end_layout]
-    #[route("/dashboard/book/read/:id")]
-    ReadBook { id: String },
+    #[route("/dashboard/book/read/:id?:chapter")]
+    ReadBook { id: String, chapter: String },
     #[route("/dashboard/book/edit/:id")]
     EditBook { id: String },
     #[route("/dashboard")]
@@ -34,4 +35,6 @@ pub enum Route {
     SuccessPage {},
     #[route("/dashboard/profile/:id")]
     UserProfile { id: String },
+    #[route("/b/:slug?:token")]
+    PublicBook { slug: String, token: String },
 }
@@ -1,5 +1,19 @@
+pub(crate) mod ai_call;
+pub(crate) mod annotation;
+pub(crate) mod audiobook;
 pub(crate) mod auth;
+pub(crate) mod backmatter;
 pub(crate) mod book;
+pub(crate) mod catalog;
 pub(crate) mod common;
 pub(crate) mod conversation;
+pub(crate) mod cover;
+pub(crate) mod edition;
+pub(crate) mod entitlement;
+pub(crate) mod export;
+pub(crate) mod illustration;
+pub(crate) mod progress;
+pub(crate) mod quiz;
+pub(crate) mod search;
+pub(crate) mod study;
 pub(crate) mod subscription;
@@ -0,0 +1,32 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum CallOutcome {
+    Success,
+    Failure,
+}
+
+/// One request to a text or image model, as recorded in the `ai_calls`
+/// ledger.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AiCall {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    /// Whom the call was made for; `None` for system work.
+    pub user: Option<ObjectId>,
+    pub model: String,
+    /// Which prompt produced the call, e.g. `chapter_html`.
+    pub template: String,
+    /// Token counts reported by the model; zero when the call failed.
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub latency_ms: u64,
+    pub outcome: CallOutcome,
+    pub error: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
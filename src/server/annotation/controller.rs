@@ -0,0 +1,334 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use futures_util::TryStreamExt;
+use std::collections::HashMap;
+
+use crate::server::annotation::model::Annotation;
+use crate::server::annotation::model::AnnotationKind;
+use crate::server::annotation::model::HIGHLIGHT_COLORS;
+use crate::server::annotation::request::AnnotationRequest;
+use crate::server::annotation::request::CreateAnnotationRequest;
+use crate::server::annotation::request::ListAnnotationsRequest;
+use crate::server::annotation::request::UpdateAnnotationRequest;
+use crate::server::annotation::response::NotebookEntry;
+use crate::server::auth::controller::auth;
+use crate::server::book::model::Book;
+use crate::server::book::model::Chapter;
+use crate::server::common::response::SuccessResponse;
+
+#[cfg(feature = "server")]
+use {crate::db::get_client, mongodb::options::ReturnDocument};
+
+fn validate_color(color: Option<String>) -> Result<Option<String>, ServerFnError> {
+    match color {
+        Some(color) if !HIGHLIGHT_COLORS.contains(&color.as_str()) => {
+            Err(ServerFnError::new("Unsupported highlight color"))
+        }
+        color => Ok(color),
+    }
+}
+
+#[server]
+pub async fn create_annotation(
+    req: CreateAnnotationRequest,
+) -> Result<SuccessResponse<Annotation>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+    let chapter_collection = db.collection::<Chapter>("chapters");
+    let annotation_collection = db.collection::<Annotation>("annotations");
+
+    let chapter_id = ObjectId::parse_str(&req.chapter_id)
+        .map_err(|_| ServerFnError::new("Invalid chapter ID"))?;
+    let chapter = chapter_collection
+        .find_one(doc! { "_id": chapter_id })
+        .await?
+        .ok_or(ServerFnError::new("Chapter not found"))?;
+    book_collection
+        .find_one(doc! { "_id": chapter.book_id, "user": user.id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Chapter not found"))?;
+
+    let note = req.note.filter(|note| !note.trim().is_empty());
+    let selector = req
+        .selector
+        .filter(|selector| !selector.quote.exact.is_empty());
+    match req.kind {
+        AnnotationKind::Highlight if selector.is_none() => {
+            return Err(ServerFnError::new("A highlight needs selected text"));
+        }
+        AnnotationKind::Note if note.is_none() => {
+            return Err(ServerFnError::new("A note can't be blank"));
+        }
+        _ => {}
+    }
+    let color = match validate_color(req.color)? {
+        None if req.kind == AnnotationKind::Highlight => Some(HIGHLIGHT_COLORS[0].to_string()),
+        color => color,
+    };
+
+    let now = Utc::now();
+    let annotation = Annotation {
+        id: ObjectId::new(),
+        user: user.id,
+        book_id: chapter.book_id,
+        chapter_id,
+        kind: req.kind,
+        selector,
+        color,
+        note,
+        created_at: now,
+        updated_at: now,
+    };
+    annotation_collection.insert_one(&annotation).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: annotation,
+    })
+}
+
+#[server]
+pub async fn update_annotation(
+    req: UpdateAnnotationRequest,
+) -> Result<SuccessResponse<Annotation>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let annotation_collection = db.collection::<Annotation>("annotations");
+
+    let annotation_id = ObjectId::parse_str(&req.annotation_id)
+        .map_err(|_| ServerFnError::new("Invalid annotation ID"))?;
+    let color = validate_color(req.color)?;
+    let note = req.note.filter(|note| !note.trim().is_empty());
+
+    let annotation = annotation_collection
+        .find_one_and_update(
+            doc! { "_id": annotation_id, "user": user.id },
+            doc! { "$set": { "color": color, "note": note, "updatedAt": Utc::now() } },
+        )
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or(ServerFnError::new("Annotation not found"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: annotation,
+    })
+}
+
+#[server]
+pub async fn delete_annotation(
+    req: AnnotationRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let annotation_collection = db.collection::<Annotation>("annotations");
+
+    let annotation_id = ObjectId::parse_str(&req.annotation_id)
+        .map_err(|_| ServerFnError::new("Invalid annotation ID"))?;
+
+    let result = annotation_collection
+        .delete_one(doc! { "_id": annotation_id, "user": user.id })
+        .await?;
+    if result.deleted_count == 0 {
+        return Err(ServerFnError::new("Annotation not found"));
+    }
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Annotation deleted".into(),
+    })
+}
+
+#[server]
+pub async fn list_annotations(
+    req: ListAnnotationsRequest,
+) -> Result<SuccessResponse<Vec<NotebookEntry>>, ServerFnError> {
+    let user = auth(req.token.clone())
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: load_notebook(&db, user.id, &req).await?,
+    })
+}
+
+#[server]
+pub async fn export_annotations_markdown(
+    req: ListAnnotationsRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token.clone())
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let entries = load_notebook(&db, user.id, &req).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: notebook_to_markdown(&entries),
+    })
+}
+
+/// Loads the user's annotations with their book and chapter titles, in
+/// reading order. Annotations on deleted books or chapters are skipped.
+#[cfg(feature = "server")]
+async fn load_notebook(
+    db: &mongodb::Database,
+    user_id: ObjectId,
+    req: &ListAnnotationsRequest,
+) -> Result<Vec<NotebookEntry>, ServerFnError> {
+    let mut filter = doc! { "user": user_id };
+    if let Some(book_id) = req.book_id.as_deref().filter(|id| !id.is_empty()) {
+        let book_id =
+            ObjectId::parse_str(book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+        filter.insert("book_id", book_id);
+    }
+    if let Some(chapter_id) = req.chapter_id.as_deref().filter(|id| !id.is_empty()) {
+        let chapter_id = ObjectId::parse_str(chapter_id)
+            .map_err(|_| ServerFnError::new("Invalid chapter ID"))?;
+        filter.insert("chapter_id", chapter_id);
+    }
+
+    let annotations = db
+        .collection::<Annotation>("annotations")
+        .find(filter)
+        .await?
+        .try_collect::<Vec<Annotation>>()
+        .await?;
+    if annotations.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut book_ids = annotations.iter().map(|a| a.book_id).collect::<Vec<_>>();
+    book_ids.sort();
+    book_ids.dedup();
+    let mut chapter_ids = annotations.iter().map(|a| a.chapter_id).collect::<Vec<_>>();
+    chapter_ids.sort();
+    chapter_ids.dedup();
+
+    let books: HashMap<ObjectId, String> = db
+        .collection::<Book>("books")
+        .find(doc! { "_id": { "$in": book_ids }, "user": user_id, "deletedAt": null })
+        .await?
+        .try_collect::<Vec<Book>>()
+        .await?
+        .into_iter()
+        .map(|book| (book.id, book.title))
+        .collect();
+    let chapters: HashMap<ObjectId, (String, u32)> = db
+        .collection::<Chapter>("chapters")
+        .find(doc! { "_id": { "$in": chapter_ids } })
+        .await?
+        .try_collect::<Vec<Chapter>>()
+        .await?
+        .into_iter()
+        .map(|chapter| (chapter.id, (chapter.title, chapter.position)))
+        .collect();
+
+    let mut entries = annotations
+        .into_iter()
+        .filter_map(|annotation| {
+            let book_title = books.get(&annotation.book_id)?.clone();
+            let (chapter_title, chapter_position) = chapters.get(&annotation.chapter_id)?.clone();
+            Some(NotebookEntry {
+                annotation,
+                book_title,
+                chapter_title,
+                chapter_position,
+            })
+        })
+        .collect::<Vec<NotebookEntry>>();
+
+    entries.sort_by(|a, b| {
+        (&a.book_title, a.annotation.book_id, a.chapter_position)
+            .cmp(&(&b.book_title, b.annotation.book_id, b.chapter_position))
+            .then_with(|| {
+                let start = |entry: &NotebookEntry| {
+                    entry
+                        .annotation
+                        .selector
+                        .as_ref()
+                        .map(|selector| selector.position.start)
+                        .unwrap_or(0)
+                };
+                start(a).cmp(&start(b))
+            })
+    });
+
+    Ok(entries)
+}
+
+/// Renders notebook entries as Markdown, grouped by book and chapter.
+pub fn notebook_to_markdown(entries: &[NotebookEntry]) -> String {
+    let mut markdown = String::from("# Notebook\n");
+    let mut current_book = None;
+    let mut current_chapter = None;
+
+    for entry in entries {
+        let annotation = &entry.annotation;
+        if current_book != Some(annotation.book_id) {
+            current_book = Some(annotation.book_id);
+            current_chapter = None;
+            markdown.push_str(&format!("\n## {}\n", entry.book_title));
+        }
+        if current_chapter != Some(annotation.chapter_id) {
+            current_chapter = Some(annotation.chapter_id);
+            markdown.push_str(&format!(
+                "\n### {}. {}\n",
+                entry.chapter_position + 1,
+                entry.chapter_title
+            ));
+        }
+
+        markdown.push('\n');
+        if annotation.kind == AnnotationKind::Bookmark {
+            markdown.push_str("- Bookmark\n");
+        }
+        if let Some(selector) = &annotation.selector {
+            for line in selector.quote.exact.lines() {
+                markdown.push_str(&format!("> {}\n", line));
+            }
+            if let Some(color) = &annotation.color {
+                markdown.push_str(&format!(">\n> *Highlighted in {}*\n", color));
+            }
+        }
+        if let Some(note) = &annotation.note {
+            if annotation.selector.is_some() {
+                markdown.push('\n');
+            }
+            markdown.push_str(note.trim());
+            markdown.push('\n');
+        }
+    }
+
+    markdown
+}
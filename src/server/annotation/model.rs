@@ -0,0 +1,101 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const HIGHLIGHT_COLORS: [&str; 4] = ["yellow", "green", "blue", "pink"];
+
+/// Characters of surrounding text kept on each side of a quote.
+pub const QUOTE_CONTEXT_LEN: usize = 32;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum AnnotationKind {
+    #[default]
+    Highlight,
+    Note,
+    Bookmark,
+}
+
+/// The quoted passage plus a little context on either side, used to find the
+/// passage again after the chapter text shifts.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct TextQuoteSelector {
+    pub exact: String,
+    pub prefix: String,
+    pub suffix: String,
+}
+
+/// Offsets into the chapter's rendered text, in UTF-16 code units so they
+/// line up with DOM ranges.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub struct TextPositionSelector {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct TextSelector {
+    pub quote: TextQuoteSelector,
+    pub position: TextPositionSelector,
+}
+
+impl TextSelector {
+    /// Finds the selected passage in `text`, returning UTF-16 offsets.
+    ///
+    /// The stored position wins while it still points at the quote. After an
+    /// edit, every occurrence of the quote is scored by how much of its old
+    /// context survives, with ties going to the one nearest the old position.
+    pub fn anchor(&self, text: &str) -> Option<(usize, usize)> {
+        let text: Vec<u16> = text.encode_utf16().collect();
+        let exact: Vec<u16> = self.quote.exact.encode_utf16().collect();
+        if exact.is_empty() || exact.len() > text.len() {
+            return None;
+        }
+
+        let start = self.position.start as usize;
+        let end = self.position.end as usize;
+        if start < end && end <= text.len() && text[start..end] == exact[..] {
+            return Some((start, end));
+        }
+
+        let prefix: Vec<u16> = self.quote.prefix.encode_utf16().collect();
+        let suffix: Vec<u16> = self.quote.suffix.encode_utf16().collect();
+
+        (0..=text.len() - exact.len())
+            .filter(|&index| text[index..index + exact.len()] == exact[..])
+            .max_by_key(|&index| {
+                let before = text[..index]
+                    .iter()
+                    .rev()
+                    .zip(prefix.iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let after = text[index + exact.len()..]
+                    .iter()
+                    .zip(suffix.iter())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                (before + after, std::cmp::Reverse(index.abs_diff(start)))
+            })
+            .map(|index| (index, index + exact.len()))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Annotation {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    pub chapter_id: ObjectId,
+    pub kind: AnnotationKind,
+    /// `None` for bookmarks on a whole chapter.
+    pub selector: Option<TextSelector>,
+    pub color: Option<String>,
+    pub note: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
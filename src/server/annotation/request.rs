@@ -0,0 +1,34 @@
+use crate::server::annotation::model::AnnotationKind;
+use crate::server::annotation::model::TextSelector;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateAnnotationRequest {
+    pub token: String,
+    pub chapter_id: String,
+    pub kind: AnnotationKind,
+    pub selector: Option<TextSelector>,
+    pub color: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateAnnotationRequest {
+    pub token: String,
+    pub annotation_id: String,
+    pub color: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnnotationRequest {
+    pub token: String,
+    pub annotation_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ListAnnotationsRequest {
+    pub token: String,
+    pub book_id: Option<String>,
+    pub chapter_id: Option<String>,
+}
@@ -0,0 +1,10 @@
+use crate::server::annotation::model::Annotation;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NotebookEntry {
+    pub annotation: Annotation,
+    pub book_title: String,
+    pub chapter_title: String,
+    pub chapter_position: u32,
+}
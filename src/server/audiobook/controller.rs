@@ -0,0 +1,438 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use dioxus_logger::tracing;
+use futures_util::TryStreamExt;
+
+use crate::server::audiobook::model::AudioStatus;
+use crate::server::audiobook::model::AudioTrack;
+use crate::server::audiobook::model::Audiobook;
+use crate::server::audiobook::request::GenerateAudiobookRequest;
+use crate::server::audiobook::response::AudioTrackView;
+use crate::server::audiobook::response::AudiobookView;
+use crate::server::auth::controller::auth;
+use crate::server::book::model::Book;
+use crate::server::book::model::Chapter;
+use crate::server::book::model::ChapterKind;
+use crate::server::book::request::BookRequest;
+use crate::server::common::html::strip_tags;
+use crate::server::common::response::SuccessResponse;
+use crate::server::common::zip::ZipWriter;
+
+#[cfg(feature = "server")]
+use {
+    crate::db::get_client,
+    crate::server::book::controller::load_outline,
+    crate::storage,
+    crate::tts::{get_tts, wav_duration_ms, TextToSpeech},
+    std::sync::Arc,
+};
+
+/// Storage prefix holding every audio file of a book.
+pub fn audio_prefix(book_id: ObjectId) -> String {
+    format!("audio/{}", book_id.to_hex())
+}
+
+/// Index and bibliography pages are lists of links and citations that make
+/// no sense read aloud.
+fn is_narrated(chapter: &Chapter) -> bool {
+    matches!(chapter.kind, ChapterKind::Body | ChapterKind::Glossary)
+}
+
+fn narration_text(chapter: &Chapter) -> String {
+    let body = if chapter.html.trim().is_empty() {
+        chapter.markdown.clone()
+    } else {
+        strip_tags(&chapter.html)
+    };
+    format!("{}.\n\n{}", chapter.title, body)
+}
+
+fn file_stem(position: u32, title: &str) -> String {
+    let title = title
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-')
+        .collect::<String>();
+    format!("{:02} - {}", position + 1, title.trim())
+}
+
+/// FFmpeg metadata with one `[CHAPTER]` per track, so the bundle can be
+/// muxed into an M4B with chapter markers.
+fn chapter_markers(title: &str, tracks: &[AudioTrack]) -> String {
+    let escape = |value: &str| {
+        value
+            .replace('\\', "\\\\")
+            .replace('=', "\\=")
+            .replace(';', "\\;")
+            .replace('#', "\\#")
+            .replace('\n', "\\\n")
+    };
+    let mut markers = format!(";FFMETADATA1\ntitle={}\n", escape(title));
+    let mut start = 0;
+    for track in tracks {
+        markers.push_str(&format!(
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            start,
+            start + track.duration_ms,
+            escape(&track.title)
+        ));
+        start += track.duration_ms;
+    }
+    markers
+}
+
+/// A zip of every chapter's WAV, an M3U playlist and the chapter markers.
+#[cfg(feature = "server")]
+async fn build_bundle(book: &Book, tracks: &[AudioTrack]) -> anyhow::Result<String> {
+    let mut zip = ZipWriter::new();
+    let mut playlist = String::from("#EXTM3U\n");
+    for track in tracks {
+        let Some(key) = &track.key else {
+            anyhow::bail!("chapter '{}' has no audio", track.title);
+        };
+        let name = format!("{}.wav", file_stem(track.position, &track.title));
+        zip.add(&name, &storage::get(key).await?);
+        playlist.push_str(&format!(
+            "#EXTINF:{},{}\n{}\n",
+            track.duration_ms / 1000,
+            track.title,
+            name
+        ));
+    }
+    zip.add("playlist.m3u", playlist.as_bytes());
+    zip.add("chapters.txt", chapter_markers(&book.title, tracks).as_bytes());
+
+    let key = format!(
+        "{}/{}.zip",
+        audio_prefix(book.id),
+        storage::random_name()
+    );
+    storage::put(&key, &zip.finish()).await?;
+    Ok(key)
+}
+
+/// Narrates the book's tracks one after another, then bundles them.
+#[cfg(feature = "server")]
+async fn narrate_book(
+    db: mongodb::Database,
+    tts: Arc<dyn TextToSpeech>,
+    book: Book,
+    chapters: Vec<Chapter>,
+    mut tracks: Vec<AudioTrack>,
+) {
+    let track_collection = db.collection::<AudioTrack>("audio_tracks");
+
+    for (track, chapter) in tracks.iter_mut().zip(&chapters) {
+        let _ = track_collection
+            .update_one(
+                doc! { "_id": track.id },
+                doc! { "$set": { "status": "Generating", "updatedAt": Utc::now() } },
+            )
+            .await;
+
+        let text = narration_text(chapter);
+        let language = chapter.language.clone();
+        let engine = tts.clone();
+        let audio = tokio::task::spawn_blocking(move || engine.synthesize(&text, &language))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+        let stored = match audio {
+            Ok(wav) => {
+                let key = format!(
+                    "{}/{}-{:03}.wav",
+                    audio_prefix(book.id),
+                    storage::random_name(),
+                    track.position
+                );
+                storage::put(&key, &wav)
+                    .await
+                    .map(|_| (key, wav_duration_ms(&wav).unwrap_or_default()))
+            }
+            Err(err) => Err(err),
+        };
+
+        let update = match stored {
+            Ok((key, duration_ms)) => {
+                track.status = AudioStatus::Ready;
+                track.key = Some(key.clone());
+                track.duration_ms = duration_ms;
+                doc! {
+                    "status": "Ready",
+                    "key": key,
+                    "duration_ms": duration_ms as i64,
+                    "error": null,
+                    "updatedAt": Utc::now(),
+                }
+            }
+            Err(err) => {
+                tracing::error!("narrating chapter {} failed: {}", chapter.id, err);
+                track.status = AudioStatus::Failed;
+                doc! {
+                    "status": "Failed",
+                    "error": err.to_string(),
+                    "updatedAt": Utc::now(),
+                }
+            }
+        };
+        let _ = track_collection
+            .update_one(doc! { "_id": track.id }, doc! { "$set": update })
+            .await;
+    }
+
+    if tracks.iter().all(|track| track.status == AudioStatus::Ready) {
+        match build_bundle(&book, &tracks).await {
+            Ok(key) => {
+                let _ = db
+                    .collection::<Audiobook>("audiobooks")
+                    .update_one(
+                        doc! { "book_id": book.id },
+                        doc! { "$set": { "bundle_key": key, "updatedAt": Utc::now() } },
+                    )
+                    .await;
+            }
+            Err(err) => tracing::error!("bundling audiobook {} failed: {}", book.id, err),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+fn audiobook_view(audiobook: Option<Audiobook>, tracks: Vec<AudioTrack>) -> AudiobookView {
+    let mut start_ms = 0;
+    let tracks = tracks
+        .into_iter()
+        .map(|track| {
+            let view = AudioTrackView {
+                chapter_id: track.chapter_id.to_hex(),
+                title: track.title,
+                position: track.position,
+                status: track.status,
+                url: track.key.as_deref().map(storage::url),
+                duration_ms: track.duration_ms,
+                start_ms,
+                error: track.error,
+            };
+            start_ms += track.duration_ms;
+            view
+        })
+        .collect();
+
+    AudiobookView {
+        engine: audiobook.as_ref().map(|audiobook| audiobook.engine.clone()),
+        bundle_url: audiobook
+            .and_then(|audiobook| audiobook.bundle_key)
+            .as_deref()
+            .map(storage::url),
+        tracks,
+        total_duration_ms: start_ms,
+    }
+}
+
+/// Starts narrating every chapter of a book, replacing any earlier
+/// audiobook. Tracks are filled in by a background task.
+#[server]
+pub async fn generate_audiobook(
+    req: GenerateAudiobookRequest,
+) -> Result<SuccessResponse<AudiobookView>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let track_collection = db.collection::<AudioTrack>("audio_tracks");
+    let audiobook_collection = db.collection::<Audiobook>("audiobooks");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let (book, chapters) = load_outline(&db, user.id, book_id).await?;
+    let chapters = chapters
+        .into_iter()
+        .filter(|chapter| is_narrated(chapter) && !narration_text(chapter).trim().is_empty())
+        .collect::<Vec<Chapter>>();
+    if chapters.is_empty() {
+        return Err(ServerFnError::new("Generate chapter content first"));
+    }
+
+    if track_collection
+        .count_documents(doc! {
+            "book_id": book_id,
+            "status": { "$in": ["Pending", "Generating"] },
+        })
+        .await?
+        > 0
+    {
+        return Err(ServerFnError::new("An audiobook is already being generated"));
+    }
+
+    track_collection
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    storage::delete(&audio_prefix(book_id))
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+
+    let tts = get_tts();
+    let now = Utc::now();
+    let tracks = chapters
+        .iter()
+        .enumerate()
+        .map(|(position, chapter)| AudioTrack {
+            id: ObjectId::new(),
+            user: user.id,
+            book_id,
+            chapter_id: chapter.id,
+            position: position as u32,
+            title: chapter.title.clone(),
+            status: AudioStatus::Pending,
+            engine: tts.name().to_string(),
+            key: None,
+            duration_ms: 0,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        })
+        .collect::<Vec<AudioTrack>>();
+    track_collection.insert_many(&tracks).await?;
+
+    let audiobook = audiobook_collection
+        .find_one_and_update(
+            doc! { "book_id": book_id },
+            doc! {
+                "$set": {
+                    "user": user.id,
+                    "engine": tts.name(),
+                    "bundle_key": null,
+                    "updatedAt": now,
+                },
+                "$setOnInsert": { "createdAt": now },
+            },
+        )
+        .upsert(true)
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await?;
+
+    tokio::spawn(narrate_book(
+        db.clone(),
+        tts,
+        book,
+        chapters,
+        tracks.clone(),
+    ));
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: audiobook_view(audiobook, tracks),
+    })
+}
+
+#[server]
+pub async fn get_audiobook(
+    req: BookRequest,
+) -> Result<SuccessResponse<AudiobookView>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let audiobook = db
+        .collection::<Audiobook>("audiobooks")
+        .find_one(doc! { "book_id": book_id, "user": user.id })
+        .await?;
+    let tracks = db
+        .collection::<AudioTrack>("audio_tracks")
+        .find(doc! { "book_id": book_id, "user": user.id })
+        .sort(doc! { "position": 1 })
+        .await?
+        .try_collect::<Vec<AudioTrack>>()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: audiobook_view(audiobook, tracks),
+    })
+}
+
+/// Tracks left pending by a restart will never finish; mark them failed so
+/// the book can be narrated again.
+#[cfg(feature = "server")]
+pub async fn fail_interrupted_narrations() -> Result<u64, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let result = db
+        .collection::<AudioTrack>("audio_tracks")
+        .update_many(
+            doc! { "status": { "$in": ["Pending", "Generating"] } },
+            doc! {
+                "$set": {
+                    "status": "Failed",
+                    "error": "Interrupted by a server restart",
+                }
+            },
+        )
+        .await?;
+    Ok(result.modified_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(title: &str, duration_ms: u64) -> AudioTrack {
+        AudioTrack {
+            id: ObjectId::new(),
+            user: ObjectId::new(),
+            book_id: ObjectId::new(),
+            chapter_id: ObjectId::new(),
+            position: 0,
+            title: title.to_string(),
+            status: AudioStatus::Ready,
+            engine: "silent".to_string(),
+            key: None,
+            duration_ms,
+            error: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn file_stem_numbers_from_one_and_drops_path_characters() {
+        assert_eq!(file_stem(0, "Getting Started"), "01 - Getting Started");
+        assert_eq!(file_stem(11, " Traits: a/b \\ <c>? "), "12 - Traits ab  c");
+        assert_eq!(file_stem(2, "Café-crème"), "03 - Café-crème");
+    }
+
+    #[test]
+    fn chapter_markers_chain_start_and_end_times() {
+        let markers = chapter_markers(
+            "Rust",
+            &[track("Ownership", 1_500), track("Borrowing", 2_000)],
+        );
+        assert_eq!(
+            markers,
+            ";FFMETADATA1\ntitle=Rust\n\
+             \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=1500\ntitle=Ownership\n\
+             \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=1500\nEND=3500\ntitle=Borrowing\n"
+        );
+    }
+
+    #[test]
+    fn chapter_markers_escape_metadata_specials() {
+        let markers = chapter_markers("a=b;c#d\\e\nf", &[]);
+        assert_eq!(markers, ";FFMETADATA1\ntitle=a\\=b\\;c\\#d\\\\e\\\nf\n");
+    }
+}
@@ -0,0 +1,53 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum AudioStatus {
+    #[default]
+    Pending,
+    Generating,
+    Ready,
+    Failed,
+}
+
+/// The narration of one chapter.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AudioTrack {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    pub chapter_id: ObjectId,
+    pub position: u32,
+    pub title: String,
+    pub status: AudioStatus,
+    /// Speech engine that produced (or is producing) the audio.
+    pub engine: String,
+    /// Media storage key of the WAV file once ready.
+    pub key: Option<String>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One generation run over a book; the bundle is built once every track is
+/// ready.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Audiobook {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    pub engine: String,
+    pub bundle_key: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
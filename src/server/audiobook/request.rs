@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateAudiobookRequest {
+    pub token: String,
+    pub book_id: String,
+}
@@ -0,0 +1,23 @@
+use crate::server::audiobook::model::AudioStatus;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AudioTrackView {
+    pub chapter_id: String,
+    pub title: String,
+    pub position: u32,
+    pub status: AudioStatus,
+    pub url: Option<String>,
+    pub duration_ms: u64,
+    /// Chapter marker: where the track starts in the whole audiobook.
+    pub start_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct AudiobookView {
+    pub engine: Option<String>,
+    pub tracks: Vec<AudioTrackView>,
+    pub bundle_url: Option<String>,
+    pub total_duration_ms: u64,
+}
@@ -15,6 +15,7 @@ use crate::server::auth::response::{
 };
 use crate::server::book::model::Book;
 use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::model::Plan;
 
 #[cfg(feature = "server")]
 use {
@@ -58,9 +59,10 @@ pub async fn register_user(
         name: body.name,
         email: body.email.to_lowercase(),
         password: hashed_password,
-        role: "user".into(),
+        role: Plan::Free.role().into(),
         photo: "".into(),
         verified: false,
+        stripe_customer: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -242,7 +244,9 @@ pub async fn dashboard_overview() -> Result<SuccessResponse<DashboardResponse>,
     let users = user_collection.estimated_document_count().await?;
     let books = book_collection.estimated_document_count().await?;
     let paid_users = user_collection
-        .count_documents(doc! { "role": { "$ne": "free" } })
+        .count_documents(doc! {
+            "role": { "$in": Plan::PAID.iter().map(|plan| plan.role()).collect::<Vec<&str>>() },
+        })
         .await?;
 
     Ok(SuccessResponse {
@@ -13,6 +13,9 @@ pub struct User {
     pub role: String,
     pub photo: String,
     pub verified: bool,
+    /// The Stripe customer paying for this user, created at first checkout.
+    #[serde(default, rename = "stripeCustomer")]
+    pub stripe_customer: Option<String>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
@@ -0,0 +1,643 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::server::auth::controller::auth;
+use crate::server::backmatter::model::BackMatter;
+use crate::server::backmatter::model::GlossaryEntry;
+use crate::server::backmatter::model::IndexEntry;
+use crate::server::backmatter::model::Reference;
+use crate::server::backmatter::request::GenerateBackMatterRequest;
+use crate::server::book::model::Chapter;
+use crate::server::book::model::ChapterKind;
+use crate::server::book::request::BookRequest;
+use crate::server::common::html::escape_html;
+use crate::server::common::html::strip_tags;
+use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::controller::check_model;
+
+#[cfg(feature = "server")]
+use {
+    crate::ai::{chat_model, generate, get_ai},
+    crate::db::get_client,
+    crate::server::book::controller::{load_outline, save_positions},
+    gems::chat::ChatBuilder,
+    gems::messages::Content,
+    gems::messages::Message,
+    gems::models::Model,
+    gems::traits::CTrait,
+};
+
+/// Per-chapter text budget for the extraction prompt.
+const MAX_CHARS_PER_CHAPTER: usize = 3_000;
+const GENERATION_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedBackMatter {
+    glossary: Vec<GeneratedTerm>,
+    index_terms: Vec<String>,
+    references: Vec<GeneratedReference>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedTerm {
+    term: String,
+    definition: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedReference {
+    #[serde(rename = "type")]
+    entry_type: String,
+    title: String,
+    authors: Vec<String>,
+    year: Option<String>,
+    publisher: Option<String>,
+    url: Option<String>,
+}
+
+const BACK_MATTER_SCHEMA: &str = r#"{
+  "glossary": [ { "term": string, "definition": string } ],
+  "index_terms": [ string ],
+  "references": [
+    { "type": "book" | "article" | "online" | "misc", "title": string, "authors": [string], "year": string | null, "publisher": string | null, "url": string | null }
+  ]
+}"#;
+
+fn parse_generated_back_matter(raw: &str) -> Result<GeneratedBackMatter, String> {
+    let json = raw
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let generated: GeneratedBackMatter = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+    if let Some(index) = generated
+        .glossary
+        .iter()
+        .position(|entry| entry.term.trim().is_empty() || entry.definition.trim().is_empty())
+    {
+        return Err(format!("glossary[{}] has an empty field", index));
+    }
+    if let Some(index) = generated
+        .references
+        .iter()
+        .position(|reference| reference.title.trim().is_empty())
+    {
+        return Err(format!("references[{}] has no title", index));
+    }
+
+    Ok(generated)
+}
+
+/// The chapter's readable text, preferring Markdown over rendered HTML.
+fn chapter_text(chapter: &Chapter) -> String {
+    if chapter.markdown.trim().is_empty() {
+        strip_tags(&chapter.html)
+    } else {
+        chapter.markdown.clone()
+    }
+}
+
+/// URLs cited anywhere in the chapters, in order of first appearance.
+fn cited_urls(chapters: &[Chapter]) -> Vec<String> {
+    let url_re = Regex::new(r#"https?://[^\s)\]"'<>]+"#).unwrap();
+    let mut urls: Vec<String> = Vec::new();
+    for chapter in chapters {
+        for source in [&chapter.markdown, &chapter.html] {
+            for found in url_re.find_iter(source) {
+                let url = found.as_str().trim_end_matches(['.', ',', ';']).to_string();
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+    }
+    urls
+}
+
+/// Builds `surname + year` citation keys, suffixed -2, -3, ... on collision.
+fn citation_key(reference: &GeneratedReference, taken: &mut HashMap<String, u32>) -> String {
+    let stem = reference
+        .authors
+        .first()
+        .and_then(|author| author.split_whitespace().last())
+        .unwrap_or_else(|| reference.title.split_whitespace().next().unwrap_or("ref"))
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    let stem = format!(
+        "{}{}",
+        if stem.is_empty() { "ref" } else { &stem },
+        reference.year.as_deref().unwrap_or("")
+    );
+
+    let count = taken.entry(stem.clone()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        stem
+    } else {
+        // Stems are alphanumeric, so the dash keeps suffixed keys apart
+        // from every stem.
+        format!("{}-{}", stem, count)
+    }
+}
+
+fn build_index(terms: Vec<String>, chapters: &[Chapter]) -> Vec<IndexEntry> {
+    let texts = chapters
+        .iter()
+        .map(|chapter| (chapter.id, chapter_text(chapter).to_lowercase()))
+        .collect::<Vec<(ObjectId, String)>>();
+
+    let mut index: Vec<IndexEntry> = Vec::new();
+    for term in terms {
+        let term = term.trim().to_string();
+        let needle = term.to_lowercase();
+        if needle.is_empty()
+            || index
+                .iter()
+                .any(|entry| entry.term.to_lowercase() == needle)
+        {
+            continue;
+        }
+        let chapter_ids = texts
+            .iter()
+            .filter(|(_, text)| text.contains(&needle))
+            .map(|(id, _)| *id)
+            .collect::<Vec<ObjectId>>();
+        if !chapter_ids.is_empty() {
+            index.push(IndexEntry { term, chapter_ids });
+        }
+    }
+    index.sort_by_key(|entry| entry.term.to_lowercase());
+    index
+}
+
+fn render_glossary(glossary: &[GlossaryEntry]) -> (String, String) {
+    let markdown = glossary
+        .iter()
+        .map(|entry| format!("**{}**: {}", entry.term, entry.definition))
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    let html = format!(
+        "<dl>{}</dl>",
+        glossary
+            .iter()
+            .map(|entry| format!(
+                "<dt><strong>{}</strong></dt><dd>{}</dd>",
+                escape_html(&entry.term),
+                escape_html(&entry.definition)
+            ))
+            .collect::<String>()
+    );
+    (markdown, html)
+}
+
+fn render_index(index: &[IndexEntry], chapters: &[Chapter]) -> (String, String) {
+    let chapters: HashMap<ObjectId, (usize, &Chapter)> = chapters
+        .iter()
+        .enumerate()
+        .map(|(number, chapter)| (chapter.id, (number + 1, chapter)))
+        .collect();
+
+    let mut markdown = String::new();
+    let mut html = String::new();
+    let mut letter = None;
+    for entry in index {
+        let initial = entry
+            .term
+            .chars()
+            .next()
+            .map(|c| c.to_uppercase().collect::<String>());
+        if initial != letter {
+            if letter.is_some() {
+                html.push_str("</ul>");
+            }
+            letter = initial.clone();
+            let heading = initial.unwrap_or_default();
+            markdown.push_str(&format!("\n### {}\n\n", heading));
+            html.push_str(&format!("<h3>{}</h3><ul>", escape_html(&heading)));
+        }
+
+        let links = entry
+            .chapter_ids
+            .iter()
+            .filter_map(|id| chapters.get(id))
+            .map(|(number, chapter)| (*number, chapter.anchor()))
+            .collect::<Vec<(usize, String)>>();
+        markdown.push_str(&format!(
+            "- {}: {}\n",
+            entry.term,
+            links
+                .iter()
+                .map(|(number, anchor)| format!("[{}](#{})", number, anchor))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+        html.push_str(&format!(
+            "<li>{}: {}</li>",
+            escape_html(&entry.term),
+            links
+                .iter()
+                .map(|(number, anchor)| format!("<a href=\"#{}\">{}</a>", anchor, number))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+    if letter.is_some() {
+        html.push_str("</ul>");
+    }
+    (markdown.trim().to_string(), html)
+}
+
+/// Whether a model-supplied URL is safe to link to; anything else, such as
+/// `javascript:`, is shown as text.
+fn is_web_url(url: &str) -> bool {
+    let url = url.trim().to_ascii_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn render_bibliography(bibliography: &[Reference]) -> (String, String) {
+    let format_reference = |reference: &Reference, escape: &dyn Fn(&str) -> String| {
+        let mut parts = Vec::new();
+        if !reference.authors.is_empty() {
+            parts.push(escape(&reference.authors.join(", ")));
+        }
+        if let Some(year) = &reference.year {
+            parts.push(format!("({})", escape(year)));
+        }
+        parts.push(format!("<em>{}</em>", escape(&reference.title)));
+        if let Some(publisher) = &reference.publisher {
+            parts.push(escape(publisher));
+        }
+        parts.join(". ")
+    };
+
+    let markdown = bibliography
+        .iter()
+        .enumerate()
+        .map(|(number, reference)| {
+            let mut line = format!(
+                "{}. {}",
+                number + 1,
+                format_reference(reference, &|s: &str| s.to_string())
+                    .replace("<em>", "*")
+                    .replace("</em>", "*")
+            );
+            match &reference.url {
+                Some(url) if is_web_url(url) => line.push_str(&format!(". <{}>", url.trim())),
+                Some(url) => line.push_str(&format!(". {}", url)),
+                None => {}
+            }
+            line
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let html = format!(
+        "<ol>{}</ol>",
+        bibliography
+            .iter()
+            .map(|reference| {
+                let mut item = format_reference(reference, &escape_html);
+                match &reference.url {
+                    Some(url) if is_web_url(url) => item.push_str(&format!(
+                        ". <a href=\"{0}\">{0}</a>",
+                        escape_html(url.trim())
+                    )),
+                    Some(url) => item.push_str(&format!(". {}", escape_html(url))),
+                    None => {}
+                }
+                format!(
+                    "<li id=\"ref-{}\">{}</li>",
+                    escape_html(&reference.key),
+                    item
+                )
+            })
+            .collect::<String>()
+    );
+    (markdown, html)
+}
+
+/// Extracts a glossary, keyword index and bibliography from the book's body
+/// chapters and stores them as back-matter chapters, replacing earlier ones.
+#[server]
+pub async fn generate_back_matter(
+    req: GenerateBackMatterRequest,
+) -> Result<SuccessResponse<Vec<Chapter>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    check_model(&user, &req.model)?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let chapter_collection = db.collection::<Chapter>("chapters");
+    let back_matter_collection = db.collection::<BackMatter>("back_matter");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let (book, chapters) = load_outline(&db, user.id, book_id).await?;
+    let body = chapters
+        .into_iter()
+        .filter(|chapter| !chapter.kind.is_back_matter())
+        .collect::<Vec<Chapter>>();
+
+    if body
+        .iter()
+        .all(|chapter| chapter_text(chapter).trim().is_empty())
+    {
+        return Err(ServerFnError::new(
+            "Generate chapter content before creating back matter",
+        ));
+    }
+
+    let language = body
+        .first()
+        .map(|chapter| chapter.language.clone())
+        .unwrap_or_else(|| "English".to_string());
+    let content = body
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| {
+            format!(
+                "## Chapter {}: {}\n{}",
+                index + 1,
+                chapter.title,
+                chapter_text(chapter)
+                    .chars()
+                    .take(MAX_CHARS_PER_CHAPTER)
+                    .collect::<String>()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    let back_matter_prompt = format!(
+        "
+        **System Prompt (SP):** You are a book indexer preparing the back matter of '{title}'.
+
+        **Prompt (P):** From the chapters below, pick the key terms a reader may need defined and write a one or two sentence definition for each in {language}. List the keywords worth indexing, using the exact wording that appears in the text. List every work, author or website the chapters cite or quote as a reference; do not invent references that are not mentioned.
+
+        **Chapters:**
+        {content}
+
+        **Expected Format (EF):** Respond with a single JSON object and nothing else, matching this schema exactly:
+        {schema}
+
+        **Roleplay (RP):** As a professional indexer, prefer precision over volume.
+        ",
+        title = book.title,
+        schema = BACK_MATTER_SCHEMA,
+    );
+
+    let mut ai_client = get_ai(req.model.to_string()).await.lock().await;
+    let mut prompt = back_matter_prompt.clone();
+    let mut generated = Err(String::new());
+    for _ in 0..GENERATION_ATTEMPTS {
+        let parameters = ChatBuilder::default()
+            .model(chat_model(&req.model)?)
+            .messages(vec![Message::User {
+                content: Content::Text(prompt.clone()),
+                name: None,
+            }])
+            .build()?;
+
+        let raw = generate(&ai_client, Some(user.id), "back_matter", parameters)
+            .await
+            .map_err(ServerFnError::new)?;
+
+        generated = parse_generated_back_matter(&raw);
+        match &generated {
+            Ok(_) => break,
+            Err(error) => {
+                prompt = format!(
+                    "{}\n\nYour previous answer was rejected: {}. Return only valid JSON matching the schema.",
+                    back_matter_prompt, error
+                );
+            }
+        }
+    }
+    let generated = generated.map_err(|error| {
+        ServerFnError::new(format!("Invalid back matter from model: {}", error))
+    })?;
+
+    let mut glossary = generated
+        .glossary
+        .into_iter()
+        .map(|entry| GlossaryEntry {
+            term: entry.term.trim().to_string(),
+            definition: entry.definition.trim().to_string(),
+        })
+        .collect::<Vec<GlossaryEntry>>();
+    glossary.sort_by_key(|entry| entry.term.to_lowercase());
+    glossary.dedup_by_key(|entry| entry.term.to_lowercase());
+
+    let index = build_index(
+        glossary
+            .iter()
+            .map(|entry| entry.term.clone())
+            .chain(generated.index_terms)
+            .collect(),
+        &body,
+    );
+
+    // Links in the text are citations too, even when the model skipped them.
+    let mut references = generated.references;
+    for url in cited_urls(&body) {
+        if !references
+            .iter()
+            .any(|reference| reference.url.as_deref() == Some(url.as_str()))
+        {
+            references.push(GeneratedReference {
+                entry_type: "online".to_string(),
+                title: url.clone(),
+                authors: Vec::new(),
+                year: None,
+                publisher: None,
+                url: Some(url),
+            });
+        }
+    }
+    let mut taken = HashMap::new();
+    let bibliography = references
+        .into_iter()
+        .map(|reference| Reference {
+            key: citation_key(&reference, &mut taken),
+            entry_type: match reference.entry_type.as_str() {
+                "book" | "article" | "online" => reference.entry_type.clone(),
+                _ => "misc".to_string(),
+            },
+            title: reference.title.trim().to_string(),
+            authors: reference.authors,
+            year: reference.year.filter(|year| !year.trim().is_empty()),
+            publisher: reference.publisher.filter(|p| !p.trim().is_empty()),
+            url: reference.url.filter(|url| !url.trim().is_empty()),
+        })
+        .collect::<Vec<Reference>>();
+
+    let now = Utc::now();
+    let mut back_chapters = Vec::new();
+    let mut add_chapter = |kind: ChapterKind, title: &str, (markdown, html): (String, String)| {
+        back_chapters.push(Chapter {
+            id: ObjectId::new(),
+            book_id,
+            position: (body.len() + back_chapters.len()) as u32,
+            kind,
+            title: title.to_string(),
+            estimated_duration: 0,
+            markdown,
+            language: language.clone(),
+            html,
+            completed: true,
+            created_at: now,
+            updated_at: now,
+        });
+    };
+    if !glossary.is_empty() {
+        add_chapter(
+            ChapterKind::Glossary,
+            "Glossary",
+            render_glossary(&glossary),
+        );
+    }
+    if !index.is_empty() {
+        add_chapter(ChapterKind::Index, "Index", render_index(&index, &body));
+    }
+    if !bibliography.is_empty() {
+        add_chapter(
+            ChapterKind::Bibliography,
+            "Bibliography",
+            render_bibliography(&bibliography),
+        );
+    }
+
+    chapter_collection
+        .delete_many(doc! {
+            "book_id": book_id,
+            "kind": { "$in": ["Glossary", "Index", "Bibliography"] },
+        })
+        .await?;
+    if !back_chapters.is_empty() {
+        chapter_collection.insert_many(&back_chapters).await?;
+    }
+
+    back_matter_collection
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    back_matter_collection
+        .insert_one(BackMatter {
+            id: ObjectId::new(),
+            user: user.id,
+            book_id,
+            glossary,
+            index,
+            bibliography,
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+
+    // Body chapters may have had gaps where old back matter sat.
+    let mut outline = body
+        .into_iter()
+        .chain(back_chapters)
+        .collect::<Vec<Chapter>>();
+    save_positions(&db, &mut outline).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: outline,
+    })
+}
+
+#[server]
+pub async fn export_bibtex(req: BookRequest) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let back_matter = db
+        .collection::<BackMatter>("back_matter")
+        .find_one(doc! { "book_id": book_id, "user": user.id })
+        .await?
+        .ok_or(ServerFnError::new("Generate back matter first"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: back_matter
+            .bibliography
+            .iter()
+            .map(Reference::to_bibtex)
+            .collect::<Vec<String>>()
+            .join("\n"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(url: &str) -> Reference {
+        Reference {
+            key: "klabnik2019".to_string(),
+            entry_type: "book".to_string(),
+            title: "The Rust Programming Language".to_string(),
+            authors: vec!["Steve Klabnik".to_string()],
+            year: Some("2019".to_string()),
+            publisher: None,
+            url: Some(url.to_string()),
+        }
+    }
+
+    #[test]
+    fn links_only_web_urls() {
+        let (markdown, html) = render_bibliography(&[reference("https://doc.rust-lang.org/book/")]);
+        assert!(markdown.ends_with(". <https://doc.rust-lang.org/book/>"));
+        assert!(html.contains("<a href=\"https://doc.rust-lang.org/book/\">"));
+
+        let (markdown, html) = render_bibliography(&[reference("javascript:alert(\"hi\")")]);
+        assert!(markdown.ends_with(". javascript:alert(\"hi\")"));
+        assert!(!html.contains("<a "));
+        assert!(html.contains(". javascript:alert(&quot;hi&quot;)</li>"));
+    }
+
+    #[test]
+    fn citation_keys_stay_unique() {
+        let generated = GeneratedReference {
+            entry_type: "book".to_string(),
+            title: "Programming Rust".to_string(),
+            authors: vec!["Jim Blandy".to_string()],
+            year: Some("2021".to_string()),
+            publisher: None,
+            url: None,
+        };
+        let mut taken = HashMap::new();
+        let keys = (0..300)
+            .map(|_| citation_key(&generated, &mut taken))
+            .collect::<std::collections::HashSet<String>>();
+        assert_eq!(keys.len(), 300);
+        assert!(keys.contains("blandy2021"));
+        assert!(keys.contains("blandy2021-2"));
+        assert!(keys.contains("blandy2021-300"));
+    }
+}
@@ -0,0 +1,70 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct GlossaryEntry {
+    pub term: String,
+    pub definition: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct IndexEntry {
+    pub term: String,
+    /// Chapters mentioning the term, in reading order.
+    pub chapter_ids: Vec<ObjectId>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Reference {
+    /// BibTeX citation key, unique within the book.
+    pub key: String,
+    /// BibTeX entry type such as `book`, `article` or `misc`.
+    pub entry_type: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub year: Option<String>,
+    pub publisher: Option<String>,
+    pub url: Option<String>,
+}
+
+impl Reference {
+    pub fn to_bibtex(&self) -> String {
+        let escape = |value: &str| value.replace('{', "\\{").replace('}', "\\}");
+        let mut fields = vec![format!("  title = {{{}}}", escape(&self.title))];
+        if !self.authors.is_empty() {
+            fields.push(format!(
+                "  author = {{{}}}",
+                escape(&self.authors.join(" and "))
+            ));
+        }
+        if let Some(year) = &self.year {
+            fields.push(format!("  year = {{{}}}", escape(year)));
+        }
+        if let Some(publisher) = &self.publisher {
+            fields.push(format!("  publisher = {{{}}}", escape(publisher)));
+        }
+        if let Some(url) = &self.url {
+            fields.push(format!("  url = {{{}}}", escape(url)));
+        }
+        format!("@{}{{{},\n{}\n}}\n", self.entry_type, self.key, fields.join(",\n"))
+    }
+}
+
+/// The structured source of a book's back-matter chapters.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BackMatter {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    pub glossary: Vec<GlossaryEntry>,
+    pub index: Vec<IndexEntry>,
+    pub bibliography: Vec<Reference>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
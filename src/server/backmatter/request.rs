@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateBackMatterRequest {
+    pub token: String,
+    pub book_id: String,
+    pub model: String,
+}
@@ -5,49 +5,90 @@ use bson::doc;
 use dioxus::prelude::*;
 use dioxus_logger::tracing;
 
+use crate::server::ai_call::model::AiCall;
+use crate::server::annotation::model::Annotation;
+use crate::server::audiobook::controller::audio_prefix;
+use crate::server::audiobook::model::AudioTrack;
+use crate::server::audiobook::model::Audiobook;
 use crate::server::auth::controller::auth;
+use crate::server::backmatter::model::BackMatter;
 use crate::server::book::model::Book;
 use crate::server::book::model::Chapter;
+use crate::server::book::model::ChapterKind;
+use crate::server::book::model::FrontMatterEntry;
+use crate::server::book::model::FrontMatterKind;
+use crate::server::book::model::SeriesEntry;
+use crate::server::book::model::TRASH_RETENTION_DAYS;
 use crate::server::book::request::AIRequest;
+use crate::server::book::request::BookRequest;
+use crate::server::book::request::BookSort;
+use crate::server::book::request::ChapterRequest;
 use crate::server::book::request::CompleteBookRequest;
+use crate::server::book::request::CreateShareLinkRequest;
 use crate::server::book::request::GenerateBookRequest;
 use crate::server::book::request::GenerateChapterContentRequest;
+use crate::server::book::request::GenerateFrontMatterRequest;
 use crate::server::book::request::GetBookForUserRequest;
 use crate::server::book::request::GetBooksForUserRequest;
 use crate::server::book::request::GetChaptersContentRequest;
+use crate::server::book::request::GetPublicBookRequest;
+use crate::server::book::request::InsertChapterRequest;
+use crate::server::book::request::LabelKind;
+use crate::server::book::request::ListBooksRequest;
+use crate::server::book::request::PublishBookRequest;
+use crate::server::book::request::RenameLabelRequest;
+use crate::server::book::request::ReorderChaptersRequest;
+use crate::server::book::request::ReorderSeriesRequest;
+use crate::server::book::request::RevokeShareLinkRequest;
+use crate::server::book::request::SetBookLabelsRequest;
+use crate::server::book::request::SetBookSeriesRequest;
+use crate::server::book::request::SetFrontMatterRequest;
+use crate::server::book::request::SortOrder;
+use crate::server::book::request::SplitChapterRequest;
 use crate::server::book::request::StoreBookRequest;
 use crate::server::book::request::UpdateBookContentRequest;
 use crate::server::book::response::BookResponse;
+use crate::server::book::response::BooksPageResponse;
 use crate::server::book::response::GenerateBookOutlineResponse;
+use crate::server::book::response::LibraryFacets;
+use crate::server::book::response::PublicBookResponse;
+use crate::server::book::response::ShareLinkResponse;
 use crate::server::book::response::{
     AIUsageStats, AnalyticsData, EngagementStats, PredictiveStats,
 };
+use crate::server::common::html::strip_tags;
 use crate::server::common::response::SuccessResponse;
+use crate::server::conversation::model::Conversation;
+use crate::server::conversation::model::Message as ChatMessage;
+use crate::server::entitlement::controller::{check_chapters, check_model, user_plan};
+use crate::server::illustration::controller::PLACEHOLDER_INSTRUCTIONS;
+use crate::server::progress::model::ReadingProgress;
+use crate::server::quiz::model::Quiz;
+use crate::server::quiz::model::QuizAttempt;
+use crate::server::study::model::CardState;
 use std::env;
 
 use bson::oid::ObjectId;
+use bson::Bson;
 use chrono::prelude::*;
 use futures_util::StreamExt;
 use futures_util::TryStreamExt;
 use regex::Regex;
 #[cfg(feature = "server")]
 use {
-    crate::ai::get_ai,
+    crate::ai::{chat_model, generate, get_ai},
     crate::db::get_client,
-    crate::unsplash::get_unsplash_client,
+    crate::server::cover::controller::{assign_default_cover, cover_prefix},
+    crate::server::entitlement::controller::{check_book_quota, record_book_creation},
+    crate::server::illustration::controller::illustration_prefix,
     gems::chat::ChatBuilder,
     gems::messages::Content,
     gems::messages::Message,
     gems::models::Model,
     gems::traits::CTrait,
-    http_api_isahc_client::{Client as _, IsahcClient},
+    rand::distributions::Alphanumeric,
     rand::thread_rng,
     rand::Rng,
-    unsplash_api::endpoints::common::EndpointRet,
-    unsplash_api::endpoints::search_photos::SearchPhotos,
-    unsplash_api::endpoints::search_photos::SearchPhotosResponseBodyOkJson,
-    unsplash_api::objects::pagination::Pagination,
-    unsplash_api::objects::rate_limiting::RateLimiting,
 };
 
 #[server]
@@ -63,68 +104,760 @@ pub async fn store_book(
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let book_collection = db.collection::<Book>("books");
 
-    let photo_url = match req.main_topic {
-        Some(ref topic) => fetch_cover(topic.to_string()).await?,
-        None => None,
-    };
+    check_book_quota(&db, &user).await?;
 
-    let new_book = Book {
+    let mut new_book = Book {
         id: ObjectId::new(),
         user: user.id,
         title: req.title,
         subtitle: Some(req.subtitle),
         book_type: req.book_type,
         main_topic: req.main_topic,
-        cover: photo_url,
         completed: false,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        ..Default::default()
+    };
+    assign_default_cover(&mut new_book).await;
+    book_collection.insert_one(new_book.clone()).await?;
+    record_book_creation(&db, user.id, new_book.id).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: BookResponse { id: new_book.id },
+    })
+}
+
+#[server]
+pub async fn update_book_content(
+    req: UpdateBookContentRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    book_collection
+        .update_one(
+            doc! { "_id": book_id },
+            doc! { "$set": { "content": req.new_content, "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Book updated successfully".into(),
+    })
+}
+
+#[server]
+pub async fn complete_book(
+    req: CompleteBookRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    book_collection
+        .update_one(
+            doc! { "_id": req.book_id },
+            doc! { "$set": { "completed": true, "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Book marked as completed".into(),
+    })
+}
+
+#[server]
+pub async fn get_books_for_user(
+    req: GetBooksForUserRequest,
+) -> Result<SuccessResponse<Vec<Book>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let books = book_collection
+        .find(doc! { "user": user.id, "deletedAt": null })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: books,
+    })
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+fn sort_field(sort: BookSort) -> &'static str {
+    match sort {
+        BookSort::Created => "createdAt",
+        BookSort::Updated => "updatedAt",
+        BookSort::Title => "title",
+        BookSort::Completion => "completed",
+        BookSort::SeriesOrder => "series.order",
+    }
+}
+
+/// Cursors are the sort key of the last book on a page followed by its id,
+/// so pages stay stable while books are added or edited. Books outside a
+/// series have no series order and get an empty key, matching the null
+/// they sort as.
+fn encode_cursor(book: &Book, sort: BookSort) -> String {
+    let value = match sort {
+        BookSort::Created => book.created_at.timestamp_millis().to_string(),
+        BookSort::Updated => book.updated_at.timestamp_millis().to_string(),
+        BookSort::Title => book.title.clone(),
+        BookSort::Completion => book.completed.to_string(),
+        BookSort::SeriesOrder => book
+            .series
+            .as_ref()
+            .map(|series| series.order.to_string())
+            .unwrap_or_default(),
+    };
+    format!("{}|{}", value, book.id.to_hex())
+}
+
+fn decode_cursor(cursor: &str, sort: BookSort) -> Result<(Bson, ObjectId), ServerFnError> {
+    let invalid = || ServerFnError::new("Invalid cursor");
+    let (value, id) = cursor.rsplit_once('|').ok_or_else(invalid)?;
+    let id = ObjectId::parse_str(id).map_err(|_| invalid())?;
+    let value = match sort {
+        BookSort::Created | BookSort::Updated => Bson::DateTime(bson::DateTime::from_millis(
+            value.parse().map_err(|_| invalid())?,
+        )),
+        BookSort::Title => Bson::String(value.to_string()),
+        BookSort::Completion => Bson::Boolean(value.parse().map_err(|_| invalid())?),
+        BookSort::SeriesOrder if value.is_empty() => Bson::Null,
+        BookSort::SeriesOrder => Bson::Int64(value.parse().map_err(|_| invalid())?),
+    };
+    Ok((value, id))
+}
+
+/// The books after the cursor's. Null keys sort before every value, but
+/// range operators never match them, so they're spelled out.
+fn after_cursor(field: &str, value: Bson, id: ObjectId, order: SortOrder) -> Vec<bson::Document> {
+    match (value, order) {
+        (Bson::Null, SortOrder::Asc) => vec![
+            doc! { field: { "$ne": null } },
+            doc! { field: null, "_id": { "$gt": id } },
+        ],
+        (Bson::Null, SortOrder::Desc) => vec![doc! { field: null, "_id": { "$lt": id } }],
+        (value, SortOrder::Asc) => vec![
+            doc! { field: { "$gt": value.clone() } },
+            doc! { field: value, "_id": { "$gt": id } },
+        ],
+        (value, SortOrder::Desc) => vec![
+            doc! { field: { "$lt": value.clone() } },
+            doc! { field: value, "_id": { "$lt": id } },
+            doc! { field: null },
+        ],
+    }
+}
+
+#[server]
+pub async fn list_books(
+    req: ListBooksRequest,
+) -> Result<SuccessResponse<BooksPageResponse>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let mut filter = doc! { "user": user.id, "deletedAt": null };
+    if let Some(completed) = req.completed {
+        filter.insert("completed", completed);
+    }
+    if let Some(book_type) = req.book_type.filter(|t| !t.is_empty()) {
+        filter.insert("bookType", book_type);
+    }
+    if let Some(tag) = req.tag.filter(|t| !t.is_empty()) {
+        filter.insert("tags", tag);
+    }
+    if let Some(collection) = req.collection.filter(|c| !c.is_empty()) {
+        filter.insert("collections", collection);
+    }
+    if let Some(series) = req.series.filter(|s| !s.is_empty()) {
+        filter.insert("series.name", series);
+    }
+    if let Some(query) = req.query.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        filter.insert("$text", doc! { "$search": query });
+    }
+
+    let field = sort_field(req.sort);
+    let direction = match req.order {
+        SortOrder::Asc => 1,
+        SortOrder::Desc => -1,
+    };
+
+    if let Some(cursor) = req.cursor.as_deref() {
+        let (value, id) = decode_cursor(cursor, req.sort)?;
+        filter.insert("$or", after_cursor(field, value, id, req.order));
+    }
+
+    let limit = req
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let mut books = book_collection
+        .find(filter)
+        .sort(doc! { field: direction, "_id": direction })
+        .limit(limit as i64 + 1)
+        .await?
+        .try_collect::<Vec<Book>>()
+        .await?;
+
+    let next_cursor = if books.len() > limit as usize {
+        books.truncate(limit as usize);
+        books.last().map(|book| encode_cursor(book, req.sort))
+    } else {
+        None
+    };
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: BooksPageResponse { books, next_cursor },
+    })
+}
+
+#[server]
+pub async fn get_library_facets(
+    req: GetBooksForUserRequest,
+) -> Result<SuccessResponse<LibraryFacets>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let filter = doc! { "user": user.id, "deletedAt": null };
+    let book_types = distinct_labels(&book_collection, "bookType", filter.clone()).await?;
+    let tags = distinct_labels(&book_collection, "tags", filter.clone()).await?;
+    let collections = distinct_labels(&book_collection, "collections", filter.clone()).await?;
+    let series = distinct_labels(&book_collection, "series.name", filter).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: LibraryFacets {
+            book_types,
+            tags,
+            collections,
+            series,
+        },
+    })
+}
+
+#[cfg(feature = "server")]
+async fn distinct_labels(
+    collection: &mongodb::Collection<Book>,
+    field: &str,
+    filter: bson::Document,
+) -> Result<Vec<String>, ServerFnError> {
+    let mut labels = collection
+        .distinct(field, filter)
+        .await?
+        .into_iter()
+        .filter_map(|value| value.as_str().map(|s| s.to_string()))
+        .collect::<Vec<String>>();
+    labels.sort_by_key(|label| label.to_lowercase());
+    Ok(labels)
+}
+
+/// Trims labels and drops blanks and case-insensitive duplicates, keeping the
+/// first spelling the user typed.
+fn normalize_labels(labels: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for label in labels {
+        let label = label.trim().to_string();
+        if !label.is_empty()
+            && !normalized
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(&label))
+        {
+            normalized.push(label);
+        }
+    }
+    normalized
+}
+
+#[server]
+pub async fn set_book_labels(
+    req: SetBookLabelsRequest,
+) -> Result<SuccessResponse<Book>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let book = book_collection
+        .find_one_and_update(
+            doc! { "_id": book_id, "user": user.id, "deletedAt": null },
+            doc! {
+                "$set": {
+                    "tags": normalize_labels(req.tags),
+                    "collections": normalize_labels(req.collections),
+                    "updatedAt": Utc::now(),
+                }
+            },
+        )
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: book,
+    })
+}
+
+#[server]
+pub async fn set_front_matter(
+    req: SetFrontMatterRequest,
+) -> Result<SuccessResponse<Book>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    // One entry per kind, in reading order; clearing the text removes it.
+    let mut entries: Vec<FrontMatterEntry> = Vec::new();
+    for entry in req.entries {
+        let text = entry.text.trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        if entries.iter().any(|existing| existing.kind == entry.kind) {
+            return Err(ServerFnError::new(format!(
+                "{} was given more than once",
+                entry.kind.title()
+            )));
+        }
+        entries.push(FrontMatterEntry {
+            kind: entry.kind,
+            text,
+        });
+    }
+    entries.sort_by_key(|entry| {
+        FrontMatterKind::ALL
+            .iter()
+            .position(|kind| *kind == entry.kind)
+    });
+
+    let book = book_collection
+        .find_one_and_update(
+            doc! { "_id": book_id, "user": user.id, "deletedAt": null },
+            doc! {
+                "$set": {
+                    "frontMatter": bson::to_bson(&entries)?,
+                    "updatedAt": Utc::now(),
+                }
+            },
+        )
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: book,
+    })
+}
+
+/// Per-chapter excerpt given to the front matter prompt.
+const FRONT_MATTER_EXCERPT_CHARS: usize = 600;
+
+/// What the model is asked to write for each kind of front matter.
+fn front_matter_brief(kind: FrontMatterKind) -> &'static str {
+    match kind {
+        FrontMatterKind::Dedication => {
+            "a dedication of one or two short lines. Address it to a person or group \
+             named with a placeholder such as [Name], never an invented name."
+        }
+        FrontMatterKind::Preface => {
+            "a preface of three to five paragraphs in the author's voice: why the book was \
+             written, who it is for and how to get the most out of it."
+        }
+        FrontMatterKind::Acknowledgements => {
+            "acknowledgements of two or three short paragraphs. Thank people with \
+             placeholders such as [Editor's name], never invented names."
+        }
+        FrontMatterKind::Introduction => {
+            "an introduction of four to six paragraphs: what the reader will learn, what \
+             they should know beforehand and how the chapters build on each other."
+        }
+        FrontMatterKind::AuthorBio => {
+            "a short author biography of one paragraph in the third person. Refer to the \
+             author as [Author Name] and invent no credentials, only an interest in the topic."
+        }
+        FrontMatterKind::Blurb => {
+            "back-cover copy of 120 to 180 words that hooks a browsing reader and promises \
+             what the book delivers, without spoilers or quotes from reviewers."
+        }
+    }
+}
+
+/// Drafts one front matter entry from the book's outline and chapters. The
+/// draft is returned for the author to edit, not saved.
+#[server]
+pub async fn generate_front_matter(
+    req: GenerateFrontMatterRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    check_model(&user, &req.model)?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let (book, chapters) = load_outline(&db, user.id, book_id).await?;
+
+    let body = chapters
+        .iter()
+        .filter(|chapter| !chapter.kind.is_back_matter())
+        .collect::<Vec<&Chapter>>();
+    if body.is_empty() {
+        return Err(ServerFnError::new(
+            "Add chapters before generating front matter",
+        ));
+    }
+    let language = body[0].language.clone();
+    let chapters_text = body
+        .iter()
+        .enumerate()
+        .map(|(number, chapter)| {
+            let text = if chapter.markdown.trim().is_empty() {
+                strip_tags(&chapter.html)
+            } else {
+                chapter.markdown.clone()
+            };
+            format!(
+                "Chapter {}: {}\n{}",
+                number + 1,
+                chapter.title,
+                text.chars()
+                    .take(FRONT_MATTER_EXCERPT_CHARS)
+                    .collect::<String>()
+                    .trim()
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    let front_matter_prompt = format!(
+        "
+        **System Prompt (SP):** You are the editor of the book '{title}'{subtitle} on '{topic}'.
+
+        **Prompt (P):** Write the {kind} of this book in {language}: {brief}
+
+        **Chapters:**
+        {chapters}
+
+        **Expected Format (EF):** Plain text only, with a blank line between paragraphs. No title, headings, markdown or HTML.
+
+        **Roleplay (RP):** As an editor, match the tone of the chapters and promise nothing they don't cover.
+        ",
+        title = book.title,
+        subtitle = book
+            .subtitle
+            .as_deref()
+            .filter(|subtitle| !subtitle.is_empty())
+            .map(|subtitle| format!(" ({})", subtitle))
+            .unwrap_or_default(),
+        topic = book.main_topic.clone().unwrap_or_default(),
+        kind = req.kind.title().to_lowercase(),
+        brief = front_matter_brief(req.kind),
+        chapters = chapters_text,
+    );
+
+    let ai_client = get_ai(req.model.to_string()).await.lock().await;
+    let parameters = ChatBuilder::default()
+        .model(chat_model(&req.model)?)
+        .messages(vec![Message::User {
+            content: Content::Text(front_matter_prompt),
+            name: None,
+        }])
+        .build()?;
+
+    let text = generate(&ai_client, Some(user.id), "front_matter", parameters)
+        .await
+        .map_err(ServerFnError::new)?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: text.trim().to_string(),
+    })
+}
+
+#[server]
+pub async fn set_book_series(
+    req: SetBookSeriesRequest,
+) -> Result<SuccessResponse<Book>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let filter = doc! { "_id": book_id, "user": user.id, "deletedAt": null };
+
+    let update = match req.series.map(|name| name.trim().to_string()).filter(|n| !n.is_empty()) {
+        Some(name) => {
+            let order = match req.order {
+                Some(order) => order.max(1),
+                None => {
+                    let last = book_collection
+                        .find_one(doc! {
+                            "user": user.id,
+                            "deletedAt": null,
+                            "series.name": &name,
+                            "_id": { "$ne": book_id },
+                        })
+                        .sort(doc! { "series.order": -1 })
+                        .await?;
+                    last.and_then(|book| book.series).map(|s| s.order).unwrap_or(0) + 1
+                }
+            };
+            doc! {
+                "$set": {
+                    "series": { "name": name, "order": order },
+                    "updatedAt": Utc::now(),
+                }
+            }
+        }
+        None => doc! {
+            "$unset": { "series": "" },
+            "$set": { "updatedAt": Utc::now() },
+        },
+    };
+
+    let book = book_collection
+        .find_one_and_update(filter, update)
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: book,
+    })
+}
+
+#[server]
+pub async fn reorder_series(
+    req: ReorderSeriesRequest,
+) -> Result<SuccessResponse<Vec<Book>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    for (index, book_id) in req.book_ids.iter().enumerate() {
+        let book_id =
+            ObjectId::parse_str(book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+        book_collection
+            .update_one(
+                doc! {
+                    "_id": book_id,
+                    "user": user.id,
+                    "deletedAt": null,
+                    "series.name": &req.series,
+                },
+                doc! { "$set": { "series.order": index as u32 + 1, "updatedAt": Utc::now() } },
+            )
+            .await?;
+    }
+
+    let books = book_collection
+        .find(doc! { "user": user.id, "deletedAt": null, "series.name": &req.series })
+        .sort(doc! { "series.order": 1, "_id": 1 })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: books,
+    })
+}
+
+/// Renames a tag, collection or series across the user's whole library, or
+/// removes it from every book when no new name is given.
+#[server]
+pub async fn rename_label(req: RenameLabelRequest) -> Result<SuccessResponse<u64>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let new_name = req
+        .new_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    let now = Utc::now();
+
+    let modified = match req.kind {
+        LabelKind::Series => {
+            let filter = doc! { "user": user.id, "series.name": &req.name };
+            let update = match &new_name {
+                Some(name) => doc! { "$set": { "series.name": name, "updatedAt": now } },
+                None => doc! { "$unset": { "series": "" }, "$set": { "updatedAt": now } },
+            };
+            book_collection
+                .update_many(filter, update)
+                .await?
+                .modified_count
+        }
+        LabelKind::Tag | LabelKind::Collection => {
+            let field = if req.kind == LabelKind::Tag {
+                "tags"
+            } else {
+                "collections"
+            };
+            let book_ids = book_collection
+                .distinct("_id", doc! { "user": user.id, field: &req.name })
+                .await?;
+            let filter = doc! { "_id": { "$in": book_ids } };
+            let modified = book_collection
+                .update_many(
+                    filter.clone(),
+                    doc! { "$pull": { field: &req.name }, "$set": { "updatedAt": now } },
+                )
+                .await?
+                .modified_count;
+            // $pull and $addToSet can't touch the same field in one update, so
+            // renames re-add the label in a second pass.
+            if let Some(name) = &new_name {
+                book_collection
+                    .update_many(filter, doc! { "$addToSet": { field: name } })
+                    .await?;
+            }
+            modified
+        }
     };
-    book_collection.insert_one(new_book.clone()).await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: BookResponse { id: new_book.id },
+        data: modified,
     })
 }
 
 #[server]
-pub async fn fetch_cover(topic: String) -> Result<Option<String>, ServerFnError> {
-    let client = get_unsplash_client().await.lock().await;
+pub async fn get_book_for_user(
+    req: GetBookForUserRequest,
+) -> Result<SuccessResponse<Book>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
 
-    let search_photos = SearchPhotos::new(
-        &env::var("UNSPLASH_API_KEY").expect("UNSPLASH_API_KEY must be set."),
-        topic,
-    );
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let book = book_collection
+        .find_one(doc! { "_id": book_id, "user": user.id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
 
-    let response: EndpointRet<(SearchPhotosResponseBodyOkJson, Pagination, RateLimiting)> =
-        client.respond_endpoint(&search_photos).await?;
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: book,
+    })
+}
 
-    let mut extracted_data = Vec::new();
+#[server]
+pub async fn delete_book(req: BookRequest) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
 
-    if let EndpointRet::Ok((ok_json, _pagination, _rate_limiting)) = response {
-        for photo in ok_json.results {
-            let image_url = photo.urls.regular.to_string();
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
 
-            extracted_data.push(image_url);
-        }
-    } else {
-        tracing::error!("Unexpected response type");
-    }
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let result = book_collection
+        .update_one(
+            doc! { "_id": book_id, "user": user.id, "deletedAt": null },
+            doc! { "$set": { "deletedAt": Utc::now(), "published": false, "updatedAt": Utc::now() } },
+        )
+        .await?;
 
-    if extracted_data.is_empty() {
-        return Ok(None);
+    if result.matched_count == 0 {
+        return Err(ServerFnError::new("Book not found"));
     }
 
-    let mut rng = thread_rng();
-    let random_index = rng.gen_range(0..extracted_data.len());
-    Ok(Some(extracted_data[random_index].clone()))
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Book moved to trash".into(),
+    })
 }
 
 #[server]
-pub async fn update_book_content(
-    req: UpdateBookContentRequest,
-) -> Result<SuccessResponse<String>, ServerFnError> {
+pub async fn restore_book(req: BookRequest) -> Result<SuccessResponse<Book>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
@@ -133,45 +866,211 @@ pub async fn update_book_content(
     let book_id =
         ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
 
-    book_collection
-        .update_one(
-            doc! { "_id": book_id },
-            doc! { "$set": { "content": req.new_content, "updatedAt": Utc::now() } },
+    let book = book_collection
+        .find_one_and_update(
+            doc! { "_id": book_id, "user": user.id, "deletedAt": { "$ne": null } },
+            doc! { "$unset": { "deletedAt": "" }, "$set": { "updatedAt": Utc::now() } },
         )
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await?
+        .ok_or(ServerFnError::new("Book not found in trash"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: book,
+    })
+}
+
+#[server]
+pub async fn get_trash(
+    req: GetBooksForUserRequest,
+) -> Result<SuccessResponse<Vec<Book>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let books = book_collection
+        .find(doc! { "user": user.id, "deletedAt": { "$ne": null } })
+        .sort(doc! { "deletedAt": -1 })
+        .await?
+        .try_collect()
         .await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: "Book updated successfully".into(),
+        data: books,
     })
 }
 
 #[server]
-pub async fn complete_book(
-    req: CompleteBookRequest,
-) -> Result<SuccessResponse<String>, ServerFnError> {
+pub async fn purge_book(req: BookRequest) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    book_collection
+        .find_one(doc! { "_id": book_id, "user": user.id, "deletedAt": { "$ne": null } })
+        .await?
+        .ok_or(ServerFnError::new("Book not found in trash"))?;
+
+    purge_book_data(&db, book_id).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Book deleted permanently".into(),
+    })
+}
+
+/// Hard-deletes a book together with every document that references it.
+#[cfg(feature = "server")]
+pub(crate) async fn purge_book_data(
+    db: &mongodb::Database,
+    book_id: ObjectId,
+) -> Result<(), ServerFnError> {
+    let conversation_collection = db.collection::<Conversation>("conversations");
+
+    // Conversations keep the book id as a hex string rather than an ObjectId.
+    let conversation_ids = conversation_collection
+        .find(doc! { "book": book_id.to_hex() })
+        .await?
+        .try_collect::<Vec<Conversation>>()
+        .await?
+        .into_iter()
+        .map(|conversation| conversation.id)
+        .collect::<Vec<ObjectId>>();
+
+    if !conversation_ids.is_empty() {
+        db.collection::<ChatMessage>("messages")
+            .delete_many(doc! { "conversation": { "$in": &conversation_ids } })
+            .await?;
+    }
+    conversation_collection
+        .delete_many(doc! { "book": book_id.to_hex() })
+        .await?;
+
+    db.collection::<ReadingProgress>("reading_progress")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<Annotation>("annotations")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<Quiz>("quizzes")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<QuizAttempt>("quiz_attempts")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<CardState>("card_states")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<BackMatter>("back_matter")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<AudioTrack>("audio_tracks")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<Audiobook>("audiobooks")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    crate::storage::delete(&audio_prefix(book_id))
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+    crate::storage::delete(&cover_prefix(book_id))
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+    crate::storage::delete(&illustration_prefix(book_id))
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+    db.collection::<Chapter>("chapters")
+        .delete_many(doc! { "book_id": book_id })
+        .await?;
+    db.collection::<Book>("books")
+        .delete_one(doc! { "_id": book_id })
+        .await?;
+
+    Ok(())
+}
+
+/// Purges every book that has sat in the trash longer than the retention window.
+#[cfg(feature = "server")]
+pub(crate) async fn purge_expired_books() -> Result<u64, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let cutoff = Utc::now() - chrono::Duration::days(TRASH_RETENTION_DAYS);
+    let expired = book_collection
+        .find(doc! { "deletedAt": { "$lte": cutoff } })
+        .await?
+        .try_collect::<Vec<Book>>()
+        .await?;
+
+    for book in &expired {
+        purge_book_data(&db, book.id).await?;
+    }
+
+    Ok(expired.len() as u64)
+}
+
+#[server]
+pub async fn publish_book(
+    req: PublishBookRequest,
+) -> Result<SuccessResponse<ShareLinkResponse>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let book_collection = db.collection::<Book>("books");
 
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let book = book_collection
+        .find_one(doc! { "_id": book_id, "user": user.id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+
+    let slug = book.slug.clone().unwrap_or_else(|| book_slug(&book));
+
     book_collection
         .update_one(
-            doc! { "_id": req.book_id },
-            doc! { "$set": { "completed": true, "updatedAt": Utc::now() } },
+            doc! { "_id": book.id },
+            doc! { "$set": { "published": req.published, "slug": &slug, "updatedAt": Utc::now() } },
         )
         .await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: "Book marked as completed".into(),
+        data: ShareLinkResponse {
+            slug,
+            published: req.published,
+            share_token: book.share_token,
+            expires_at: book.share_expires_at,
+        },
     })
 }
 
 #[server]
-pub async fn get_books_for_user(
-    req: GetBooksForUserRequest,
-) -> Result<SuccessResponse<Vec<Book>>, ServerFnError> {
+pub async fn create_share_link(
+    req: CreateShareLinkRequest,
+) -> Result<SuccessResponse<ShareLinkResponse>, ServerFnError> {
     let user = auth(req.token)
         .await
         .map_err(|_| ServerFnError::new("Not Authenticated"))?;
@@ -181,22 +1080,52 @@ pub async fn get_books_for_user(
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let book_collection = db.collection::<Book>("books");
 
-    let books = book_collection
-        .find(doc! { "user": user.id })
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let book = book_collection
+        .find_one(doc! { "_id": book_id, "user": user.id, "deletedAt": null })
         .await?
-        .try_collect()
+        .ok_or(ServerFnError::new("Book not found"))?;
+
+    let slug = book.slug.clone().unwrap_or_else(|| book_slug(&book));
+    let share_token: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    let expires_at = match req.expires_in_days {
+        Some(days) if days > 0 => Some(Utc::now() + chrono::Duration::days(days)),
+        _ => None,
+    };
+
+    book_collection
+        .update_one(
+            doc! { "_id": book.id },
+            doc! { "$set": {
+                "slug": &slug,
+                "shareToken": &share_token,
+                "shareExpiresAt": expires_at,
+                "updatedAt": Utc::now(),
+            }},
+        )
         .await?;
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: books,
+        data: ShareLinkResponse {
+            slug,
+            published: book.published,
+            share_token: Some(share_token),
+            expires_at,
+        },
     })
 }
 
 #[server]
-pub async fn get_book_for_user(
-    req: GetBookForUserRequest,
-) -> Result<SuccessResponse<Book>, ServerFnError> {
+pub async fn revoke_share_link(
+    req: RevokeShareLinkRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
     let user = auth(req.token)
         .await
         .map_err(|_| ServerFnError::new("Not Authenticated"))?;
@@ -209,17 +1138,94 @@ pub async fn get_book_for_user(
     let book_id =
         ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
 
+    let result = book_collection
+        .update_one(
+            doc! { "_id": book_id, "user": user.id },
+            doc! {
+                "$set": { "updatedAt": Utc::now() },
+                "$unset": { "shareToken": "", "shareExpiresAt": "" },
+            },
+        )
+        .await?;
+
+    if result.matched_count == 0 {
+        return Err(ServerFnError::new("Book not found"));
+    }
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: "Share link revoked".into(),
+    })
+}
+
+#[server]
+pub async fn get_public_book(
+    req: GetPublicBookRequest,
+) -> Result<SuccessResponse<PublicBookResponse>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+    let chapter_collection = db.collection::<Chapter>("chapters");
+
     let book = book_collection
-        .find_one(doc! { "_id": book_id, "user": user.id })
+        .find_one(doc! { "slug": &req.slug, "deletedAt": null })
         .await?
         .ok_or(ServerFnError::new("Book not found"))?;
 
+    // A valid share token opens unpublished books until it expires or is revoked.
+    let shared = match (&book.share_token, &req.share_token) {
+        (Some(expected), Some(given)) => {
+            expected == given && book.share_expires_at.map_or(true, |exp| exp > Utc::now())
+        }
+        _ => false,
+    };
+
+    if !book.published && !shared {
+        return Err(ServerFnError::new("Book not found"));
+    }
+
+    // Chapters not generated yet only have the model's raw markdown, which
+    // isn't safe to show strangers as HTML.
+    let chapters = chapter_collection
+        .find(doc! { "book_id": book.id, "html": { "$nin": ["", null] } })
+        .sort(doc! { "position": 1, "_id": 1 })
+        .await?
+        .try_collect::<Vec<Chapter>>()
+        .await?;
+
     Ok(SuccessResponse {
         status: "success".into(),
-        data: book,
+        data: PublicBookResponse {
+            title: book.title,
+            subtitle: book.subtitle,
+            cover: book.cover,
+            cover_credit: book.cover_credit,
+            chapters,
+        },
     })
 }
 
+pub(crate) fn book_slug(book: &Book) -> String {
+    let mut slug = String::new();
+    for c in book.title.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+
+    // The id suffix keeps slugs unique across books that share a title.
+    let hex = book.id.to_hex();
+    if slug.is_empty() {
+        hex
+    } else {
+        format!("{}-{}", slug, &hex[hex.len() - 8..])
+    }
+}
+
 #[server]
 pub async fn generate_book_outline(
     req: GenerateBookRequest,
@@ -228,6 +1234,19 @@ pub async fn generate_book_outline(
         .await
         .map_err(|_| ServerFnError::new("Not Authenticated"))?;
 
+    let db_client = get_client().await;
+    let db = db_client
+        .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let requested_chapters = req
+        .chapters
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| ServerFnError::new("Invalid chapter count"))?;
+    check_model(&user, &req.model)?;
+    check_chapters(&user, requested_chapters)?;
+    check_book_quota(&db, &user).await?;
+
     let mut client = get_ai(req.model.to_string()).await.lock().await;
 
     let outline_prompt = format!(
@@ -250,27 +1269,20 @@ pub async fn generate_book_outline(
     );
 
     let parameters = ChatBuilder::default()
-        .model(Model::Flash20)
+        .model(chat_model(&req.model)?)
         .messages(vec![Message::User {
             content: Content::Text(outline_prompt.to_string()),
             name: None,
         }])
         .build()?;
 
-    let outline = client
-        .chat()
-        .generate(parameters)
+    let outline = generate(&client, Some(user.id), "book_outline", parameters)
         .await
         .map_err(ServerFnError::new)?;
 
-    let db_client = get_client().await;
-    let db = db_client
-        .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let book_collection = db.collection::<Book>("books");
 
-    let photo_url = fetch_cover(req.title.clone()).await?;
-
-    let book = Book {
+    let mut book = Book {
         id: ObjectId::new(),
         user: user.id,
         title: req.title.clone(),
@@ -278,14 +1290,18 @@ pub async fn generate_book_outline(
         book_type: Some(req.title.clone()),
         main_topic: Some(req.title.clone()),
         completed: false,
-        cover: photo_url,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        ..Default::default()
     };
+    assign_default_cover(&mut book).await;
 
     book_collection.insert_one(book.clone()).await?;
+    record_book_creation(&db, user.id, book.id).await?;
 
-    let chapters = parse_outline(outline.clone(), book.id, req.language)?;
+    let mut chapters = parse_outline(outline.clone(), book.id, req.language)?;
+    // The model doesn't always stick to the requested chapter count.
+    chapters.truncate(user_plan(&user).limits().chapters_per_book as usize);
 
     let chapters_collection = db.collection::<Chapter>("chapters");
     chapters_collection.insert_many(chapters.clone()).await?;
@@ -331,6 +1347,8 @@ fn parse_outline(
         chapters.push(Chapter {
             id: ObjectId::new(),
             book_id,
+            position: chapters.len() as u32,
+            kind: ChapterKind::Body,
             title: title.to_string(),
             estimated_duration,
             markdown: bullet_points.trim().to_string(),
@@ -351,6 +1369,11 @@ fn parse_outline(
 pub async fn generate_chapter_content(
     req: GenerateChapterContentRequest,
 ) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    check_model(&user, &req.model)?;
+
     let mut client = get_ai(req.model.to_string()).await.lock().await;
 
     let content_prompt = format!(
@@ -371,44 +1394,42 @@ pub async fn generate_chapter_content(
     );
 
     let parameters = ChatBuilder::default()
-        .model(Model::Flash20)
+        .model(chat_model(&req.model)?)
         .messages(vec![Message::User {
             content: Content::Text(content_prompt.to_string()),
             name: None,
         }])
         .build()?;
 
-    let markdown = client
-        .chat()
-        .generate(parameters)
+    let markdown = generate(&client, Some(user.id), "chapter_markdown", parameters)
         .await
         .map_err(ServerFnError::new)?;
 
     let content_prompt = format!(
-        "Generate a comprehensive HTML-formatted book chapter with examples, links and images, based on the outline: '{}' in {language}. \
+        "Generate a comprehensive HTML-formatted book chapter with examples, links and illustrations, based on the outline: '{}' in {language}. \
         Each section should be structured with appropriate HTML tags, including <h1> for the main title, \
         <h2> for chapter titles, <h3> for subheadings, and <p> for paragraphs. \
         Include well-organized, readable content that aligns with the book's outline, ensuring each section is \
         clear and logically flows from one to the next. Avoid markdown format entirely, and provide inline HTML styling \
         if necessary to enhance readability. The HTML content should be well-formatted, semantically correct, and \
         cover all relevant subtopics in depth to create an engaging reading experience. \
+        {illustrations} \
         Make sure to always return back with html formmatted text and not empty response.
         ",
         markdown.clone(),
         language = req.language,
+        illustrations = PLACEHOLDER_INSTRUCTIONS,
     );
 
     let parameters = ChatBuilder::default()
-        .model(Model::Flash20)
+        .model(chat_model(&req.model)?)
         .messages(vec![Message::User {
             content: Content::Text(content_prompt.to_string()),
             name: None,
         }])
         .build()?;
 
-    let html = client
-        .chat()
-        .generate(parameters)
+    let html = generate(&client, Some(user.id), "chapter_html", parameters)
         .await
         .map_err(ServerFnError::new)?
         .trim_start_matches("```html")
@@ -423,54 +1444,72 @@ pub async fn generate_chapter_content(
     })
 }
 
+/// Writes the HTML of chapters opened before it was generated.
+const READER_MODEL: &str = "gemini-2.0-flash";
+
 #[server]
 pub async fn get_chapters_for_book(
     req: GetChaptersContentRequest,
 ) -> Result<SuccessResponse<Vec<Chapter>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let client = get_client().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let chapter_collection = db.collection::<Chapter>("chapters");
 
-    let mut gemini_client = get_ai("gemini-2.0-flash".to_string()).await.lock().await;
+    let mut gemini_client = get_ai(READER_MODEL.to_string()).await.lock().await;
 
     let book_object_id =
         ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
 
+    // Chapters are written on first read, on the reader's own plan.
+    db.collection::<Book>("books")
+        .find_one(doc! { "_id": book_object_id, "user": user.id })
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+
     let mut chapters = chapter_collection
         .find(doc! { "book_id": book_object_id })
+        .sort(doc! { "position": 1, "_id": 1 })
         .await?
         .try_collect::<Vec<Chapter>>()
         .await?;
 
+    if chapters.iter().any(|chapter| chapter.html.is_empty()) {
+        check_model(&user, READER_MODEL)?;
+    }
+
     for chapter in chapters.iter_mut() {
         if chapter.html.is_empty() {
             let markdown_content = chapter.markdown.clone();
 
             let content_prompt = format!(
-                "Generate a comprehensive HTML-formatted book chapter with examples, links and images, based on the outline: '{}' in {language}. \
+                "Generate a comprehensive HTML-formatted book chapter with examples, links and illustrations, based on the outline: '{}' in {language}. \
                 Each section should be structured with appropriate HTML tags, including <h1> for the main title, \
                 <h2> for chapter titles, <h3> for subheadings, and <p> for paragraphs. \
                 Include well-organized, readable content that aligns with the book's outline, ensuring each section is \
                 clear and logically flows from one to the next. Avoid markdown format entirely, and provide inline HTML styling \
                 if necessary to enhance readability. The HTML content should be well-formatted, semantically correct, and \
                 cover all relevant subtopics in depth to create an engaging reading experience. \
+                {illustrations} \
                 Make sure to always return back with html formmatted text and not empty response.",
                 markdown_content,
                 language = chapter.language,
+                illustrations = PLACEHOLDER_INSTRUCTIONS,
             );
 
             let parameters = ChatBuilder::default()
-                .model(Model::Flash20)
+                .model(chat_model(READER_MODEL)?)
                 .messages(vec![Message::User {
                     content: Content::Text(content_prompt.to_string()),
                     name: None,
                 }])
                 .build()?;
 
-            let html_content = gemini_client
-                .chat()
-                .generate(parameters)
+            let html_content = generate(&gemini_client, Some(user.id), "chapter_html", parameters)
                 .await
                 .map_err(ServerFnError::new)?
                 .trim_start_matches("```html")
@@ -495,6 +1534,510 @@ pub async fn get_chapters_for_book(
     })
 }
 
+/// Chapters stored before `position` existed come back in insertion order;
+/// gives them positions from that order. Runs once at startup.
+#[cfg(feature = "server")]
+pub(crate) async fn backfill_chapter_positions() -> Result<u64, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let chapter_collection = db.collection::<Chapter>("chapters");
+
+    let book_ids = chapter_collection
+        .distinct("book_id", doc! { "position": { "$exists": false } })
+        .await?;
+
+    let mut updated = 0;
+    for book_id in book_ids {
+        let chapters = chapter_collection
+            .find(doc! { "book_id": book_id })
+            .sort(doc! { "position": 1, "_id": 1 })
+            .await?
+            .try_collect::<Vec<Chapter>>()
+            .await?;
+
+        for (index, chapter) in chapters.iter().enumerate() {
+            chapter_collection
+                .update_one(
+                    doc! { "_id": chapter.id },
+                    doc! { "$set": { "position": index as u32 } },
+                )
+                .await?;
+        }
+        updated += chapters.len() as u64;
+    }
+
+    Ok(updated)
+}
+
+/// Number of body chapters; back matter always follows them.
+fn body_len(chapters: &[Chapter]) -> usize {
+    chapters
+        .iter()
+        .position(|chapter| chapter.kind.is_back_matter())
+        .unwrap_or(chapters.len())
+}
+
+/// Loads the ordered outline of a book after checking that `user_id` owns it.
+#[cfg(feature = "server")]
+pub(crate) async fn load_outline(
+    db: &mongodb::Database,
+    user_id: ObjectId,
+    book_id: ObjectId,
+) -> Result<(Book, Vec<Chapter>), ServerFnError> {
+    let book = db
+        .collection::<Book>("books")
+        .find_one(doc! { "_id": book_id, "user": user_id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+
+    let chapters = db
+        .collection::<Chapter>("chapters")
+        .find(doc! { "book_id": book_id })
+        .sort(doc! { "position": 1, "_id": 1 })
+        .await?
+        .try_collect::<Vec<Chapter>>()
+        .await?;
+
+    Ok((book, chapters))
+}
+
+/// Resolves a chapter id to its book's outline, checking ownership on the way.
+#[cfg(feature = "server")]
+pub(crate) async fn load_outline_for_chapter(
+    db: &mongodb::Database,
+    user_id: ObjectId,
+    chapter_id: &str,
+) -> Result<(Book, Vec<Chapter>, usize), ServerFnError> {
+    let chapter_id =
+        ObjectId::parse_str(chapter_id).map_err(|_| ServerFnError::new("Invalid chapter ID"))?;
+
+    let chapter = db
+        .collection::<Chapter>("chapters")
+        .find_one(doc! { "_id": chapter_id })
+        .await?
+        .ok_or(ServerFnError::new("Chapter not found"))?;
+
+    let (book, chapters) = load_outline(db, user_id, chapter.book_id).await?;
+    let index = chapters
+        .iter()
+        .position(|c| c.id == chapter_id)
+        .ok_or(ServerFnError::new("Chapter not found"))?;
+
+    Ok((book, chapters, index))
+}
+
+/// Writes back `position` for every chapter whose index changed.
+#[cfg(feature = "server")]
+pub(crate) async fn save_positions(
+    db: &mongodb::Database,
+    chapters: &mut [Chapter],
+) -> Result<(), ServerFnError> {
+    let chapter_collection = db.collection::<Chapter>("chapters");
+
+    for (index, chapter) in chapters.iter_mut().enumerate() {
+        if chapter.position != index as u32 {
+            chapter.position = index as u32;
+            chapter_collection
+                .update_one(
+                    doc! { "_id": chapter.id },
+                    doc! { "$set": { "position": index as u32, "updatedAt": Utc::now() } },
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the tag boundary closest to `target` so a split never cuts through
+/// an element. Falls back to `target` itself on a char boundary.
+fn split_point(html: &str, target: usize) -> usize {
+    let boundary = ["<h1", "<h2", "<h3", "<p", "<ul", "<ol", "<section"]
+        .iter()
+        .flat_map(|tag| html.match_indices(tag).map(|(index, _)| index))
+        .filter(|index| *index > 0)
+        .min_by_key(|index| index.abs_diff(target));
+
+    match boundary {
+        Some(index) => index,
+        None => {
+            let mut index = target.min(html.len());
+            while !html.is_char_boundary(index) {
+                index -= 1;
+            }
+            index
+        }
+    }
+}
+
+/// Splits markdown at the paragraph break closest to `ratio` of the way
+/// through, so each half keeps roughly the text of its half of the HTML.
+fn split_markdown(markdown: &str, ratio: f64) -> (String, String) {
+    let target = (markdown.len() as f64 * ratio) as usize;
+    let offset = markdown
+        .match_indices("\n\n")
+        .map(|(index, _)| index)
+        .min_by_key(|index| index.abs_diff(target))
+        .unwrap_or(markdown.len());
+    let (first, second) = markdown.split_at(offset);
+    (first.trim().to_string(), second.trim().to_string())
+}
+
+#[server]
+pub async fn reorder_chapters(
+    req: ReorderChaptersRequest,
+) -> Result<SuccessResponse<Vec<Chapter>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let (_, chapters) = load_outline(&db, user.id, book_id).await?;
+
+    if req.chapter_ids.len() != chapters.len() {
+        return Err(ServerFnError::new("Chapter list does not match the book"));
+    }
+
+    let mut reordered = Vec::with_capacity(chapters.len());
+    for id in &req.chapter_ids {
+        let chapter = chapters
+            .iter()
+            .find(|c| c.id.to_hex() == *id)
+            .ok_or(ServerFnError::new("Chapter list does not match the book"))?;
+        if reordered.iter().any(|c: &Chapter| c.id == chapter.id) {
+            return Err(ServerFnError::new("Duplicate chapter in list"));
+        }
+        reordered.push(chapter.clone());
+    }
+    if body_len(&reordered) != body_len(&chapters)
+        || reordered[body_len(&reordered)..]
+            .iter()
+            .any(|chapter| !chapter.kind.is_back_matter())
+    {
+        return Err(ServerFnError::new("Back matter must stay after the last chapter"));
+    }
+
+    save_positions(&db, &mut reordered).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: reordered,
+    })
+}
+
+#[server]
+pub async fn insert_chapter(
+    req: InsertChapterRequest,
+) -> Result<SuccessResponse<Vec<Chapter>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    if req.title.trim().is_empty() {
+        return Err(ServerFnError::new("Chapter title can't be blank"));
+    }
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let chapter_collection = db.collection::<Chapter>("chapters");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let (book, mut chapters) = load_outline(&db, user.id, book_id).await?;
+    check_chapters(&user, body_len(&chapters) + 1)?;
+    if req.generate {
+        check_model(&user, &req.model)?;
+    }
+    let index = (req.position as usize).min(body_len(&chapters));
+
+    let language = chapters
+        .get(index)
+        .or(chapters.last())
+        .map(|c| c.language.clone())
+        .unwrap_or_else(|| "English".to_string());
+
+    let mut chapter = Chapter {
+        id: ObjectId::new(),
+        book_id,
+        position: index as u32,
+        kind: ChapterKind::Body,
+        title: req.title.trim().to_string(),
+        estimated_duration: 0,
+        markdown: String::new(),
+        language: language.clone(),
+        html: String::new(),
+        completed: false,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+
+    if req.generate {
+        let mut ai_client = get_ai(req.model.to_string()).await.lock().await;
+
+        let previous = index
+            .checked_sub(1)
+            .and_then(|i| chapters.get(i))
+            .map(|c| c.title.clone())
+            .unwrap_or_else(|| "none, this is the first chapter".to_string());
+        let next = chapters
+            .get(index)
+            .map(|c| c.title.clone())
+            .unwrap_or_else(|| "none, this is the last chapter".to_string());
+
+        let content_prompt = format!(
+            "
+            **System Prompt (SP):** You are writing detailed content for a new chapter inserted into an existing book.
+
+            **Prompt (P):** Write content for chapter '{chapter_title}' of the book '{book_title}' in {language}. The chapter comes right after '{previous}' and right before '{next}', so it must bridge the two without repeating them. Ensure clarity, detailed explanations, and structured markdown.
+
+            **Expected Format (EF):**
+            - detailed markdown format for this chapter.
+
+            **Roleplay (RP):** Provide as much educational content as possible.
+            ",
+            chapter_title = chapter.title,
+            book_title = book.title,
+            language = language,
+            previous = previous,
+            next = next,
+        );
+
+        let parameters = ChatBuilder::default()
+            .model(chat_model(&req.model)?)
+            .messages(vec![Message::User {
+                content: Content::Text(content_prompt.to_string()),
+                name: None,
+            }])
+            .build()?;
+
+        let markdown = generate(&ai_client, Some(user.id), "chapter_markdown", parameters)
+            .await
+            .map_err(ServerFnError::new)?;
+
+        let content_prompt = format!(
+            "Generate a comprehensive HTML-formatted book chapter with examples, links and illustrations, based on the outline: '{}' in {language}. \
+            Each section should be structured with appropriate HTML tags, including <h1> for the main title, \
+            <h2> for chapter titles, <h3> for subheadings, and <p> for paragraphs. \
+            Include well-organized, readable content that aligns with the book's outline, ensuring each section is \
+            clear and logically flows from one to the next. Avoid markdown format entirely, and provide inline HTML styling \
+            if necessary to enhance readability. The HTML content should be well-formatted, semantically correct, and \
+            cover all relevant subtopics in depth to create an engaging reading experience. \
+            {illustrations} \
+            Make sure to always return back with html formmatted text and not empty response.",
+            markdown.clone(),
+            language = language,
+            illustrations = PLACEHOLDER_INSTRUCTIONS,
+        );
+
+        let parameters = ChatBuilder::default()
+            .model(chat_model(&req.model)?)
+            .messages(vec![Message::User {
+                content: Content::Text(content_prompt.to_string()),
+                name: None,
+            }])
+            .build()?;
+
+        chapter.html = generate(&ai_client, Some(user.id), "chapter_html", parameters)
+            .await
+            .map_err(ServerFnError::new)?
+            .trim_start_matches("```html")
+            .trim_end_matches("```")
+            .trim()
+            .to_string();
+        chapter.markdown = markdown;
+        chapter.completed = true;
+    }
+
+    chapter_collection.insert_one(chapter.clone()).await?;
+    chapters.insert(index, chapter);
+    save_positions(&db, &mut chapters).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: chapters,
+    })
+}
+
+#[server]
+pub async fn split_chapter(
+    req: SplitChapterRequest,
+) -> Result<SuccessResponse<Vec<Chapter>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let chapter_collection = db.collection::<Chapter>("chapters");
+
+    let (_, mut chapters, index) = load_outline_for_chapter(&db, user.id, &req.chapter_id).await?;
+    check_chapters(&user, body_len(&chapters) + 1)?;
+    let chapter = chapters[index].clone();
+
+    if chapter.kind.is_back_matter() {
+        return Err(ServerFnError::new("Back matter can't be split"));
+    }
+    if chapter.html.is_empty() {
+        return Err(ServerFnError::new("Chapter has no content to split yet"));
+    }
+
+    let offset = match req.html_offset {
+        Some(offset) if offset > 0 && offset < chapter.html.len() => {
+            if !chapter.html.is_char_boundary(offset) {
+                return Err(ServerFnError::new("Invalid split position"));
+            }
+            offset
+        }
+        Some(_) => return Err(ServerFnError::new("Invalid split position")),
+        None => split_point(&chapter.html, chapter.html.len() / 2),
+    };
+
+    let (first_html, second_html) = chapter.html.split_at(offset);
+    if first_html.trim().is_empty() || second_html.trim().is_empty() {
+        return Err(ServerFnError::new("Chapter is too short to split"));
+    }
+
+    let ratio = offset as f64 / chapter.html.len() as f64;
+    let first_duration = (chapter.estimated_duration as f64 * ratio).round() as u64;
+    let (first_markdown, second_markdown) = split_markdown(&chapter.markdown, ratio);
+
+    let second = Chapter {
+        id: ObjectId::new(),
+        book_id: chapter.book_id,
+        position: index as u32 + 1,
+        kind: chapter.kind,
+        title: req
+            .new_title
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| format!("{} (Part 2)", chapter.title)),
+        estimated_duration: chapter.estimated_duration - first_duration,
+        markdown: second_markdown,
+        language: chapter.language.clone(),
+        html: second_html.trim().to_string(),
+        completed: chapter.completed,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+
+    chapter_collection
+        .update_one(
+            doc! { "_id": chapter.id },
+            doc! { "$set": {
+                "html": first_html.trim(),
+                "markdown": &first_markdown,
+                "estimated_duration": first_duration as i64,
+                "updatedAt": Utc::now(),
+            }},
+        )
+        .await?;
+    chapter_collection.insert_one(second.clone()).await?;
+
+    chapters[index].html = first_html.trim().to_string();
+    chapters[index].markdown = first_markdown;
+    chapters[index].estimated_duration = first_duration;
+    chapters.insert(index + 1, second);
+    save_positions(&db, &mut chapters).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: chapters,
+    })
+}
+
+#[server]
+pub async fn merge_chapters(
+    req: ChapterRequest,
+) -> Result<SuccessResponse<Vec<Chapter>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let chapter_collection = db.collection::<Chapter>("chapters");
+
+    let (_, mut chapters, index) = load_outline_for_chapter(&db, user.id, &req.chapter_id).await?;
+
+    if index + 1 >= chapters.len() {
+        return Err(ServerFnError::new("There is no next chapter to merge with"));
+    }
+    if chapters[index].kind.is_back_matter() || chapters[index + 1].kind.is_back_matter() {
+        return Err(ServerFnError::new("Back matter can't be merged"));
+    }
+
+    let next = chapters.remove(index + 1);
+    let chapter = &mut chapters[index];
+
+    chapter.html = format!("{}\n{}", chapter.html, next.html).trim().to_string();
+    chapter.markdown = format!("{}\n\n{}", chapter.markdown, next.markdown)
+        .trim()
+        .to_string();
+    chapter.estimated_duration += next.estimated_duration;
+    chapter.completed = chapter.completed && next.completed;
+
+    chapter_collection
+        .update_one(
+            doc! { "_id": chapter.id },
+            doc! { "$set": {
+                "html": &chapter.html,
+                "markdown": &chapter.markdown,
+                "estimated_duration": chapter.estimated_duration as i64,
+                "completed": chapter.completed,
+                "updatedAt": Utc::now(),
+            }},
+        )
+        .await?;
+    chapter_collection
+        .delete_one(doc! { "_id": next.id })
+        .await?;
+
+    save_positions(&db, &mut chapters).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: chapters,
+    })
+}
+
+#[server]
+pub async fn delete_chapter(
+    req: ChapterRequest,
+) -> Result<SuccessResponse<Vec<Chapter>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let chapter_collection = db.collection::<Chapter>("chapters");
+
+    let (_, mut chapters, index) = load_outline_for_chapter(&db, user.id, &req.chapter_id).await?;
+
+    let removed = chapters.remove(index);
+    chapter_collection
+        .delete_one(doc! { "_id": removed.id })
+        .await?;
+
+    save_positions(&db, &mut chapters).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: chapters,
+    })
+}
+
 #[server]
 async fn update_chapter_content(
     chapter_id: ObjectId,
@@ -525,6 +2068,10 @@ async fn update_chapter_content(
 
 #[server]
 pub async fn summarize_text(req: AIRequest) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let mut client = get_ai("gemini-2.0-flash".to_string()).await.lock().await;
     let prompt = format!("Summarize the following text: '{}'", req.text);
 
@@ -536,7 +2083,7 @@ pub async fn summarize_text(req: AIRequest) -> Result<SuccessResponse<String>, S
         }])
         .build()?;
 
-    match client.chat().generate(parameters).await {
+    match generate(&client, Some(user.id), "summarize", parameters).await {
         Ok(summary) => Ok(SuccessResponse {
             status: "success".into(),
             data: summary.into(),
@@ -547,6 +2094,10 @@ pub async fn summarize_text(req: AIRequest) -> Result<SuccessResponse<String>, S
 
 #[server]
 pub async fn regenerate_text(req: AIRequest) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let mut client = get_ai("gemini-2.0-flash".to_string()).await.lock().await;
     let prompt = format!("Rephrase the following text: '{}'", req.text);
 
@@ -558,7 +2109,7 @@ pub async fn regenerate_text(req: AIRequest) -> Result<SuccessResponse<String>,
         }])
         .build()?;
 
-    match client.chat().generate(parameters).await {
+    match generate(&client, Some(user.id), "rephrase", parameters).await {
         Ok(rephrased) => Ok(SuccessResponse {
             status: "success".into(),
             data: rephrased.into(),
@@ -569,6 +2120,10 @@ pub async fn regenerate_text(req: AIRequest) -> Result<SuccessResponse<String>,
 
 #[server]
 pub async fn extend_text(req: AIRequest) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let mut client = get_ai("gemini-2.0-flash".to_string()).await.lock().await;
     let prompt = format!(
         "Expand on the following text with additional details: '{}'",
@@ -583,7 +2138,7 @@ pub async fn extend_text(req: AIRequest) -> Result<SuccessResponse<String>, Serv
         }])
         .build()?;
 
-    match client.chat().generate(parameters).await {
+    match generate(&client, Some(user.id), "extend", parameters).await {
         Ok(extended) => Ok(SuccessResponse {
             status: "success".into(),
             data: extended.into(),
@@ -609,7 +2164,7 @@ pub async fn fetch_analytics_data(
 
     // Engagement Metrics
     let total_books = books_collection
-        .count_documents(doc! { "user": user.id })
+        .count_documents(doc! { "user": user.id, "deletedAt": null })
         .await?;
     let mut total_chapters = 0;
     if total_books > 0 {
@@ -621,29 +2176,53 @@ pub async fn fetch_analytics_data(
         0.0
     };
 
-    let mut total_estimated_duration = 0.0;
-    let total_ai_chapters = total_chapters as u64;
-
     // AI Usage Metrics
-    if total_books > 0 {
-        let total_estimated_duration: u64 = chapters_collection
-            .aggregate(vec![
-                doc! { "$group": { "_id": null, "total_duration": { "$sum": "$estimated_duration" } } },
-            ])
-            .await?
-            .next()
-            .await
-            .and_then(|doc| doc.ok()?.get_i64("total_duration").ok())
-            .unwrap_or(0) as u64;
-    }
-
-    let avg_gen_time = if total_ai_chapters > 0 {
-        total_estimated_duration as f64 / total_ai_chapters as f64
+    let usage = db
+        .collection::<AiCall>("ai_calls")
+        .aggregate(vec![
+            doc! { "$match": { "user": user.id } },
+            doc! { "$group": {
+                "_id": null,
+                "calls": { "$sum": 1 },
+                "successes": { "$sum": { "$cond": [{ "$eq": ["$outcome", "Success"] }, 1, 0] } },
+                "chapters": { "$sum": { "$cond": [
+                    { "$and": [
+                        { "$eq": ["$outcome", "Success"] },
+                        { "$eq": ["$template", "chapter_html"] },
+                    ] },
+                    1,
+                    0,
+                ] } },
+                // $avg skips the nulls, so failed calls don't count.
+                "latency": { "$avg": { "$cond": [
+                    { "$eq": ["$outcome", "Success"] },
+                    "$latency_ms",
+                    null,
+                ] } },
+                "tokens": { "$sum": { "$add": ["$input_tokens", "$output_tokens"] } },
+            }},
+        ])
+        .await?
+        .next()
+        .await
+        .transpose()?
+        .unwrap_or_default();
+
+    let number = |key: &str| match usage.get(key) {
+        Some(Bson::Int32(value)) => *value as f64,
+        Some(Bson::Int64(value)) => *value as f64,
+        Some(Bson::Double(value)) => *value,
+        _ => 0.0,
+    };
+    let total_ai_calls = number("calls") as u64;
+    let total_ai_chapters = number("chapters") as u64;
+    let avg_gen_time = number("latency") / 1000.0;
+    let success_rate = if total_ai_calls > 0 {
+        number("successes") / total_ai_calls as f64 * 100.0
     } else {
         0.0
     };
-
-    let success_rate = 100.0;
+    let total_tokens = number("tokens") as u64;
 
     // Trending Topic
     let trending_topic = books_collection
@@ -705,9 +2284,11 @@ pub async fn fetch_analytics_data(
                 avg_chapters_per_book,
             },
             ai_usage: AIUsageStats {
+                total_ai_calls,
                 total_ai_chapters,
                 avg_gen_time,
                 success_rate,
+                total_tokens,
             },
             predictions: PredictiveStats {
                 trending_genre: trending_topic,
@@ -716,3 +2297,25 @@ pub async fn fetch_analytics_data(
         },
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_splits_at_the_nearest_paragraph() {
+        let markdown = "# Intro\n\nFirst part.\n\n## More\n\nSecond part.";
+        let (first, second) = split_markdown(markdown, 0.5);
+        assert_eq!(first, "# Intro\n\nFirst part.");
+        assert_eq!(second, "## More\n\nSecond part.");
+    }
+
+    #[test]
+    fn markdown_without_paragraphs_stays_in_the_first_half() {
+        assert_eq!(
+            split_markdown("One long paragraph.", 0.5),
+            ("One long paragraph.".to_string(), String::new())
+        );
+        assert_eq!(split_markdown("", 0.5), (String::new(), String::new()));
+    }
+}
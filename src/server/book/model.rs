@@ -1,9 +1,17 @@
 #![allow(non_snake_case)]
 
-use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use bson::{
+    oid::ObjectId,
+    serde_helpers::{chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional},
+};
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::server::cover::model::CoverCredit;
+
+/// Days a soft-deleted book stays in the trash before it is purged for good.
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct Book {
     #[serde(rename = "_id")]
@@ -17,17 +25,177 @@ pub struct Book {
     pub main_topic: Option<String>,
     pub completed: bool,
     pub cover: Option<String>,
+    /// Set when the cover is an Unsplash photo.
+    #[serde(default, rename = "coverCredit")]
+    pub cover_credit: Option<CoverCredit>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub collections: Vec<String>,
+    #[serde(default)]
+    pub series: Option<SeriesEntry>,
+    #[serde(default, rename = "frontMatter")]
+    pub front_matter: Vec<FrontMatterEntry>,
+    /// The original book this one is a translated edition of.
+    #[serde(default, rename = "editionOf")]
+    pub edition_of: Option<ObjectId>,
+    /// Set on translated editions; originals use their chapters' language.
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub translation: Option<TranslationProgress>,
+    #[serde(default)]
+    pub published: bool,
+    /// Left out of the document until set, so the unique index only
+    /// sees real slugs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    #[serde(
+        default,
+        rename = "shareToken",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub share_token: Option<String>,
+    #[serde(
+        default,
+        with = "chrono_datetime_as_bson_datetime_optional",
+        rename = "shareExpiresAt"
+    )]
+    pub share_expires_at: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        with = "chrono_datetime_as_bson_datetime_optional",
+        rename = "deletedAt"
+    )]
+    pub deleted_at: Option<DateTime<Utc>>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
 
+/// What's left of a book after a `{ "title": 1 }` projection.
+#[derive(Debug, Deserialize, Clone)]
+pub struct BookTitle {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub title: String,
+}
+
+/// A book's place in a user-defined series; `order` is 1-based.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
+pub struct SeriesEntry {
+    pub name: String,
+    pub order: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum TranslationStatus {
+    #[default]
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct TranslationProgress {
+    pub status: TranslationStatus,
+    pub translated: u32,
+    pub total: u32,
+    pub error: Option<String>,
+}
+
+/// Author-written pages that frame the generated chapters.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrontMatterKind {
+    Dedication,
+    Preface,
+    Acknowledgements,
+    Introduction,
+    /// "About the Author", placed after the back matter.
+    AuthorBio,
+    /// Back-cover copy.
+    Blurb,
+}
+
+impl FrontMatterKind {
+    /// Every kind, in the order they appear in an exported book.
+    pub const ALL: [FrontMatterKind; 6] = [
+        FrontMatterKind::Dedication,
+        FrontMatterKind::Preface,
+        FrontMatterKind::Acknowledgements,
+        FrontMatterKind::Introduction,
+        FrontMatterKind::AuthorBio,
+        FrontMatterKind::Blurb,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            FrontMatterKind::Dedication => "Dedication",
+            FrontMatterKind::Preface => "Preface",
+            FrontMatterKind::Acknowledgements => "Acknowledgements",
+            FrontMatterKind::Introduction => "Introduction",
+            FrontMatterKind::AuthorBio => "About the Author",
+            FrontMatterKind::Blurb => "Back Cover",
+        }
+    }
+
+    pub fn slug(self) -> &'static str {
+        match self {
+            FrontMatterKind::Dedication => "dedication",
+            FrontMatterKind::Preface => "preface",
+            FrontMatterKind::Acknowledgements => "acknowledgements",
+            FrontMatterKind::Introduction => "introduction",
+            FrontMatterKind::AuthorBio => "about-the-author",
+            FrontMatterKind::Blurb => "back-cover",
+        }
+    }
+
+    /// Whether the entry is printed before the first chapter.
+    pub fn precedes_body(self) -> bool {
+        !matches!(self, FrontMatterKind::AuthorBio | FrontMatterKind::Blurb)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct FrontMatterEntry {
+    pub kind: FrontMatterKind,
+    /// Plain text; blank lines separate paragraphs.
+    pub text: String,
+}
+
+impl Book {
+    pub fn front_matter(&self, kind: FrontMatterKind) -> Option<&FrontMatterEntry> {
+        self.front_matter.iter().find(|entry| entry.kind == kind)
+    }
+}
+
+/// Back-matter chapters are generated from the rest of the book and kept
+/// after the last body chapter.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum ChapterKind {
+    #[default]
+    Body,
+    Glossary,
+    Index,
+    Bibliography,
+}
+
+impl ChapterKind {
+    pub fn is_back_matter(self) -> bool {
+        self != ChapterKind::Body
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Chapter {
     #[serde(rename = "_id")]
     pub id: ObjectId,
     pub book_id: ObjectId,
+    #[serde(default)]
+    pub position: u32,
+    #[serde(default)]
+    pub kind: ChapterKind,
     pub title: String,
     pub estimated_duration: u64,
     pub markdown: String,
@@ -39,3 +207,11 @@ pub struct Chapter {
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
+
+impl Chapter {
+    /// The fragment id a chapter is rendered under, used by the index and
+    /// the table of contents.
+    pub fn anchor(&self) -> String {
+        format!("chapter-{}", self.id.to_hex())
+    }
+}
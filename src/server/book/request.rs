@@ -1,4 +1,6 @@
 use bson::oid::ObjectId;
+use crate::server::book::model::FrontMatterEntry;
+use crate::server::book::model::FrontMatterKind;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,6 +13,21 @@ pub struct StoreBookRequest {
     pub main_topic: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetFrontMatterRequest {
+    pub token: String,
+    pub book_id: String,
+    pub entries: Vec<FrontMatterEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateFrontMatterRequest {
+    pub token: String,
+    pub book_id: String,
+    pub kind: FrontMatterKind,
+    pub model: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateBookContentRequest {
     pub token: String,
@@ -42,6 +59,7 @@ pub struct GenerateBookRequest {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GenerateChapterContentRequest {
+    pub token: String,
     pub chapter_title: String,
     pub chapter_id: ObjectId,
     pub book_title: String,
@@ -64,5 +82,142 @@ pub struct AIRequest {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GetChaptersContentRequest {
+    pub token: String,
+    pub book_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishBookRequest {
+    pub token: String,
+    pub book_id: String,
+    pub published: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateShareLinkRequest {
+    pub token: String,
+    pub book_id: String,
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevokeShareLinkRequest {
+    pub token: String,
+    pub book_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GetPublicBookRequest {
+    pub slug: String,
+    pub share_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReorderChaptersRequest {
+    pub token: String,
+    pub book_id: String,
+    pub chapter_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsertChapterRequest {
+    pub token: String,
+    pub book_id: String,
+    pub position: u32,
+    pub title: String,
+    pub generate: bool,
+    pub model: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SplitChapterRequest {
+    pub token: String,
+    pub chapter_id: String,
+    pub html_offset: Option<usize>,
+    pub new_title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChapterRequest {
+    pub token: String,
+    pub chapter_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookRequest {
+    pub token: String,
+    pub book_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum BookSort {
+    #[default]
+    Created,
+    Updated,
+    Title,
+    Completion,
+    SeriesOrder,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ListBooksRequest {
+    pub token: String,
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub sort: BookSort,
+    pub order: SortOrder,
+    pub completed: Option<bool>,
+    pub book_type: Option<String>,
+    pub tag: Option<String>,
+    pub collection: Option<String>,
+    pub series: Option<String>,
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetBookLabelsRequest {
+    pub token: String,
+    pub book_id: String,
+    pub tags: Vec<String>,
+    pub collections: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetBookSeriesRequest {
+    pub token: String,
     pub book_id: String,
+    /// `None` removes the book from its series.
+    pub series: Option<String>,
+    /// Defaults to the end of the series.
+    pub order: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum LabelKind {
+    Tag,
+    Collection,
+    Series,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RenameLabelRequest {
+    pub token: String,
+    pub kind: LabelKind,
+    pub name: String,
+    /// `None` removes the label from every book instead of renaming it.
+    pub new_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReorderSeriesRequest {
+    pub token: String,
+    pub series: String,
+    pub book_ids: Vec<String>,
 }
@@ -1,6 +1,8 @@
 use crate::server::book::model::Book;
 use crate::server::book::model::Chapter;
+use crate::server::cover::model::CoverCredit;
 use bson::oid::ObjectId;
+use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -14,6 +16,37 @@ pub struct GenerateBookOutlineResponse {
     pub book: Book,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BooksPageResponse {
+    pub books: Vec<Book>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct LibraryFacets {
+    pub book_types: Vec<String>,
+    pub tags: Vec<String>,
+    pub collections: Vec<String>,
+    pub series: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ShareLinkResponse {
+    pub slug: String,
+    pub published: bool,
+    pub share_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublicBookResponse {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub cover: Option<String>,
+    pub cover_credit: Option<CoverCredit>,
+    pub chapters: Vec<Chapter>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct AnalyticsData {
     pub engagement: EngagementStats,
@@ -30,9 +63,12 @@ pub struct EngagementStats {
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct AIUsageStats {
+    pub total_ai_calls: u64,
     pub total_ai_chapters: u64,
+    /// Mean latency of successful calls, in seconds.
     pub avg_gen_time: f64,
     pub success_rate: f64,
+    pub total_tokens: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -0,0 +1,192 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use dioxus::prelude::*;
+
+use crate::server::catalog::model::CatalogPlan;
+use crate::server::catalog::model::Interval;
+use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::model::Plan;
+
+#[cfg(feature = "server")]
+use {
+    crate::db::get_client,
+    crate::pay::get_stripe,
+    futures_util::TryStreamExt,
+    mongodb::Database,
+    std::str::FromStr,
+    stripe::{Price, PriceId, RecurringInterval},
+};
+
+/// The catalog a fresh database starts with. Paid plans are only listed
+/// when their Stripe price is configured.
+#[cfg(feature = "server")]
+fn default_catalog() -> Vec<CatalogPlan> {
+    let mut plans = vec![CatalogPlan {
+        id: "free".into(),
+        name: "Free".into(),
+        description: "Sign up for free".into(),
+        features: Vec::new(),
+        plan: Plan::Free,
+        price_id: None,
+        paypal_plan_id: None,
+        amount: 0,
+        currency: "usd".into(),
+        interval: None,
+        trial_days: 0,
+        highlight: false,
+        position: 0,
+        active: true,
+    }];
+    if let Ok(price) = std::env::var("STRIPE_PRICE_ONE") {
+        plans.push(CatalogPlan {
+            id: "monthly".into(),
+            name: "Monthly".into(),
+            description: "For frequent creators".into(),
+            features: Vec::new(),
+            plan: Plan::Monthly,
+            price_id: Some(price),
+            paypal_plan_id: None,
+            amount: 200,
+            currency: "usd".into(),
+            interval: Some(Interval::Month),
+            trial_days: 0,
+            highlight: true,
+            position: 1,
+            active: true,
+        });
+    }
+    if let Ok(price) = std::env::var("STRIPE_PRICE_TWO") {
+        plans.push(CatalogPlan {
+            id: "yearly".into(),
+            name: "Yearly".into(),
+            description: "Two months free".into(),
+            features: Vec::new(),
+            plan: Plan::Yearly,
+            price_id: Some(price),
+            paypal_plan_id: None,
+            amount: 10000,
+            currency: "usd".into(),
+            interval: Some(Interval::Year),
+            trial_days: 0,
+            highlight: false,
+            position: 2,
+            active: true,
+        });
+    }
+    plans
+}
+
+/// The entitlements a Stripe price or PayPal plan sells. Retired plans
+/// still resolve, so their existing subscribers keep what they paid for.
+#[cfg(feature = "server")]
+pub(crate) async fn plan_for_price(
+    db: &Database,
+    price: &str,
+) -> Result<Option<Plan>, ServerFnError> {
+    Ok(db
+        .collection::<CatalogPlan>("plans")
+        .find_one(doc! { "$or": [{ "priceId": price }, { "paypalPlanId": price }] })
+        .await?
+        .map(|plan| plan.plan))
+}
+
+/// A plan users can still check out.
+#[cfg(feature = "server")]
+pub(crate) async fn active_plan(
+    db: &Database,
+    id: &str,
+) -> Result<Option<CatalogPlan>, ServerFnError> {
+    Ok(db
+        .collection::<CatalogPlan>("plans")
+        .find_one(doc! { "_id": id, "active": true })
+        .await?)
+}
+
+/// Seeds an empty catalog, then copies amount, currency, interval and
+/// availability from each plan's Stripe price. Returns how many plans
+/// changed.
+#[cfg(feature = "server")]
+pub(crate) async fn sync_plan_catalog() -> Result<u64, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let plan_collection = db.collection::<CatalogPlan>("plans");
+
+    if plan_collection.count_documents(doc! {}).await? == 0 {
+        plan_collection.insert_many(default_catalog()).await?;
+    }
+
+    let plans = plan_collection
+        .find(doc! { "priceId": { "$ne": null } })
+        .await?
+        .try_collect::<Vec<CatalogPlan>>()
+        .await?;
+
+    let stripe_client = get_stripe().await.lock().await;
+    let mut changed = 0;
+    for plan in plans {
+        let Some(price_id) = plan.price_id.as_deref() else {
+            continue;
+        };
+        let price_id =
+            PriceId::from_str(price_id).map_err(|_| ServerFnError::new("Invalid price ID"))?;
+        let price = Price::retrieve(&stripe_client, &price_id, &[]).await?;
+
+        let interval = price.recurring.map(|recurring| match recurring.interval {
+            RecurringInterval::Day => Interval::Day,
+            RecurringInterval::Week => Interval::Week,
+            RecurringInterval::Month => Interval::Month,
+            RecurringInterval::Year => Interval::Year,
+        });
+        let amount = price.unit_amount.unwrap_or(plan.amount);
+        let currency = price
+            .currency
+            .map(|currency| currency.to_string())
+            .unwrap_or(plan.currency.clone());
+        let active = price.active.unwrap_or(plan.active);
+        if (amount, &currency, interval, active)
+            == (plan.amount, &plan.currency, plan.interval, plan.active)
+        {
+            continue;
+        }
+
+        plan_collection
+            .update_one(
+                doc! { "_id": &plan.id },
+                doc! {
+                    "$set": {
+                        "amount": amount,
+                        "currency": currency,
+                        "interval": bson::to_bson(&interval)?,
+                        "active": active,
+                    }
+                },
+            )
+            .await?;
+        changed += 1;
+    }
+
+    Ok(changed)
+}
+
+#[server]
+pub async fn get_plan_catalog() -> Result<SuccessResponse<Vec<CatalogPlan>>, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let plans = db
+        .collection::<CatalogPlan>("plans")
+        .find(doc! { "active": true })
+        .sort(doc! { "position": 1 })
+        .await?
+        .try_collect::<Vec<CatalogPlan>>()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: plans,
+    })
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use crate::server::entitlement::model::Plan;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Interval {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Interval {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Interval::Day => "day",
+            Interval::Week => "week",
+            Interval::Month => "month",
+            Interval::Year => "year",
+        }
+    }
+}
+
+/// A plan on the pricing page, stored in `plans`. Adding one only takes a
+/// new document with a Stripe price; its amount, currency and interval are
+/// copied from that price.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct CatalogPlan {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Extra bullets for the pricing page. The plan's limits are listed
+    /// from `plan` and don't belong here.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// The entitlements a subscriber gets.
+    pub plan: Plan,
+    /// Absent for the free plan, which has nothing to check out.
+    #[serde(rename = "priceId")]
+    pub price_id: Option<String>,
+    /// The PayPal billing plan selling the same thing, if any.
+    #[serde(default, rename = "paypalPlanId")]
+    pub paypal_plan_id: Option<String>,
+    /// In the currency's smallest unit, e.g. cents.
+    pub amount: i64,
+    pub currency: String,
+    pub interval: Option<Interval>,
+    /// Free days before the first charge, 0 for none.
+    #[serde(default, rename = "trialDays")]
+    pub trial_days: u32,
+    #[serde(default)]
+    pub highlight: bool,
+    /// Where the plan sits on the pricing page, lowest first.
+    #[serde(default)]
+    pub position: i32,
+    pub active: bool,
+}
+
+impl CatalogPlan {
+    /// E.g. `$2` or `€9.50`, without the interval.
+    pub fn amount_label(&self) -> String {
+        let symbol = match self.currency.as_str() {
+            "usd" => "$".to_string(),
+            "eur" => "€".to_string(),
+            "gbp" => "£".to_string(),
+            other => format!("{} ", other.to_uppercase()),
+        };
+        let amount = if self.amount % 100 == 0 {
+            (self.amount / 100).to_string()
+        } else {
+            format!("{:.2}", self.amount as f64 / 100.0)
+        };
+        format!("{}{}", symbol, amount)
+    }
+}
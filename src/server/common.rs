@@ -1,2 +1,5 @@
+pub(crate) mod html;
+pub(crate) mod language;
 pub(crate) mod request;
 pub(crate) mod response;
+pub(crate) mod zip;
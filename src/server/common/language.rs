@@ -0,0 +1,19 @@
+/// Maps the language names chapters are generated in to BCP 47 tags.
+pub fn language_tag(language: &str) -> &'static str {
+    match language.trim().to_lowercase().as_str() {
+        "arabic" => "ar",
+        "chinese" => "zh",
+        "dutch" => "nl",
+        "french" => "fr",
+        "german" => "de",
+        "hindi" => "hi",
+        "italian" => "it",
+        "japanese" => "ja",
+        "korean" => "ko",
+        "portuguese" => "pt",
+        "russian" => "ru",
+        "spanish" => "es",
+        "turkish" => "tr",
+        _ => "en",
+    }
+}
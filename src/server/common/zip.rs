@@ -0,0 +1,91 @@
+/// A minimal ZIP archive writer. Entries are stored without compression,
+/// which is plenty for already-compressed payloads such as images and audio.
+#[derive(Debug, Default)]
+pub struct ZipWriter {
+    buffer: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.buffer.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name = name.as_bytes();
+
+        // Local file header.
+        self.buffer.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.buffer.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.buffer.extend_from_slice(&0x0800u16.to_le_bytes()); // UTF-8 names
+        self.buffer.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.buffer.extend_from_slice(&0u16.to_le_bytes()); // mod time
+        self.buffer.extend_from_slice(&0x0021u16.to_le_bytes()); // mod date, 1980-01-01
+        self.buffer.extend_from_slice(&crc.to_le_bytes());
+        self.buffer.extend_from_slice(&size.to_le_bytes());
+        self.buffer.extend_from_slice(&size.to_le_bytes());
+        self.buffer.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.buffer.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        self.buffer.extend_from_slice(name);
+        self.buffer.extend_from_slice(data);
+
+        // Matching central directory record.
+        let cd = &mut self.central_directory;
+        cd.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        cd.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        cd.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        cd.extend_from_slice(&0x0800u16.to_le_bytes());
+        cd.extend_from_slice(&0u16.to_le_bytes());
+        cd.extend_from_slice(&0u16.to_le_bytes());
+        cd.extend_from_slice(&0x0021u16.to_le_bytes());
+        cd.extend_from_slice(&crc.to_le_bytes());
+        cd.extend_from_slice(&size.to_le_bytes());
+        cd.extend_from_slice(&size.to_le_bytes());
+        cd.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        cd.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        cd.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        cd.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        cd.extend_from_slice(&offset.to_le_bytes());
+        cd.extend_from_slice(name);
+
+        self.entries += 1;
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        let offset = self.buffer.len() as u32;
+        let size = self.central_directory.len() as u32;
+        self.buffer.extend_from_slice(&self.central_directory);
+
+        // End of central directory record.
+        self.buffer.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.buffer.extend_from_slice(&0u16.to_le_bytes());
+        self.buffer.extend_from_slice(&0u16.to_le_bytes());
+        self.buffer.extend_from_slice(&self.entries.to_le_bytes());
+        self.buffer.extend_from_slice(&self.entries.to_le_bytes());
+        self.buffer.extend_from_slice(&size.to_le_bytes());
+        self.buffer.extend_from_slice(&offset.to_le_bytes());
+        self.buffer.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.buffer
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
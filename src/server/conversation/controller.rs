@@ -19,14 +19,21 @@ use crate::server::conversation::response::ConversationResponse;
 use crate::server::conversation::response::ConversationsListResponse;
 use crate::server::conversation::response::MessageResponse;
 use crate::server::conversation::response::MessagesListResponse;
+use crate::server::entitlement::controller::check_model;
 use bson::oid::ObjectId;
 use chrono::prelude::*;
 use futures_util::TryStreamExt;
 use std::env;
 #[cfg(feature = "server")]
 use {
-    crate::ai::get_ai, crate::db::get_client, gems::chat::ChatBuilder, gems::messages::Content,
-    gems::messages::Message as GMessage, gems::models::Model, gems::traits::CTrait,
+    crate::ai::{chat_model, generate, get_ai},
+    crate::db::get_client,
+    crate::server::entitlement::controller::check_chat_quota,
+    gems::chat::ChatBuilder,
+    gems::messages::Content,
+    gems::messages::Message as GMessage,
+    gems::models::Model,
+    gems::traits::CTrait,
 };
 
 #[server]
@@ -144,13 +151,16 @@ pub async fn send_query_to_gemini(req: SendQueryRequest) -> Result<MessageRespon
     let book_collection = db.collection::<Book>("books");
     let chapters_collection = db.collection::<Chapter>("chapters");
 
+    check_model(&user, &req.model)?;
+    check_chat_quota(&db, &user).await?;
+
     let client = get_ai(req.model.to_string()).await.lock().await;
 
     let book_id =
         ObjectId::parse_str(&req.book).map_err(|_| ServerFnError::new("Invalid book ID"))?;
 
     let book = book_collection
-        .find_one(doc! { "_id": book_id, "user": user.id })
+        .find_one(doc! { "_id": book_id, "user": user.id, "deletedAt": null })
         .await?
         .ok_or(ServerFnError::new("Book not found"))?;
 
@@ -202,16 +212,14 @@ pub async fn send_query_to_gemini(req: SendQueryRequest) -> Result<MessageRespon
     );
 
     let parameters = ChatBuilder::default()
-        .model(Model::Flash20)
+        .model(chat_model(&req.model)?)
         .messages(vec![GMessage::User {
             content: Content::Text(system_prompt.to_string()),
             name: None,
         }])
         .build()?;
 
-    let content = client
-        .chat()
-        .generate(parameters)
+    let content = generate(&client, Some(user.id), "chat", parameters)
         .await
         .map_err(ServerFnError::new)?
         .trim_start_matches("```html")
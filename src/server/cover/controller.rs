@@ -0,0 +1,348 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use dioxus_logger::tracing;
+
+use crate::server::auth::controller::auth;
+use crate::server::book::model::Book;
+use crate::server::book::request::BookRequest;
+use crate::server::common::response::SuccessResponse;
+use crate::server::cover::model::CoverCredit;
+use crate::server::cover::model::CoverSource;
+use crate::server::cover::request::ChooseCoverRequest;
+use crate::server::cover::request::UploadCoverRequest;
+use crate::server::cover::response::CoverCandidate;
+use crate::server::cover::response::CoverResponse;
+
+#[cfg(feature = "server")]
+use {
+    crate::cover::{
+        candidate_credit, get_ai_cover_provider, get_cover_providers, typographic_cover,
+        CoverProvider, CoverQuery,
+    },
+    crate::db::get_client,
+    crate::server::entitlement::controller::check_image_quota,
+    crate::storage,
+    crate::unsplash,
+    base64::engine::general_purpose::STANDARD,
+    base64::Engine,
+    futures_util::future::join_all,
+};
+
+/// Candidates each provider is asked for.
+const CANDIDATES_PER_PROVIDER: usize = 4;
+const MAX_UPLOAD_BYTES: usize = 4 * 1024 * 1024;
+
+/// Storage prefix holding every cover image of a book.
+pub fn cover_prefix(book_id: ObjectId) -> String {
+    format!("covers/{}", book_id.to_hex())
+}
+
+/// Candidates are rendered here and thrown away on the next search.
+fn candidate_prefix(book_id: ObjectId) -> String {
+    format!("{}/candidates", cover_prefix(book_id))
+}
+
+#[cfg(feature = "server")]
+fn cover_query(book: &Book, count: usize) -> CoverQuery {
+    CoverQuery {
+        user: book.user,
+        title: book.title.clone(),
+        subtitle: book
+            .subtitle
+            .clone()
+            .filter(|subtitle| !subtitle.is_empty()),
+        topic: book
+            .main_topic
+            .clone()
+            .filter(|topic| !topic.is_empty())
+            .unwrap_or_else(|| book.title.clone()),
+        prefix: candidate_prefix(book.id),
+        count,
+    }
+}
+
+/// Gives a new book a cover: an Unsplash photo on its topic when one is
+/// found, otherwise a typographic cover rendered from the title.
+#[cfg(feature = "server")]
+pub(crate) async fn assign_default_cover(book: &mut Book) {
+    use rand::seq::SliceRandom;
+
+    if let Ok(access_key) = std::env::var("UNSPLASH_API_KEY") {
+        let query = cover_query(book, 10);
+        match unsplash::search_photos(&access_key, &query.topic, query.count).await {
+            Ok(photos) => {
+                // Picked before awaiting: the thread's RNG can't be held
+                // across an await in a `Send` future.
+                let photo = photos.choose(&mut rand::thread_rng()).cloned();
+                if let Some(photo) = photo {
+                    match store_unsplash_photo(
+                        &access_key,
+                        book.id,
+                        &photo.image_url,
+                        &photo.credit,
+                    )
+                    .await
+                    {
+                        Ok(url) => {
+                            book.cover = Some(url);
+                            book.cover_credit = Some(photo.credit.clone());
+                            return;
+                        }
+                        Err(err) => {
+                            tracing::warn!("caching cover for book {} failed: {}", book.id, err)
+                        }
+                    }
+                }
+            }
+            Err(err) => tracing::warn!("no Unsplash cover for book {}: {}", book.id, err),
+        }
+    }
+
+    let svg = typographic_cover(&book.title, book.subtitle.as_deref(), 0);
+    let key = format!("{}/{}.svg", cover_prefix(book.id), storage::random_name());
+    match storage::put(&key, svg.as_bytes()).await {
+        Ok(url) => book.cover = Some(url),
+        Err(err) => tracing::error!("storing cover for book {} failed: {}", book.id, err),
+    }
+}
+
+/// Downloads an Unsplash photo into the book's covers and reports the
+/// download to Unsplash.
+#[cfg(feature = "server")]
+async fn store_unsplash_photo(
+    access_key: &str,
+    book_id: ObjectId,
+    image_url: &str,
+    credit: &CoverCredit,
+) -> anyhow::Result<String> {
+    let bytes = unsplash::download_image(image_url).await?;
+    let extension = storage::image_extension(&bytes).unwrap_or("jpg");
+    let key = format!(
+        "{}/{}.{}",
+        cover_prefix(book_id),
+        storage::random_name(),
+        extension
+    );
+    let url = storage::put(&key, &bytes).await?;
+    if let Err(err) = unsplash::track_download(access_key, credit).await {
+        tracing::warn!("tracking Unsplash download failed: {}", err);
+    }
+    Ok(url)
+}
+
+/// Gives a copy of `book` (a translated edition) its own copy of the cover
+/// file, so either book can change or drop its cover independently.
+#[cfg(feature = "server")]
+pub(crate) async fn copy_cover(book: &Book, target_id: ObjectId) -> Option<String> {
+    let url = book.cover.clone()?;
+    let Some(key) = storage::key_from_url(&url) else {
+        return Some(url);
+    };
+    let name = key.rsplit('/').next().unwrap_or_default();
+    let bytes = storage::get(key).await.ok()?;
+    storage::put(&format!("{}/{}", cover_prefix(target_id), name), &bytes)
+        .await
+        .ok()
+}
+
+/// Points the book at its new cover and drops the file of the old one.
+#[cfg(feature = "server")]
+async fn replace_cover(
+    db: &mongodb::Database,
+    book: &Book,
+    url: &str,
+    credit: Option<&CoverCredit>,
+) -> Result<(), ServerFnError> {
+    db.collection::<Book>("books")
+        .update_one(
+            doc! { "_id": book.id },
+            doc! {
+                "$set": {
+                    "cover": url,
+                    "coverCredit": bson::to_bson(&credit)?,
+                    "updatedAt": Utc::now(),
+                }
+            },
+        )
+        .await?;
+
+    if let Some(key) = book.cover.as_deref().and_then(storage::key_from_url) {
+        if key.starts_with(&format!("{}/", cover_prefix(book.id))) {
+            let _ = storage::delete(key).await;
+        }
+    }
+    let _ = storage::delete(&candidate_prefix(book.id)).await;
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+async fn load_book(
+    db: &mongodb::Database,
+    user_id: ObjectId,
+    book_id: &str,
+) -> Result<Book, ServerFnError> {
+    let book_id =
+        ObjectId::parse_str(book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    db.collection::<Book>("books")
+        .find_one(doc! { "_id": book_id, "user": user_id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))
+}
+
+/// Asks every cover provider for candidates. A provider that fails is
+/// skipped; the typographic one always answers.
+#[server]
+pub async fn get_cover_candidates(
+    req: BookRequest,
+) -> Result<SuccessResponse<Vec<CoverCandidate>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book = load_book(&db, user.id, &req.book_id).await?;
+
+    storage::delete(&candidate_prefix(book.id))
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+
+    let query = cover_query(&book, CANDIDATES_PER_PROVIDER);
+    let providers = get_cover_providers();
+    let results = join_all(providers.iter().map(|provider| provider.covers(&query))).await;
+
+    let mut candidates = Vec::new();
+    for (provider, result) in providers.iter().zip(results) {
+        match result {
+            Ok(found) => candidates.extend(found),
+            Err(err) => tracing::warn!(
+                "{} covers for book {} failed: {}",
+                provider.source().label(),
+                book.id,
+                err
+            ),
+        }
+    }
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: candidates,
+    })
+}
+
+/// Paints cover art with the image model. Candidates from the last search
+/// are kept, so these are offered alongside them.
+#[server]
+pub async fn paint_covers(
+    req: BookRequest,
+) -> Result<SuccessResponse<Vec<CoverCandidate>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book = load_book(&db, user.id, &req.book_id).await?;
+    check_image_quota(&db, &user).await?;
+
+    let query = cover_query(&book, CANDIDATES_PER_PROVIDER);
+    let candidates = get_ai_cover_provider()
+        .covers(&query)
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: candidates,
+    })
+}
+
+/// Makes one of the offered candidates the book's cover. The image is moved
+/// out of the scratch area so the next search doesn't delete it.
+#[server]
+pub async fn choose_cover(
+    req: ChooseCoverRequest,
+) -> Result<SuccessResponse<CoverResponse>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book = load_book(&db, user.id, &req.book_id).await?;
+
+    let key = storage::key_from_url(&req.url)
+        .filter(|key| key.starts_with(&format!("{}/", candidate_prefix(book.id))))
+        .ok_or(ServerFnError::new("Unknown cover"))?;
+    let name = key.rsplit('/').next().unwrap_or_default();
+    let bytes = storage::get(key)
+        .await
+        .map_err(|_| ServerFnError::new("Cover candidate has expired"))?;
+    let credit = candidate_credit(key).await;
+
+    let url = storage::put(&format!("{}/{}", cover_prefix(book.id), name), &bytes)
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+    if let (Some(credit), Ok(access_key)) = (&credit, std::env::var("UNSPLASH_API_KEY")) {
+        if let Err(err) = unsplash::track_download(&access_key, credit).await {
+            tracing::warn!("tracking Unsplash download failed: {}", err);
+        }
+    }
+
+    replace_cover(&db, &book, &url, credit.as_ref()).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: CoverResponse { url, credit },
+    })
+}
+
+/// Stores a PNG, JPEG or WebP file from the user as the book's cover.
+#[server]
+pub async fn upload_cover(
+    req: UploadCoverRequest,
+) -> Result<SuccessResponse<CoverResponse>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book = load_book(&db, user.id, &req.book_id).await?;
+
+    // Four base64 characters per three bytes; check before decoding anything.
+    if req.data.len() / 4 * 3 > MAX_UPLOAD_BYTES {
+        return Err(ServerFnError::new("Cover images are limited to 4 MB"));
+    }
+    let bytes = STANDARD
+        .decode(&req.data)
+        .map_err(|_| ServerFnError::new("Invalid file data"))?;
+    let extension = storage::image_extension(&bytes)
+        .ok_or(ServerFnError::new("Upload a PNG, JPEG or WebP image"))?;
+
+    let key = format!(
+        "{}/{}.{}",
+        cover_prefix(book.id),
+        storage::random_name(),
+        extension
+    );
+    let url = storage::put(&key, &bytes)
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+
+    replace_cover(&db, &book, &url, None).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: CoverResponse { url, credit: None },
+    })
+}
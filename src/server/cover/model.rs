@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// Where a cover image came from.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum CoverSource {
+    #[default]
+    Unsplash,
+    /// Rendered locally from the title, subtitle and a color palette.
+    Typographic,
+    /// Painted by an image model.
+    Ai,
+    Upload,
+}
+
+impl CoverSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            CoverSource::Unsplash => "Unsplash",
+            CoverSource::Typographic => "Typographic",
+            CoverSource::Ai => "AI",
+            CoverSource::Upload => "Upload",
+        }
+    }
+}
+
+/// Attribution Unsplash asks for wherever one of its photos is shown.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct CoverCredit {
+    pub photographer: String,
+    pub profile_url: String,
+    /// The photo's page on Unsplash.
+    pub photo_url: String,
+    /// Pinged when the photo is chosen, as the API guidelines require.
+    pub download_location: String,
+}
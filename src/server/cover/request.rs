@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChooseCoverRequest {
+    pub token: String,
+    pub book_id: String,
+    /// One of the URLs returned by `get_cover_candidates`.
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UploadCoverRequest {
+    pub token: String,
+    pub book_id: String,
+    /// The image file, base64-encoded.
+    pub data: String,
+}
@@ -0,0 +1,17 @@
+use crate::server::cover::model::CoverCredit;
+use crate::server::cover::model::CoverSource;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CoverCandidate {
+    pub source: CoverSource,
+    pub url: String,
+    #[serde(default)]
+    pub credit: Option<CoverCredit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CoverResponse {
+    pub url: String,
+    pub credit: Option<CoverCredit>,
+}
@@ -0,0 +1,654 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use bson::Document;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use dioxus_logger::tracing;
+use futures_util::TryStreamExt;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::server::auth::controller::auth;
+use crate::server::backmatter::model::BackMatter;
+use crate::server::backmatter::model::GlossaryEntry;
+use crate::server::book::model::Book;
+use crate::server::book::model::Chapter;
+use crate::server::book::model::FrontMatterEntry;
+use crate::server::book::model::TranslationProgress;
+use crate::server::book::model::TranslationStatus;
+use crate::server::book::request::BookRequest;
+use crate::server::common::response::SuccessResponse;
+use crate::server::edition::request::TranslateBookRequest;
+use crate::server::edition::response::Edition;
+use crate::server::entitlement::controller::check_model;
+
+#[cfg(feature = "server")]
+use {
+    crate::ai::{chat_model, generate, get_ai},
+    crate::db::get_client,
+    crate::server::book::controller::load_outline,
+    crate::server::cover::controller::copy_cover,
+    crate::server::entitlement::controller::{check_book_quota, record_book_creation},
+    gems::chat::ChatBuilder,
+    gems::messages::Content,
+    gems::messages::Message,
+    gems::models::Model,
+    gems::traits::CTrait,
+};
+
+const TRANSLATION_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TranslatedTerm {
+    term: String,
+    translation: String,
+    definition: String,
+}
+
+/// The language a book is written in: explicit on editions, otherwise
+/// whatever its chapters were generated in.
+fn book_language(book: &Book, chapters: &[Chapter]) -> String {
+    book.language
+        .clone()
+        .or_else(|| chapters.first().map(|chapter| chapter.language.clone()))
+        .filter(|language| !language.trim().is_empty())
+        .unwrap_or_else(|| "English".to_string())
+}
+
+/// Opening and closing tag names in document order; a faithful translation
+/// leaves this sequence untouched.
+fn tag_sequence(html: &str) -> Vec<String> {
+    let tags = Regex::new(r"<\s*(/?)\s*([a-zA-Z][a-zA-Z0-9]*)").unwrap();
+    tags.captures_iter(html)
+        .map(|captures| format!("{}{}", &captures[1], captures[2].to_lowercase()))
+        .collect()
+}
+
+fn strip_fences(raw: &str) -> String {
+    raw.trim()
+        .trim_start_matches("```html")
+        .trim_start_matches("```markdown")
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim()
+        .to_string()
+}
+
+fn glossary_instructions(glossary: &[(String, String)]) -> String {
+    if glossary.is_empty() {
+        return String::new();
+    }
+    format!(
+        "Always translate these glossary terms exactly as given:\n{}",
+        glossary
+            .iter()
+            .map(|(term, translation)| format!("- {} => {}", term, translation))
+            .collect::<Vec<String>>()
+            .join("\n")
+    )
+}
+
+#[cfg(feature = "server")]
+async fn complete(
+    model: &str,
+    user: ObjectId,
+    template: &str,
+    prompt: String,
+) -> Result<String, ServerFnError> {
+    let mut client = get_ai(model.to_string()).await.lock().await;
+    let parameters = ChatBuilder::default()
+        .model(chat_model(model)?)
+        .messages(vec![Message::User {
+            content: Content::Text(prompt),
+            name: None,
+        }])
+        .build()?;
+
+    generate(&client, Some(user), template, parameters)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[cfg(feature = "server")]
+async fn translate_text(
+    model: &str,
+    user: ObjectId,
+    text: &str,
+    from: &str,
+    to: &str,
+    glossary: &[(String, String)],
+) -> Result<String, ServerFnError> {
+    if text.trim().is_empty() {
+        return Ok(String::new());
+    }
+    let prompt = format!(
+        "
+        **System Prompt (SP):** You are a professional literary translator.
+
+        **Prompt (P):** Translate the text below from {from} to {to}. Keep any Markdown syntax, links and code exactly as they are and translate only the prose.
+        {glossary}
+
+        **Text:**
+        {text}
+
+        **Expected Format (EF):** Only the translated text, with no commentary.
+
+        **Roleplay (RP):** As a translator, stay faithful to the meaning and tone of the original.
+        ",
+        glossary = glossary_instructions(glossary),
+    );
+    Ok(strip_fences(
+        &complete(model, user, "translation", prompt).await?,
+    ))
+}
+
+/// Translates the text nodes of an HTML fragment, retrying once when the
+/// model alters the markup.
+#[cfg(feature = "server")]
+async fn translate_html(
+    model: &str,
+    user: ObjectId,
+    html: &str,
+    from: &str,
+    to: &str,
+    glossary: &[(String, String)],
+) -> Result<String, ServerFnError> {
+    if html.trim().is_empty() {
+        return Ok(String::new());
+    }
+    let expected = tag_sequence(html);
+    let html_prompt = format!(
+        "
+        **System Prompt (SP):** You are a professional translator working on HTML book chapters.
+
+        **Prompt (P):** Translate the HTML below from {from} to {to}. Translate only the human-readable text and the alt and title attributes. Keep every tag, every other attribute, every URL and the exact order and nesting of elements unchanged. Do not add or remove elements.
+        {glossary}
+
+        **HTML:**
+        {html}
+
+        **Expected Format (EF):** Only the translated HTML fragment, with no commentary or code fences.
+
+        **Roleplay (RP):** As a translator, stay faithful to the meaning and tone of the original.
+        ",
+        glossary = glossary_instructions(glossary),
+    );
+
+    let mut prompt = html_prompt.clone();
+    for _ in 0..TRANSLATION_ATTEMPTS {
+        let translated = strip_fences(&complete(model, user, "translation_html", prompt).await?);
+        if tag_sequence(&translated) == expected {
+            return Ok(translated);
+        }
+        prompt = format!(
+            "{}\n\nYour previous answer changed the HTML structure. Return exactly the same tags in the same order.",
+            html_prompt
+        );
+    }
+    Err(ServerFnError::new(
+        "Translation did not preserve the chapter's HTML structure",
+    ))
+}
+
+#[cfg(feature = "server")]
+async fn translate_glossary(
+    model: &str,
+    user: ObjectId,
+    glossary: &[GlossaryEntry],
+    from: &str,
+    to: &str,
+) -> Result<Vec<TranslatedTerm>, ServerFnError> {
+    if glossary.is_empty() {
+        return Ok(Vec::new());
+    }
+    let entries = serde_json::to_string(
+        &glossary
+            .iter()
+            .map(|entry| serde_json::json!({ "term": entry.term, "definition": entry.definition }))
+            .collect::<Vec<serde_json::Value>>(),
+    )?;
+    let glossary_prompt = format!(
+        "
+        **System Prompt (SP):** You are a terminologist localizing a book glossary.
+
+        **Prompt (P):** Translate each glossary term and its definition from {from} to {to}. Use the established {to} term where one exists.
+
+        **Glossary:**
+        {entries}
+
+        **Expected Format (EF):** Respond with a single JSON array and nothing else, one object per input entry and in the same order:
+        [ {{ \"term\": string (the original term), \"translation\": string, \"definition\": string (translated) }} ]
+
+        **Roleplay (RP):** As a terminologist, keep terms short and consistent.
+        "
+    );
+
+    let mut prompt = glossary_prompt.clone();
+    let mut error = String::new();
+    for _ in 0..TRANSLATION_ATTEMPTS {
+        let raw = complete(model, user, "glossary_translation", prompt.clone()).await?;
+        match serde_json::from_str::<Vec<TranslatedTerm>>(&strip_fences(&raw)) {
+            Ok(terms) if terms.len() == glossary.len() => return Ok(terms),
+            Ok(terms) => {
+                error = format!("expected {} entries, got {}", glossary.len(), terms.len())
+            }
+            Err(err) => error = err.to_string(),
+        }
+        prompt = format!(
+            "{}\n\nYour previous answer was rejected: {}. Return only valid JSON matching the format.",
+            glossary_prompt, error
+        );
+    }
+    Err(ServerFnError::new(format!(
+        "Invalid glossary translation from model: {}",
+        error
+    )))
+}
+
+/// Fills in a freshly created edition chapter by chapter, recording
+/// progress on the edition so the reader can show it.
+#[cfg(feature = "server")]
+async fn translate_edition(
+    db: &mongodb::Database,
+    model: &str,
+    source: &Book,
+    source_chapters: &[Chapter],
+    edition: &Book,
+    chapter_ids: &HashMap<ObjectId, ObjectId>,
+    from: &str,
+) -> Result<(), ServerFnError> {
+    let book_collection = db.collection::<Book>("books");
+    let chapter_collection = db.collection::<Chapter>("chapters");
+    let back_matter_collection = db.collection::<BackMatter>("back_matter");
+    let to = edition.language.clone().unwrap_or_default();
+    let user = source.user;
+
+    // Settle the glossary first so every chapter uses the same terms.
+    let back_matter = back_matter_collection
+        .find_one(doc! { "book_id": source.id })
+        .await?;
+    let translated_terms = match &back_matter {
+        Some(back_matter) => {
+            translate_glossary(model, user, &back_matter.glossary, from, &to).await?
+        }
+        None => Vec::new(),
+    };
+    let glossary = translated_terms
+        .iter()
+        .map(|term| (term.term.clone(), term.translation.clone()))
+        .collect::<Vec<(String, String)>>();
+
+    let title = translate_text(model, user, &source.title, from, &to, &glossary).await?;
+    let subtitle = match &source.subtitle {
+        Some(subtitle) => Some(translate_text(model, user, subtitle, from, &to, &glossary).await?),
+        None => None,
+    };
+    let mut front_matter = Vec::new();
+    for entry in &source.front_matter {
+        front_matter.push(FrontMatterEntry {
+            kind: entry.kind,
+            text: translate_text(model, user, &entry.text, from, &to, &glossary).await?,
+        });
+    }
+    book_collection
+        .update_one(
+            doc! { "_id": edition.id },
+            doc! {
+                "$set": {
+                    "title": &title,
+                    "subtitle": subtitle,
+                    "frontMatter": bson::to_bson(&front_matter)?,
+                    "updatedAt": Utc::now(),
+                }
+            },
+        )
+        .await?;
+
+    for chapter in source_chapters {
+        let Some(edition_chapter_id) = chapter_ids.get(&chapter.id) else {
+            continue;
+        };
+        let chapter_title =
+            translate_text(model, user, &chapter.title, from, &to, &glossary).await?;
+        let mut html = translate_html(model, user, &chapter.html, from, &to, &glossary).await?;
+        let markdown = translate_text(model, user, &chapter.markdown, from, &to, &glossary).await?;
+
+        // Index links point at chapter anchors, which carry chapter ids.
+        for (source_id, edition_id) in chapter_ids {
+            html = html.replace(&source_id.to_hex(), &edition_id.to_hex());
+        }
+
+        chapter_collection
+            .update_one(
+                doc! { "_id": edition_chapter_id },
+                doc! {
+                    "$set": {
+                        "title": chapter_title,
+                        "html": html,
+                        "markdown": markdown,
+                        "completed": chapter.completed,
+                        "updatedAt": Utc::now(),
+                    }
+                },
+            )
+            .await?;
+        book_collection
+            .update_one(
+                doc! { "_id": edition.id },
+                doc! { "$inc": { "translation.translated": 1 } },
+            )
+            .await?;
+    }
+
+    if let Some(back_matter) = back_matter {
+        let now = Utc::now();
+        back_matter_collection
+            .insert_one(BackMatter {
+                id: ObjectId::new(),
+                user: back_matter.user,
+                book_id: edition.id,
+                glossary: translated_terms
+                    .into_iter()
+                    .map(|term| GlossaryEntry {
+                        term: term.translation,
+                        definition: term.definition,
+                    })
+                    .collect(),
+                index: back_matter
+                    .index
+                    .into_iter()
+                    .map(|mut entry| {
+                        entry.chapter_ids = entry
+                            .chapter_ids
+                            .iter()
+                            .filter_map(|id| chapter_ids.get(id).copied())
+                            .collect();
+                        entry
+                    })
+                    .collect(),
+                bibliography: back_matter.bibliography,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+    }
+
+    book_collection
+        .update_one(
+            doc! { "_id": edition.id },
+            doc! {
+                "$set": {
+                    "translation.status": bson::to_bson(&TranslationStatus::Completed)?,
+                    "completed": source.completed,
+                    "updatedAt": Utc::now(),
+                }
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Starts translating a book into another language. The new edition is
+/// returned right away and filled in by a background task.
+#[server]
+pub async fn translate_book(
+    req: TranslateBookRequest,
+) -> Result<SuccessResponse<Book>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    check_model(&user, &req.model)?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    check_book_quota(&db, &user).await?;
+
+    let language = req.language.trim().to_string();
+    if language.is_empty() {
+        return Err(ServerFnError::new("Choose a language to translate into"));
+    }
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let (source, source_chapters) = load_outline(&db, user.id, book_id).await?;
+    if source
+        .translation
+        .as_ref()
+        .is_some_and(|progress| progress.status != TranslationStatus::Completed)
+    {
+        return Err(ServerFnError::new(
+            "This edition is not fully translated yet",
+        ));
+    }
+    let from = book_language(&source, &source_chapters);
+    let original_id = source.edition_of.unwrap_or(source.id);
+
+    for edition in book_collection
+        .find(doc! {
+            "user": user.id,
+            "deletedAt": null,
+            "$or": [ { "_id": original_id }, { "editionOf": original_id } ],
+        })
+        .await?
+        .try_collect::<Vec<Book>>()
+        .await?
+    {
+        let edition_language = match edition.language.clone() {
+            Some(language) => language,
+            None if edition.id == source.id => from.clone(),
+            None => continue,
+        };
+        if edition_language.eq_ignore_ascii_case(&language) {
+            return Err(ServerFnError::new(format!(
+                "An edition in {} already exists",
+                language
+            )));
+        }
+    }
+
+    let now = Utc::now();
+    let edition_id = ObjectId::new();
+    let edition = Book {
+        id: edition_id,
+        edition_of: Some(original_id),
+        cover: copy_cover(&source, edition_id).await,
+        language: Some(language.clone()),
+        translation: Some(TranslationProgress {
+            status: TranslationStatus::Running,
+            translated: 0,
+            total: source_chapters.len() as u32,
+            error: None,
+        }),
+        completed: false,
+        published: false,
+        slug: None,
+        share_token: None,
+        share_expires_at: None,
+        series: None,
+        created_at: now,
+        updated_at: now,
+        ..source.clone()
+    };
+    book_collection.insert_one(&edition).await?;
+    record_book_creation(&db, user.id, edition.id).await?;
+
+    let mut chapter_ids = HashMap::new();
+    let edition_chapters = source_chapters
+        .iter()
+        .map(|chapter| {
+            let id = ObjectId::new();
+            chapter_ids.insert(chapter.id, id);
+            Chapter {
+                id,
+                book_id: edition.id,
+                markdown: String::new(),
+                html: String::new(),
+                language: language.clone(),
+                completed: false,
+                created_at: now,
+                updated_at: now,
+                ..chapter.clone()
+            }
+        })
+        .collect::<Vec<Chapter>>();
+    if !edition_chapters.is_empty() {
+        db.collection::<Chapter>("chapters")
+            .insert_many(&edition_chapters)
+            .await?;
+    }
+
+    let job_edition = edition.clone();
+    let model = req.model;
+    tokio::spawn(async move {
+        if let Err(err) = translate_edition(
+            &db,
+            &model,
+            &source,
+            &source_chapters,
+            &job_edition,
+            &chapter_ids,
+            &from,
+        )
+        .await
+        {
+            tracing::error!("translating book {} failed: {}", job_edition.id, err);
+            let _ = db
+                .collection::<Book>("books")
+                .update_one(
+                    doc! { "_id": job_edition.id },
+                    doc! {
+                        "$set": {
+                            "translation.status": "Failed",
+                            "translation.error": err.to_string(),
+                            "updatedAt": Utc::now(),
+                        }
+                    },
+                )
+                .await;
+        }
+    });
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: edition,
+    })
+}
+
+/// The original book and all of its translated editions.
+#[server]
+pub async fn get_book_editions(
+    req: BookRequest,
+) -> Result<SuccessResponse<Vec<Edition>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let book = book_collection
+        .find_one(doc! { "_id": book_id, "user": user.id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+    let original_id = book.edition_of.unwrap_or(book.id);
+
+    let books = book_collection
+        .find(doc! {
+            "user": user.id,
+            "deletedAt": null,
+            "$or": [ { "_id": original_id }, { "editionOf": original_id } ],
+        })
+        .sort(doc! { "createdAt": 1 })
+        .await?
+        .try_collect::<Vec<Book>>()
+        .await?;
+    let book_ids = books.iter().map(|book| book.id).collect::<Vec<ObjectId>>();
+
+    let mut chapters: HashMap<ObjectId, Vec<(u32, ObjectId, String)>> = HashMap::new();
+    let mut cursor = db
+        .collection::<Document>("chapters")
+        .find(doc! { "book_id": { "$in": &book_ids } })
+        .projection(doc! { "book_id": 1, "position": 1, "language": 1 })
+        .await?;
+    while let Some(chapter) = cursor.try_next().await? {
+        let (Ok(id), Ok(book_id)) = (chapter.get_object_id("_id"), chapter.get_object_id("book_id"))
+        else {
+            continue;
+        };
+        let position = chapter
+            .get("position")
+            .and_then(|position| position.as_i64().or(position.as_i32().map(i64::from)))
+            .unwrap_or_default() as u32;
+        let language = chapter.get_str("language").unwrap_or_default().to_string();
+        chapters
+            .entry(book_id)
+            .or_default()
+            .push((position, id, language));
+    }
+
+    let editions = books
+        .into_iter()
+        .map(|book| {
+            let mut book_chapters = chapters.remove(&book.id).unwrap_or_default();
+            book_chapters.sort_by_key(|(position, _, _)| *position);
+            let language = book
+                .language
+                .clone()
+                .or_else(|| book_chapters.first().map(|(_, _, language)| language.clone()))
+                .filter(|language| !language.trim().is_empty())
+                .unwrap_or_else(|| "English".to_string());
+            Edition {
+                book_id: book.id.to_hex(),
+                title: book.title,
+                language,
+                is_original: book.id == original_id,
+                translation: book.translation,
+                chapter_ids: book_chapters
+                    .into_iter()
+                    .map(|(_, id, _)| id.to_hex())
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: editions,
+    })
+}
+
+/// Marks translations that were running when the server stopped as failed,
+/// since their background task is gone.
+#[cfg(feature = "server")]
+pub async fn fail_interrupted_translations() -> Result<u64, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let result = db
+        .collection::<Book>("books")
+        .update_many(
+            doc! { "translation.status": "Running" },
+            doc! {
+                "$set": {
+                    "translation.status": "Failed",
+                    "translation.error": "Interrupted by a server restart",
+                }
+            },
+        )
+        .await?;
+    Ok(result.modified_count)
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TranslateBookRequest {
+    pub token: String,
+    pub book_id: String,
+    /// Target language name, e.g. "French".
+    pub language: String,
+    pub model: String,
+}
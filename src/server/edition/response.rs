@@ -0,0 +1,14 @@
+use crate::server::book::model::TranslationProgress;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Edition {
+    pub book_id: String,
+    pub title: String,
+    pub language: String,
+    pub is_original: bool,
+    pub translation: Option<TranslationProgress>,
+    /// Chapter ids in reading order, so the reader can keep its place when
+    /// switching editions.
+    pub chapter_ids: Vec<String>,
+}
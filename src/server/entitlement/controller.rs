@@ -0,0 +1,218 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+
+use crate::server::auth::controller::auth;
+use crate::server::auth::model::User;
+use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::model::Plan;
+use crate::server::entitlement::response::QuotaResponse;
+use crate::server::export::request::ExportFormat;
+
+#[cfg(feature = "server")]
+use {
+    crate::db::get_client, crate::server::ai_call::model::AiCall,
+    crate::server::entitlement::model::BookCreation,
+    crate::server::subscription::model::Subscription, bson::oid::ObjectId,
+};
+
+/// Ledger templates of calls that paint an image.
+const IMAGE_TEMPLATES: [&str; 2] = ["cover_image", "illustration_image"];
+
+pub fn user_plan(user: &User) -> Plan {
+    Plan::from_role(&user.role)
+}
+
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .unwrap()
+}
+
+fn day_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+        .unwrap()
+}
+
+/// Books created this month, read from the `book_creations` ledger so
+/// purged books and translated editions count too.
+#[cfg(feature = "server")]
+async fn books_this_month(db: &mongodb::Database, user: ObjectId) -> Result<u64, ServerFnError> {
+    Ok(db
+        .collection::<BookCreation>("book_creations")
+        .count_documents(doc! {
+            "user": user,
+            "createdAt": { "$gte": month_start(Utc::now()) },
+        })
+        .await?)
+}
+
+/// Counts a new book against its owner's monthly quota.
+#[cfg(feature = "server")]
+pub(crate) async fn record_book_creation(
+    db: &mongodb::Database,
+    user: ObjectId,
+    book_id: ObjectId,
+) -> Result<(), ServerFnError> {
+    db.collection::<BookCreation>("book_creations")
+        .insert_one(BookCreation {
+            id: ObjectId::new(),
+            user,
+            book_id,
+            created_at: Utc::now(),
+        })
+        .await?;
+    Ok(())
+}
+
+/// Chat answers served today, read from the AI call ledger.
+#[cfg(feature = "server")]
+async fn chat_messages_today(db: &mongodb::Database, user: ObjectId) -> Result<u64, ServerFnError> {
+    Ok(db
+        .collection::<AiCall>("ai_calls")
+        .count_documents(doc! {
+            "user": user,
+            "template": "chat",
+            "outcome": "Success",
+            "createdAt": { "$gte": day_start(Utc::now()) },
+        })
+        .await?)
+}
+
+/// Covers and illustrations painted today, read from the AI call ledger.
+#[cfg(feature = "server")]
+async fn ai_images_today(db: &mongodb::Database, user: ObjectId) -> Result<u64, ServerFnError> {
+    Ok(db
+        .collection::<AiCall>("ai_calls")
+        .count_documents(doc! {
+            "user": user,
+            "template": { "$in": IMAGE_TEMPLATES.to_vec() },
+            "outcome": "Success",
+            "createdAt": { "$gte": day_start(Utc::now()) },
+        })
+        .await?)
+}
+
+pub fn check_model(user: &User, model: &str) -> Result<(), ServerFnError> {
+    let plan = user_plan(user);
+    if plan.limits().models.contains(&model) {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!(
+            "The {} plan doesn't include the {} model",
+            plan.label(),
+            model
+        )))
+    }
+}
+
+pub fn check_chapters(user: &User, chapters: usize) -> Result<(), ServerFnError> {
+    let plan = user_plan(user);
+    let limit = plan.limits().chapters_per_book;
+    if chapters <= limit as usize {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!(
+            "The {} plan allows at most {} chapters per book",
+            plan.label(),
+            limit
+        )))
+    }
+}
+
+pub fn check_export(user: &User, format: ExportFormat) -> Result<(), ServerFnError> {
+    let plan = user_plan(user);
+    if plan.limits().export_formats.contains(&format) {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!(
+            "The {} plan doesn't include {:?} export",
+            plan.label(),
+            format
+        )))
+    }
+}
+
+/// Fails once the user has created this month's allowance of books.
+#[cfg(feature = "server")]
+pub(crate) async fn check_book_quota(
+    db: &mongodb::Database,
+    user: &User,
+) -> Result<(), ServerFnError> {
+    let plan = user_plan(user);
+    let limit = plan.limits().books_per_month;
+    if books_this_month(db, user.id).await? < limit as u64 {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!(
+            "The {} plan allows {} books per month",
+            plan.label(),
+            limit
+        )))
+    }
+}
+
+/// Fails once the user has sent today's allowance of chat messages.
+#[cfg(feature = "server")]
+pub(crate) async fn check_chat_quota(
+    db: &mongodb::Database,
+    user: &User,
+) -> Result<(), ServerFnError> {
+    let plan = user_plan(user);
+    let limit = plan.limits().chat_messages_per_day;
+    if chat_messages_today(db, user.id).await? < limit as u64 {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!(
+            "The {} plan allows {} chat messages per day",
+            plan.label(),
+            limit
+        )))
+    }
+}
+
+/// Fails once the user has painted today's allowance of AI images.
+#[cfg(feature = "server")]
+pub(crate) async fn check_image_quota(
+    db: &mongodb::Database,
+    user: &User,
+) -> Result<(), ServerFnError> {
+    let plan = user_plan(user);
+    let limit = plan.limits().ai_images_per_day;
+    if ai_images_today(db, user.id).await? < limit as u64 {
+        Ok(())
+    } else {
+        Err(ServerFnError::new(format!(
+            "The {} plan allows {} AI images per day",
+            plan.label(),
+            limit
+        )))
+    }
+}
+
+#[server]
+pub async fn get_quota(token: String) -> Result<SuccessResponse<QuotaResponse>, ServerFnError> {
+    let user = auth(token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: QuotaResponse {
+            plan: user_plan(&user),
+            books_this_month: books_this_month(&db, user.id).await? as u32,
+            chat_messages_today: chat_messages_today(&db, user.id).await? as u32,
+            trial_ends_at: db
+                .collection::<Subscription>("subscriptions")
+                .find_one(doc! { "user": user.id, "active": true, "status": "trialing" })
+                .await?
+                .and_then(|subscription| subscription.trial_end),
+        },
+    })
+}
@@ -0,0 +1,112 @@
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::server::export::request::ExportFormat;
+
+/// What a user pays for, stored as `User.role`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum Plan {
+    #[default]
+    Free,
+    Monthly,
+    Yearly,
+}
+
+/// What a plan allows. Books are counted per calendar month, chat messages
+/// and AI images per day, all in UTC.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub books_per_month: u32,
+    pub chapters_per_book: u32,
+    pub models: &'static [&'static str],
+    pub export_formats: &'static [ExportFormat],
+    pub chat_messages_per_day: u32,
+    /// AI-painted covers and illustrations.
+    pub ai_images_per_day: u32,
+}
+
+const FREE_MODELS: &[&str] = &[
+    "gemini-1.5-flash",
+    "gemini-2.0-flash",
+    "gemini-2.0-flash-lite",
+    "gemini-1.5-flash-8b",
+];
+
+const PAID_MODELS: &[&str] = &[
+    "gemini-1.5-flash",
+    "gemini-2.5-pro-preview-03-25",
+    "gemini-2.0-flash",
+    "gemini-2.0-flash-lite",
+    "gemini-1.5-flash-8b",
+    "gemini-1.5-pro",
+    "gemini-2.0-flash-live-001",
+];
+
+const FREE_LIMITS: Limits = Limits {
+    books_per_month: 3,
+    chapters_per_book: 10,
+    models: FREE_MODELS,
+    export_formats: &[ExportFormat::Markdown, ExportFormat::Html],
+    chat_messages_per_day: 20,
+    ai_images_per_day: 5,
+};
+
+const PAID_LIMITS: Limits = Limits {
+    books_per_month: 50,
+    chapters_per_book: 50,
+    models: PAID_MODELS,
+    export_formats: &[ExportFormat::Markdown, ExportFormat::Html],
+    chat_messages_per_day: 500,
+    ai_images_per_day: 100,
+};
+
+impl Plan {
+    pub const PAID: [Plan; 2] = [Plan::Monthly, Plan::Yearly];
+
+    /// Reads `User.role`. Accounts created before plans existed hold
+    /// `"user"`, which like anything unknown means free.
+    pub fn from_role(role: &str) -> Self {
+        match role {
+            "monthly" => Plan::Monthly,
+            "yearly" => Plan::Yearly,
+            _ => Plan::Free,
+        }
+    }
+
+    /// The value written to `User.role`.
+    pub fn role(&self) -> &'static str {
+        match self {
+            Plan::Free => "free",
+            Plan::Monthly => "monthly",
+            Plan::Yearly => "yearly",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Plan::Free => "Free",
+            Plan::Monthly => "Monthly",
+            Plan::Yearly => "Yearly",
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        match self {
+            Plan::Free => FREE_LIMITS,
+            Plan::Monthly | Plan::Yearly => PAID_LIMITS,
+        }
+    }
+}
+
+/// A book someone created, counted against their monthly quota. Purging the
+/// book leaves this behind, so deleting doesn't hand the quota back.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BookCreation {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
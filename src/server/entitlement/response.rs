@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::server::entitlement::model::Plan;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct QuotaResponse {
+    pub plan: Plan,
+    pub books_this_month: u32,
+    pub chat_messages_today: u32,
+    /// Set while the plan is on a free trial.
+    pub trial_ends_at: Option<DateTime<Utc>>,
+}
+
+impl QuotaResponse {
+    pub fn books_remaining(&self) -> u32 {
+        self.plan
+            .limits()
+            .books_per_month
+            .saturating_sub(self.books_this_month)
+    }
+
+    pub fn chat_messages_remaining(&self) -> u32 {
+        self.plan
+            .limits()
+            .chat_messages_per_day
+            .saturating_sub(self.chat_messages_today)
+    }
+}
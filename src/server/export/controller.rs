@@ -0,0 +1,167 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::oid::ObjectId;
+use dioxus::prelude::*;
+
+use crate::server::auth::controller::auth;
+use crate::server::book::model::Book;
+use crate::server::book::model::Chapter;
+use crate::server::book::model::FrontMatterEntry;
+use crate::server::book::model::FrontMatterKind;
+use crate::server::common::html::escape_html;
+use crate::server::common::html::paragraphs_html;
+use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::controller::check_export;
+use crate::server::export::request::ExportBookRequest;
+use crate::server::export::request::ExportFormat;
+use crate::server::export::response::ExportedFile;
+
+#[cfg(feature = "server")]
+use {
+    crate::db::get_client,
+    crate::server::book::controller::{book_slug, load_outline},
+};
+
+/// Front-matter entries split into those printed before and after the
+/// chapters, each in reading order.
+pub fn front_matter_sections(book: &Book) -> (Vec<&FrontMatterEntry>, Vec<&FrontMatterEntry>) {
+    FrontMatterKind::ALL
+        .iter()
+        .filter_map(|kind| book.front_matter(*kind))
+        .partition(|entry| entry.kind.precedes_body())
+}
+
+pub fn front_matter_html(entry: &FrontMatterEntry) -> String {
+    match entry.kind {
+        FrontMatterKind::Dedication => format!(
+            "<div class=\"dedication\">{}</div>",
+            paragraphs_html(&entry.text)
+        ),
+        _ => format!(
+            "<h2>{}</h2>{}",
+            entry.kind.title(),
+            paragraphs_html(&entry.text)
+        ),
+    }
+}
+
+pub fn render_markdown(book: &Book, chapters: &[Chapter]) -> String {
+    let (before, after) = front_matter_sections(book);
+    let front_matter_markdown = |entry: &FrontMatterEntry| match entry.kind {
+        FrontMatterKind::Dedication => format!("\n_{}_\n", entry.text.trim()),
+        _ => format!("\n## {}\n\n{}\n", entry.kind.title(), entry.text.trim()),
+    };
+
+    let mut markdown = format!("# {}\n", book.title);
+    if let Some(subtitle) = &book.subtitle {
+        markdown.push_str(&format!("\n_{}_\n", subtitle));
+    }
+    for entry in before {
+        markdown.push_str(&front_matter_markdown(entry));
+    }
+    for chapter in chapters {
+        // Anchors keep the index's `#chapter-...` links working in renderers
+        // that allow inline HTML.
+        markdown.push_str(&format!(
+            "\n<a id=\"{}\"></a>\n\n## {}\n\n{}\n",
+            chapter.anchor(),
+            chapter.title,
+            chapter.markdown.trim()
+        ));
+    }
+    for entry in after {
+        markdown.push_str(&front_matter_markdown(entry));
+    }
+    markdown
+}
+
+/// A single printable page: the print stylesheet starts every section on a
+/// new sheet so "Save as PDF" yields a book-shaped document.
+pub fn render_html(book: &Book, chapters: &[Chapter]) -> String {
+    let (before, after) = front_matter_sections(book);
+    let title = escape_html(&book.title);
+    let subtitle = book
+        .subtitle
+        .as_deref()
+        .map(|subtitle| format!("<p class=\"subtitle\">{}</p>", escape_html(subtitle)))
+        .unwrap_or_default();
+    let front_matter = |entries: Vec<&FrontMatterEntry>| {
+        entries
+            .into_iter()
+            .map(|entry| {
+                format!(
+                    "<section id=\"{}\">{}</section>",
+                    entry.kind.slug(),
+                    front_matter_html(entry)
+                )
+            })
+            .collect::<String>()
+    };
+    let toc = chapters
+        .iter()
+        .map(|chapter| {
+            format!(
+                "<li><a href=\"#{}\">{}</a></li>",
+                chapter.anchor(),
+                escape_html(&chapter.title)
+            )
+        })
+        .collect::<String>();
+    let sections = chapters
+        .iter()
+        .map(|chapter| {
+            format!(
+                "<section id=\"{}\"><h2>{}</h2>{}</section>",
+                chapter.anchor(),
+                escape_html(&chapter.title),
+                chapter.html
+            )
+        })
+        .collect::<String>();
+    let before = front_matter(before);
+    let after = front_matter(after);
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>.dedication {{ text-align: center; font-style: italic; margin-top: 30vh; }} @media print {{ section, nav {{ break-before: page; }} }}</style>\n</head>\n<body>\n<h1>{title}</h1>{subtitle}\n{before}\n<nav><h2>Contents</h2><ol>{toc}</ol></nav>\n{sections}\n{after}\n</body>\n</html>\n"
+    )
+}
+
+/// Renders the whole book, front and back matter included, as a
+/// downloadable file.
+#[server]
+pub async fn export_book(
+    req: ExportBookRequest,
+) -> Result<SuccessResponse<ExportedFile>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    check_export(&user, req.format)?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let (book, chapters) = load_outline(&db, user.id, book_id).await?;
+
+    let slug = book_slug(&book);
+    let file = match req.format {
+        ExportFormat::Markdown => ExportedFile {
+            filename: format!("{}.md", slug),
+            mime_type: "text/markdown".to_string(),
+            content: render_markdown(&book, &chapters).into_bytes(),
+        },
+        ExportFormat::Html => ExportedFile {
+            filename: format!("{}.html", slug),
+            mime_type: "text/html".to_string(),
+            content: render_html(&book, &chapters).into_bytes(),
+        },
+    };
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: file,
+    })
+}
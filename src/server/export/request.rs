@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportBookRequest {
+    pub token: String,
+    pub book_id: String,
+    pub format: ExportFormat,
+}
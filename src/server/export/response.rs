@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExportedFile {
+    pub filename: String,
+    pub mime_type: String,
+    pub content: Vec<u8>,
+}
@@ -0,0 +1,411 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use dioxus_logger::tracing;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::server::auth::controller::auth;
+use crate::server::book::model::Chapter;
+use crate::server::common::html::escape_html;
+use crate::server::common::html::strip_tags;
+use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::controller::check_model;
+use crate::server::illustration::request::IllustrateChapterRequest;
+use crate::server::illustration::response::IllustrationResponse;
+
+#[cfg(feature = "server")]
+use {
+    crate::ai::{chat_model, generate, get_ai},
+    crate::db::get_client,
+    crate::image_model::{get_image_model, ImageModel},
+    crate::server::book::controller::load_outline_for_chapter,
+    crate::server::entitlement::controller::check_image_quota,
+    crate::storage,
+    gems::chat::ChatBuilder,
+    gems::messages::Content,
+    gems::messages::Message,
+    gems::models::Model,
+    gems::traits::CTrait,
+};
+
+/// Appended to the chapter HTML prompts: models can only invent image URLs,
+/// so they mark where a picture belongs and we paint it later.
+pub const PLACEHOLDER_INSTRUCTIONS: &str = "Do not link to external images. Where an illustration would help the reader, insert a placeholder <figure data-illustrate=\"what the picture should show\"></figure> instead.";
+
+/// Image generation is slow; one request fills at most this many placeholders.
+const MAX_PLACEHOLDERS_PER_RUN: u32 = 4;
+/// Characters of surrounding text the prompt writer gets to see.
+const CONTEXT_CHARS: usize = 1_500;
+const PLAN_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IllustrationPlan {
+    prompt: String,
+    alt: String,
+}
+
+const PLAN_SCHEMA: &str = r#"{ "prompt": string, "alt": string }"#;
+
+/// Storage prefix holding every illustration of a book.
+pub fn illustration_prefix(book_id: ObjectId) -> String {
+    format!("illustrations/{}", book_id.to_hex())
+}
+
+fn placeholder_pattern() -> Regex {
+    Regex::new(
+        r#"(?s)<(?:figure|div|img)\b[^>]*\bdata-illustrate\s*=\s*"([^"]*)"[^>]*>(?:\s*</(?:figure|div)\s*>)?"#,
+    )
+    .unwrap()
+}
+
+fn heading_pattern() -> Regex {
+    Regex::new(r"(?is)<h[1-3]\b[^>]*>(.*?)</h[1-3]\s*>").unwrap()
+}
+
+/// Image placeholders left in a chapter.
+pub fn placeholder_count(html: &str) -> usize {
+    placeholder_pattern().find_iter(html).count()
+}
+
+/// The first placeholder's byte range and the picture it asks for.
+fn next_placeholder(html: &str) -> Option<(std::ops::Range<usize>, String)> {
+    placeholder_pattern()
+        .captures(html)
+        .map(|captures| (captures.get(0).unwrap().range(), captures[1].to_string()))
+}
+
+/// The chapter's `<h1>`–`<h3>` headings in order; a section runs from one
+/// heading to the next.
+pub fn section_headings(html: &str) -> Vec<String> {
+    heading_pattern()
+        .captures_iter(html)
+        .map(|captures| strip_tags(&captures[1]))
+        .collect()
+}
+
+fn last_chars(text: &str, count: usize) -> String {
+    let skip = text.chars().count().saturating_sub(count);
+    text.chars().skip(skip).collect()
+}
+
+fn figure_html(url: &str, alt: &str) -> String {
+    format!(
+        r#"<figure class="illustration"><img src="{}" alt="{}" loading="lazy"/></figure>"#,
+        escape_html(url),
+        escape_html(alt)
+    )
+}
+
+fn parse_illustration_plan(raw: &str) -> Result<IllustrationPlan, String> {
+    let json = raw
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let plan: IllustrationPlan = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if plan.prompt.trim().is_empty() {
+        return Err("prompt is empty".to_string());
+    }
+    if plan.alt.trim().is_empty() {
+        return Err("alt is empty".to_string());
+    }
+    Ok(plan)
+}
+
+/// Asks the text model for an image prompt and alt text for the passage.
+#[cfg(feature = "server")]
+async fn plan_illustration(
+    model: &str,
+    user: ObjectId,
+    book_title: &str,
+    language: &str,
+    context: &str,
+    hint: &str,
+) -> Result<IllustrationPlan, ServerFnError> {
+    let plan_prompt = format!(
+        "
+        **System Prompt (SP):** You are the art director of the book '{book_title}'.
+
+        **Prompt (P):** Plan one illustration for the passage below. The author asked for: '{hint}'. Write a detailed prompt for an image model describing a clear, informative picture with no text or lettering in it, and alt text in {language} of at most 125 characters describing what the picture shows for readers who can't see it.
+
+        **Passage:**
+        {context}
+
+        **Expected Format (EF):** Respond with a single JSON object and nothing else, matching this schema exactly:
+        {schema}
+
+        **Roleplay (RP):** As an art director, favour pictures that explain over pictures that decorate.
+        ",
+        schema = PLAN_SCHEMA,
+    );
+
+    let mut ai_client = get_ai(model.to_string()).await.lock().await;
+    let mut prompt = plan_prompt.clone();
+    let mut error = String::new();
+    for _ in 0..PLAN_ATTEMPTS {
+        let parameters = ChatBuilder::default()
+            .model(chat_model(model)?)
+            .messages(vec![Message::User {
+                content: Content::Text(prompt.clone()),
+                name: None,
+            }])
+            .build()?;
+
+        let raw = generate(&ai_client, Some(user), "illustration_plan", parameters)
+            .await
+            .map_err(ServerFnError::new)?;
+
+        match parse_illustration_plan(&raw) {
+            Ok(plan) => return Ok(plan),
+            Err(err) => error = err,
+        }
+        prompt = format!(
+            "{}\n\nYour previous answer was rejected: {}. Return only valid JSON matching the schema.",
+            plan_prompt, error
+        );
+    }
+    Err(ServerFnError::new(format!(
+        "Invalid illustration plan from model: {}",
+        error
+    )))
+}
+
+/// Plans, paints and stores one illustration, returning its `<figure>`.
+#[cfg(feature = "server")]
+async fn illustrate(
+    image_model: &dyn ImageModel,
+    model: &str,
+    user: ObjectId,
+    book_id: ObjectId,
+    book_title: &str,
+    language: &str,
+    context: &str,
+    hint: &str,
+) -> Result<String, ServerFnError> {
+    let plan = plan_illustration(model, user, book_title, language, context, hint).await?;
+    let image = image_model
+        .generate(user, "illustration_image", &plan.prompt)
+        .await
+        .map_err(|err| ServerFnError::new(format!("Image generation failed: {}", err)))?;
+    let key = format!(
+        "{}/{}.{}",
+        illustration_prefix(book_id),
+        storage::random_name(),
+        image.extension
+    );
+    let url = storage::put(&key, &image.bytes)
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+    Ok(figure_html(&url, plan.alt.trim()))
+}
+
+/// Illustrates one section of a chapter, or fills its image placeholders,
+/// and saves the chapter with the new figures in place.
+#[server]
+pub async fn illustrate_chapter(
+    req: IllustrateChapterRequest,
+) -> Result<SuccessResponse<IllustrationResponse>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    check_model(&user, &req.model)?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    check_image_quota(&db, &user).await?;
+
+    let (book, chapters, index) = load_outline_for_chapter(&db, user.id, &req.chapter_id).await?;
+    let chapter = &chapters[index];
+    if chapter.html.trim().is_empty() {
+        return Err(ServerFnError::new("Generate the chapter content first"));
+    }
+
+    let image_model = get_image_model();
+    let mut html = chapter.html.clone();
+    let mut added = 0;
+
+    match req.section {
+        Some(section) => {
+            let headings = heading_pattern()
+                .captures_iter(&html)
+                .map(|captures| {
+                    let whole = captures.get(0).unwrap();
+                    (whole.start(), whole.end(), strip_tags(&captures[1]))
+                })
+                .collect::<Vec<(usize, usize, String)>>();
+            let (_, end, title) = headings
+                .get(section as usize)
+                .cloned()
+                .ok_or(ServerFnError::new("Section not found"))?;
+            let next = headings
+                .get(section as usize + 1)
+                .map(|heading| heading.0)
+                .unwrap_or(html.len());
+            let context = last_chars(&strip_tags(&html[end..next]), CONTEXT_CHARS);
+
+            let figure = illustrate(
+                image_model.as_ref(),
+                &req.model,
+                user.id,
+                book.id,
+                &book.title,
+                &chapter.language,
+                &context,
+                &title,
+            )
+            .await?;
+            html.insert_str(end, &figure);
+            added = 1;
+        }
+        None => {
+            if placeholder_count(&html) == 0 {
+                return Err(ServerFnError::new("This chapter has no image placeholders"));
+            }
+            while added < MAX_PLACEHOLDERS_PER_RUN {
+                let Some((range, hint)) = next_placeholder(&html) else {
+                    break;
+                };
+                let context = last_chars(&strip_tags(&html[..range.start]), CONTEXT_CHARS);
+
+                match illustrate(
+                    image_model.as_ref(),
+                    &req.model,
+                    user.id,
+                    book.id,
+                    &book.title,
+                    &chapter.language,
+                    &context,
+                    &hint,
+                )
+                .await
+                {
+                    Ok(figure) => {
+                        html.replace_range(range, &figure);
+                        added += 1;
+                    }
+                    // Keep whatever was painted before the failure.
+                    Err(err) if added > 0 => {
+                        tracing::error!("illustrating chapter {} failed: {}", chapter.id, err);
+                        break;
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+    }
+
+    db.collection::<Chapter>("chapters")
+        .update_one(
+            doc! { "_id": chapter.id },
+            doc! { "$set": { "html": &html, "updatedAt": Utc::now() } },
+        )
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: IllustrationResponse {
+            remaining: placeholder_count(&html) as u32,
+            html,
+            added,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_figure_div_and_img_placeholders() {
+        let html = concat!(
+            "<p>Intro</p>",
+            r#"<figure data-illustrate="a crab"></figure>"#,
+            r#"<div class="x" data-illustrate = "a lighthouse">
+            </div>"#,
+            r#"<img data-illustrate="a map"/>"#,
+            r#"<figure class="illustration"><img src="/media/a.png" alt="done"/></figure>"#,
+        );
+        assert_eq!(placeholder_count(html), 3);
+        let (range, hint) = next_placeholder(html).unwrap();
+        assert_eq!(hint, "a crab");
+        assert_eq!(
+            &html[range],
+            r#"<figure data-illustrate="a crab"></figure>"#
+        );
+    }
+
+    #[test]
+    fn replacing_placeholders_one_by_one_drains_them() {
+        let mut html = concat!(
+            r#"<p>One</p><figure data-illustrate="first"></figure>"#,
+            r#"<p>Two</p><img data-illustrate="second">"#,
+        )
+        .to_string();
+        let mut hints = Vec::new();
+        while let Some((range, hint)) = next_placeholder(&html) {
+            html.replace_range(range, &figure_html("/media/x.png", &hint));
+            hints.push(hint);
+        }
+        assert_eq!(hints, vec!["first", "second"]);
+        assert_eq!(placeholder_count(&html), 0);
+        assert_eq!(
+            html,
+            concat!(
+                r#"<p>One</p><figure class="illustration"><img src="/media/x.png" alt="first" loading="lazy"/></figure>"#,
+                r#"<p>Two</p><figure class="illustration"><img src="/media/x.png" alt="second" loading="lazy"/></figure>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn section_headings_strip_markup_and_skip_deeper_levels() {
+        let html = concat!(
+            "<h1>Ownership</h1><p>text</p>",
+            r#"<H2 id="moves">Moves &amp; <em>copies</em></H2>"#,
+            "<h4>Aside</h4>",
+            "<h3>\nBorrowing\n</h3>",
+        );
+        assert_eq!(
+            section_headings(html),
+            vec!["Ownership", "Moves &amp; copies", "Borrowing"]
+        );
+        assert!(section_headings("<p>No headings</p>").is_empty());
+    }
+
+    #[test]
+    fn parses_plans_with_or_without_a_code_fence() {
+        let plan =
+            parse_illustration_plan(r#"{ "prompt": "A crab", "alt": "A red crab" }"#).unwrap();
+        assert_eq!(plan.prompt, "A crab");
+        assert_eq!(plan.alt, "A red crab");
+
+        let fenced = "```json\n{ \"prompt\": \"A map\", \"alt\": \"Map\" }\n```";
+        assert_eq!(parse_illustration_plan(fenced).unwrap().prompt, "A map");
+    }
+
+    #[test]
+    fn rejects_incomplete_or_unexpected_plans() {
+        assert!(parse_illustration_plan("Here is your plan!").is_err());
+        assert!(parse_illustration_plan(r#"{ "prompt": "A crab" }"#).is_err());
+        assert!(parse_illustration_plan(
+            r#"{ "prompt": "A crab", "alt": "Crab", "style": "oil" }"#
+        )
+        .is_err());
+        assert_eq!(
+            parse_illustration_plan(r#"{ "prompt": " ", "alt": "Crab" }"#).unwrap_err(),
+            "prompt is empty"
+        );
+        assert_eq!(
+            parse_illustration_plan(r#"{ "prompt": "A crab", "alt": "" }"#).unwrap_err(),
+            "alt is empty"
+        );
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IllustrateChapterRequest {
+    pub token: String,
+    pub chapter_id: String,
+    /// Index into the chapter's headings to illustrate that section;
+    /// `None` fills the chapter's image placeholders instead.
+    pub section: Option<u32>,
+    pub model: String,
+}
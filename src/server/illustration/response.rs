@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IllustrationResponse {
+    /// The chapter's HTML with the new illustrations in place.
+    pub html: String,
+    pub added: u32,
+    /// Placeholders still waiting for an illustration.
+    pub remaining: u32,
+}
@@ -0,0 +1,177 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use futures_util::TryStreamExt;
+use std::collections::HashMap;
+
+use crate::server::auth::controller::auth;
+use crate::server::book::model::Book;
+use crate::server::book::model::Chapter;
+use crate::server::common::response::SuccessResponse;
+use crate::server::progress::model::ReadingProgress;
+use crate::server::progress::model::COMPLETION_THRESHOLD;
+use crate::server::progress::request::BookProgressRequest;
+use crate::server::progress::request::LibraryProgressRequest;
+use crate::server::progress::request::UpdateProgressRequest;
+use crate::server::progress::response::BookProgress;
+
+#[cfg(feature = "server")]
+use {crate::db::get_client, mongodb::options::ReturnDocument};
+
+/// Upper bound on the reading time a single update may add, so a tab left
+/// open in the background doesn't inflate the total.
+const MAX_SECONDS_PER_UPDATE: u64 = 120;
+
+#[server]
+pub async fn update_reading_progress(
+    req: UpdateProgressRequest,
+) -> Result<SuccessResponse<ReadingProgress>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+    let chapter_collection = db.collection::<Chapter>("chapters");
+    let progress_collection = db.collection::<ReadingProgress>("reading_progress");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+    let chapter_id = ObjectId::parse_str(&req.chapter_id)
+        .map_err(|_| ServerFnError::new("Invalid chapter ID"))?;
+
+    book_collection
+        .find_one(doc! { "_id": book_id, "user": user.id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Book not found"))?;
+    chapter_collection
+        .find_one(doc! { "_id": chapter_id, "book_id": book_id })
+        .await?
+        .ok_or(ServerFnError::new("Chapter not found"))?;
+
+    let scroll_position = req.scroll_position.clamp(0.0, 1.0);
+    let completed = req
+        .completed
+        .unwrap_or(scroll_position >= COMPLETION_THRESHOLD);
+    let seconds = req.seconds.min(MAX_SECONDS_PER_UPDATE) as i64;
+    let now = Utc::now();
+
+    // `$max` keeps a chapter completed once it has been finished, even if the
+    // reader scrolls back up later.
+    let progress = progress_collection
+        .find_one_and_update(
+            doc! { "user": user.id, "chapter_id": chapter_id },
+            doc! {
+                "$set": {
+                    "book_id": book_id,
+                    "scroll_position": scroll_position,
+                    "updatedAt": now,
+                },
+                "$max": { "completed": completed },
+                "$inc": { "time_spent": seconds },
+                "$setOnInsert": { "createdAt": now },
+            },
+        )
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await?
+        .ok_or(ServerFnError::new("Failed to save reading progress"))?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: progress,
+    })
+}
+
+#[server]
+pub async fn get_book_progress(
+    req: BookProgressRequest,
+) -> Result<SuccessResponse<Vec<ReadingProgress>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let progress_collection = db.collection::<ReadingProgress>("reading_progress");
+
+    let book_id =
+        ObjectId::parse_str(&req.book_id).map_err(|_| ServerFnError::new("Invalid book ID"))?;
+
+    let progress = progress_collection
+        .find(doc! { "user": user.id, "book_id": book_id })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: progress,
+    })
+}
+
+#[server]
+pub async fn get_library_progress(
+    req: LibraryProgressRequest,
+) -> Result<SuccessResponse<Vec<BookProgress>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let chapter_collection = db.collection::<Chapter>("chapters");
+    let progress_collection = db.collection::<ReadingProgress>("reading_progress");
+
+    let entries = progress_collection
+        .find(doc! { "user": user.id })
+        .sort(doc! { "updatedAt": -1 })
+        .await?
+        .try_collect::<Vec<ReadingProgress>>()
+        .await?;
+
+    let mut by_book: HashMap<ObjectId, Vec<ReadingProgress>> = HashMap::new();
+    let mut order = Vec::new();
+    for entry in entries {
+        if !by_book.contains_key(&entry.book_id) {
+            order.push(entry.book_id);
+        }
+        by_book.entry(entry.book_id).or_default().push(entry);
+    }
+
+    let mut library = Vec::with_capacity(order.len());
+    for book_id in order {
+        let entries = &by_book[&book_id];
+        let total_chapters = chapter_collection
+            .count_documents(doc! { "book_id": book_id })
+            .await?;
+        let chapters_completed = entries.iter().filter(|entry| entry.completed).count() as u64;
+
+        library.push(BookProgress {
+            book_id: book_id.to_hex(),
+            // Entries are sorted newest first.
+            last_chapter_id: entries.first().map(|entry| entry.chapter_id.to_hex()),
+            chapters_completed,
+            total_chapters,
+            percent_complete: if total_chapters > 0 {
+                (chapters_completed.min(total_chapters) as f64 / total_chapters as f64) * 100.0
+            } else {
+                0.0
+            },
+            time_spent: entries.iter().map(|entry| entry.time_spent).sum(),
+        });
+    }
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: library,
+    })
+}
@@ -0,0 +1,26 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Scroll fraction past which a chapter counts as read.
+pub const COMPLETION_THRESHOLD: f64 = 0.95;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ReadingProgress {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    pub chapter_id: ObjectId,
+    /// Fraction of the chapter scrolled through, from 0.0 to 1.0.
+    pub scroll_position: f64,
+    pub completed: bool,
+    /// Seconds spent reading the chapter.
+    pub time_spent: u64,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
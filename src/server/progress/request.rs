@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UpdateProgressRequest {
+    pub token: String,
+    pub book_id: String,
+    pub chapter_id: String,
+    pub scroll_position: f64,
+    /// Reading time since the previous update, in seconds.
+    pub seconds: u64,
+    pub completed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookProgressRequest {
+    pub token: String,
+    pub book_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibraryProgressRequest {
+    pub token: String,
+}
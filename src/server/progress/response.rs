@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BookProgress {
+    pub book_id: String,
+    /// The chapter the user read most recently, for "continue reading".
+    pub last_chapter_id: Option<String>,
+    pub chapters_completed: u64,
+    pub total_chapters: u64,
+    pub percent_complete: f64,
+    pub time_spent: u64,
+}
@@ -0,0 +1,485 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use dioxus::prelude::*;
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+
+use crate::server::auth::controller::auth;
+use crate::server::book::model::Book;
+use crate::server::book::model::Chapter;
+use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::controller::check_model;
+use crate::server::quiz::model::Flashcard;
+use crate::server::quiz::model::GradedAnswer;
+use crate::server::quiz::model::QuestionKind;
+use crate::server::quiz::model::Quiz;
+use crate::server::quiz::model::QuizAttempt;
+use crate::server::quiz::model::QuizQuestion;
+use crate::server::quiz::request::ChapterQuizRequest;
+use crate::server::quiz::request::GenerateQuizRequest;
+use crate::server::quiz::request::SubmitQuizRequest;
+use crate::server::quiz::response::QuestionResult;
+use crate::server::quiz::response::QuestionView;
+use crate::server::quiz::response::QuizResult;
+use crate::server::quiz::response::QuizView;
+
+#[cfg(feature = "server")]
+use {
+    crate::ai::{chat_model, generate, get_ai},
+    crate::db::get_client,
+    gems::chat::ChatBuilder,
+    gems::messages::Content,
+    gems::messages::Message,
+    gems::models::Model,
+    gems::traits::CTrait,
+};
+
+const MAX_ITEMS: u32 = 20;
+/// Chapter text beyond this many characters is left out of the prompt.
+const MAX_CONTENT_CHARS: usize = 12_000;
+/// Generation is retried once, with the validation error fed back, when the
+/// model's output doesn't match the schema.
+const GENERATION_ATTEMPTS: usize = 2;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedQuiz {
+    multiple_choice: Vec<GeneratedChoiceQuestion>,
+    short_answer: Vec<GeneratedShortQuestion>,
+    flashcards: Vec<GeneratedFlashcard>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedChoiceQuestion {
+    question: String,
+    options: Vec<String>,
+    answer_index: u32,
+    explanation: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedShortQuestion {
+    question: String,
+    accepted_answers: Vec<String>,
+    explanation: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedFlashcard {
+    front: String,
+    back: String,
+}
+
+const QUIZ_SCHEMA: &str = r#"{
+  "multiple_choice": [
+    { "question": string, "options": [string, string, string, string], "answer_index": 0-3, "explanation": string }
+  ],
+  "short_answer": [
+    { "question": string, "accepted_answers": [string, ...], "explanation": string }
+  ],
+  "flashcards": [
+    { "front": string, "back": string }
+  ]
+}"#;
+
+/// Parses and validates the model's output against `QUIZ_SCHEMA`.
+fn parse_generated_quiz(raw: &str) -> Result<GeneratedQuiz, String> {
+    let json = raw
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let quiz: GeneratedQuiz = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+    let blank = |value: &str| value.trim().is_empty();
+    for (index, question) in quiz.multiple_choice.iter().enumerate() {
+        if blank(&question.question) || blank(&question.explanation) {
+            return Err(format!("multiple_choice[{}] has an empty field", index));
+        }
+        if question.options.len() != 4 || question.options.iter().any(|o| blank(o)) {
+            return Err(format!(
+                "multiple_choice[{}] must have exactly 4 non-empty options",
+                index
+            ));
+        }
+        if question.answer_index as usize >= question.options.len() {
+            return Err(format!(
+                "multiple_choice[{}].answer_index is out of range",
+                index
+            ));
+        }
+    }
+    for (index, question) in quiz.short_answer.iter().enumerate() {
+        if blank(&question.question)
+            || blank(&question.explanation)
+            || question.accepted_answers.iter().all(|a| blank(a))
+        {
+            return Err(format!("short_answer[{}] has an empty field", index));
+        }
+    }
+    for (index, card) in quiz.flashcards.iter().enumerate() {
+        if blank(&card.front) || blank(&card.back) {
+            return Err(format!("flashcards[{}] has an empty side", index));
+        }
+    }
+    if quiz.multiple_choice.is_empty() && quiz.short_answer.is_empty() {
+        return Err("the quiz has no questions".to_string());
+    }
+
+    Ok(quiz)
+}
+
+/// Lowercases and strips punctuation so short answers compare loosely.
+fn normalize_answer(answer: &str) -> String {
+    answer
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn grade(question: &QuizQuestion, response: &str) -> bool {
+    match question.kind {
+        QuestionKind::MultipleChoice => {
+            response.trim().parse::<u32>().ok() == question.answer_index
+        }
+        QuestionKind::ShortAnswer => {
+            let response = normalize_answer(response);
+            !response.is_empty()
+                && question
+                    .accepted_answers
+                    .iter()
+                    .any(|accepted| normalize_answer(accepted) == response)
+        }
+    }
+}
+
+fn correct_answer(question: &QuizQuestion) -> String {
+    match question.kind {
+        QuestionKind::MultipleChoice => question
+            .answer_index
+            .and_then(|index| question.options.get(index as usize))
+            .cloned()
+            .unwrap_or_default(),
+        QuestionKind::ShortAnswer => question.accepted_answers.join(" / "),
+    }
+}
+
+#[cfg(feature = "server")]
+async fn quiz_view(db: &mongodb::Database, quiz: Quiz) -> Result<QuizView, ServerFnError> {
+    let attempt_collection = db.collection::<QuizAttempt>("quiz_attempts");
+    let filter = doc! { "quiz_id": quiz.id, "user": quiz.user };
+    let attempts = attempt_collection.count_documents(filter.clone()).await?;
+    let best_score = attempt_collection
+        .find_one(filter)
+        .sort(doc! { "score": -1 })
+        .await?
+        .map(|attempt| attempt.score);
+
+    Ok(QuizView {
+        id: quiz.id.to_hex(),
+        chapter_id: quiz.chapter_id.to_hex(),
+        questions: quiz
+            .questions
+            .into_iter()
+            .map(|question| QuestionView {
+                id: question.id,
+                kind: question.kind,
+                prompt: question.prompt,
+                options: question.options,
+            })
+            .collect(),
+        flashcards: quiz.flashcards,
+        attempts,
+        best_score,
+    })
+}
+
+#[server]
+pub async fn generate_chapter_quiz(
+    req: GenerateQuizRequest,
+) -> Result<SuccessResponse<QuizView>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+    check_model(&user, &req.model)?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<Book>("books");
+    let chapter_collection = db.collection::<Chapter>("chapters");
+    let quiz_collection = db.collection::<Quiz>("quizzes");
+
+    let chapter_id = ObjectId::parse_str(&req.chapter_id)
+        .map_err(|_| ServerFnError::new("Invalid chapter ID"))?;
+    let chapter = chapter_collection
+        .find_one(doc! { "_id": chapter_id })
+        .await?
+        .ok_or(ServerFnError::new("Chapter not found"))?;
+    let book = book_collection
+        .find_one(doc! { "_id": chapter.book_id, "user": user.id, "deletedAt": null })
+        .await?
+        .ok_or(ServerFnError::new("Chapter not found"))?;
+
+    if chapter.markdown.trim().is_empty() {
+        return Err(ServerFnError::new(
+            "Generate the chapter's content before creating a quiz",
+        ));
+    }
+    let content = chapter
+        .markdown
+        .chars()
+        .take(MAX_CONTENT_CHARS)
+        .collect::<String>();
+
+    let multiple_choice = req.multiple_choice.unwrap_or(5).min(MAX_ITEMS);
+    let short_answer = req.short_answer.unwrap_or(3).min(MAX_ITEMS);
+    let flashcards = req.flashcards.unwrap_or(10).min(MAX_ITEMS);
+
+    let quiz_prompt = format!(
+        "
+        **System Prompt (SP):** You are an instructional designer writing self-test material for learners.
+
+        **Prompt (P):** Using only the chapter '{chapter_title}' of the book '{book_title}' below, write {multiple_choice} multiple-choice questions, {short_answer} short-answer questions and {flashcards} flashcards in {language}. Each multiple-choice question has exactly four options and one correct answer. Short answers should be a word or short phrase; list every acceptable phrasing. Every question needs an explanation that cites the chapter.
+
+        **Chapter Content:**
+        {content}
+
+        **Expected Format (EF):** Respond with a single JSON object and nothing else, matching this schema exactly:
+        {schema}
+
+        **Roleplay (RP):** As a teacher, test understanding rather than trivia.
+        ",
+        chapter_title = chapter.title,
+        book_title = book.title,
+        language = chapter.language,
+        schema = QUIZ_SCHEMA,
+    );
+
+    let mut ai_client = get_ai(req.model.to_string()).await.lock().await;
+    let mut prompt = quiz_prompt.clone();
+    let mut generated = Err(String::new());
+    for _ in 0..GENERATION_ATTEMPTS {
+        let parameters = ChatBuilder::default()
+            .model(chat_model(&req.model)?)
+            .messages(vec![Message::User {
+                content: Content::Text(prompt.clone()),
+                name: None,
+            }])
+            .build()?;
+
+        let raw = generate(&ai_client, Some(user.id), "quiz", parameters)
+            .await
+            .map_err(ServerFnError::new)?;
+
+        generated = parse_generated_quiz(&raw);
+        match &generated {
+            Ok(_) => break,
+            Err(error) => {
+                prompt = format!(
+                    "{}\n\nYour previous answer was rejected: {}. Return only valid JSON matching the schema.",
+                    quiz_prompt, error
+                );
+            }
+        }
+    }
+    let generated = generated
+        .map_err(|error| ServerFnError::new(format!("Invalid quiz from model: {}", error)))?;
+
+    let mut questions = Vec::new();
+    for (index, question) in generated.multiple_choice.into_iter().enumerate() {
+        questions.push(QuizQuestion {
+            id: format!("mc{}", index + 1),
+            kind: QuestionKind::MultipleChoice,
+            prompt: question.question,
+            options: question.options,
+            answer_index: Some(question.answer_index),
+            accepted_answers: Vec::new(),
+            explanation: question.explanation,
+        });
+    }
+    for (index, question) in generated.short_answer.into_iter().enumerate() {
+        questions.push(QuizQuestion {
+            id: format!("sa{}", index + 1),
+            kind: QuestionKind::ShortAnswer,
+            prompt: question.question,
+            options: Vec::new(),
+            answer_index: None,
+            accepted_answers: question.accepted_answers,
+            explanation: question.explanation,
+        });
+    }
+    let flashcards = generated
+        .flashcards
+        .into_iter()
+        .enumerate()
+        .map(|(index, card)| Flashcard {
+            id: format!("fc{}", index + 1),
+            front: card.front,
+            back: card.back,
+        })
+        .collect();
+
+    // Regenerating replaces the chapter's quiz; old attempts stay for history.
+    let now = Utc::now();
+    let quiz = Quiz {
+        id: ObjectId::new(),
+        user: user.id,
+        book_id: book.id,
+        chapter_id,
+        questions,
+        flashcards,
+        created_at: now,
+        updated_at: now,
+    };
+    quiz_collection
+        .delete_many(doc! { "user": user.id, "chapter_id": chapter_id })
+        .await?;
+    quiz_collection.insert_one(&quiz).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: quiz_view(&db, quiz).await?,
+    })
+}
+
+#[server]
+pub async fn get_chapter_quiz(
+    req: ChapterQuizRequest,
+) -> Result<SuccessResponse<Option<QuizView>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let quiz_collection = db.collection::<Quiz>("quizzes");
+
+    let chapter_id = ObjectId::parse_str(&req.chapter_id)
+        .map_err(|_| ServerFnError::new("Invalid chapter ID"))?;
+
+    let quiz = match quiz_collection
+        .find_one(doc! { "user": user.id, "chapter_id": chapter_id })
+        .await?
+    {
+        Some(quiz) => Some(quiz_view(&db, quiz).await?),
+        None => None,
+    };
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: quiz,
+    })
+}
+
+#[server]
+pub async fn submit_quiz_attempt(
+    req: SubmitQuizRequest,
+) -> Result<SuccessResponse<QuizResult>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let quiz_collection = db.collection::<Quiz>("quizzes");
+    let attempt_collection = db.collection::<QuizAttempt>("quiz_attempts");
+
+    let quiz_id =
+        ObjectId::parse_str(&req.quiz_id).map_err(|_| ServerFnError::new("Invalid quiz ID"))?;
+    let quiz = quiz_collection
+        .find_one(doc! { "_id": quiz_id, "user": user.id })
+        .await?
+        .ok_or(ServerFnError::new("Quiz not found"))?;
+
+    let mut answers = Vec::new();
+    let mut results = Vec::new();
+    for question in &quiz.questions {
+        let response = req
+            .answers
+            .iter()
+            .find(|answer| answer.question_id == question.id)
+            .map(|answer| answer.response.clone())
+            .unwrap_or_default();
+        let correct = grade(question, &response);
+
+        answers.push(GradedAnswer {
+            question_id: question.id.clone(),
+            response,
+            correct,
+        });
+        results.push(QuestionResult {
+            question_id: question.id.clone(),
+            correct,
+            correct_answer: correct_answer(question),
+            explanation: question.explanation.clone(),
+        });
+    }
+
+    let score = answers.iter().filter(|answer| answer.correct).count() as u32;
+    let total = quiz.questions.len() as u32;
+
+    attempt_collection
+        .insert_one(QuizAttempt {
+            id: ObjectId::new(),
+            user: user.id,
+            quiz_id,
+            book_id: quiz.book_id,
+            chapter_id: quiz.chapter_id,
+            answers,
+            score,
+            total,
+            created_at: Utc::now(),
+        })
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: QuizResult {
+            score,
+            total,
+            results,
+        },
+    })
+}
+
+#[server]
+pub async fn get_quiz_attempts(
+    req: ChapterQuizRequest,
+) -> Result<SuccessResponse<Vec<QuizAttempt>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let attempt_collection = db.collection::<QuizAttempt>("quiz_attempts");
+
+    let chapter_id = ObjectId::parse_str(&req.chapter_id)
+        .map_err(|_| ServerFnError::new("Invalid chapter ID"))?;
+
+    let attempts = attempt_collection
+        .find(doc! { "user": user.id, "chapter_id": chapter_id })
+        .sort(doc! { "createdAt": -1 })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: attempts,
+    })
+}
@@ -0,0 +1,69 @@
+#![allow(non_snake_case)]
+
+use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum QuestionKind {
+    MultipleChoice,
+    ShortAnswer,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct QuizQuestion {
+    pub id: String,
+    pub kind: QuestionKind,
+    pub prompt: String,
+    /// Choices for multiple-choice questions; empty for short answers.
+    pub options: Vec<String>,
+    /// Index into `options` of the correct choice.
+    pub answer_index: Option<u32>,
+    /// Answers a short-answer response is graded against.
+    pub accepted_answers: Vec<String>,
+    pub explanation: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Flashcard {
+    pub id: String,
+    pub front: String,
+    pub back: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Quiz {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    pub chapter_id: ObjectId,
+    pub questions: Vec<QuizQuestion>,
+    pub flashcards: Vec<Flashcard>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct GradedAnswer {
+    pub question_id: String,
+    pub response: String,
+    pub correct: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct QuizAttempt {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub quiz_id: ObjectId,
+    pub book_id: ObjectId,
+    pub chapter_id: ObjectId,
+    pub answers: Vec<GradedAnswer>,
+    pub score: u32,
+    pub total: u32,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
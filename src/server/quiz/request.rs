@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateQuizRequest {
+    pub token: String,
+    pub chapter_id: String,
+    pub model: String,
+    pub multiple_choice: Option<u32>,
+    pub short_answer: Option<u32>,
+    pub flashcards: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChapterQuizRequest {
+    pub token: String,
+    pub chapter_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuizAnswer {
+    pub question_id: String,
+    /// The chosen option index for multiple choice, or the typed answer.
+    pub response: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmitQuizRequest {
+    pub token: String,
+    pub quiz_id: String,
+    pub answers: Vec<QuizAnswer>,
+}
@@ -0,0 +1,36 @@
+use crate::server::quiz::model::{Flashcard, QuestionKind};
+use serde::{Deserialize, Serialize};
+
+/// A question as shown to the learner, without its answer key.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuestionView {
+    pub id: String,
+    pub kind: QuestionKind,
+    pub prompt: String,
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuizView {
+    pub id: String,
+    pub chapter_id: String,
+    pub questions: Vec<QuestionView>,
+    pub flashcards: Vec<Flashcard>,
+    pub attempts: u64,
+    pub best_score: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuestionResult {
+    pub question_id: String,
+    pub correct: bool,
+    pub correct_answer: String,
+    pub explanation: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuizResult {
+    pub score: u32,
+    pub total: u32,
+    pub results: Vec<QuestionResult>,
+}
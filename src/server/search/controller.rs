@@ -0,0 +1,261 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use bson::Document;
+use dioxus::prelude::*;
+use futures_util::TryStreamExt;
+use std::collections::HashMap;
+
+use crate::server::auth::controller::auth;
+use crate::server::book::model::BookTitle;
+use crate::server::common::response::SuccessResponse;
+use crate::server::search::request::SearchChaptersRequest;
+use crate::server::search::response::SearchHit;
+
+#[cfg(feature = "server")]
+use crate::db::get_client;
+
+const DEFAULT_RESULTS: u32 = 20;
+const MAX_RESULTS: u32 = 50;
+const SNIPPET_RADIUS: usize = 80;
+
+#[server]
+pub async fn search_chapters(
+    req: SearchChaptersRequest,
+) -> Result<SuccessResponse<Vec<SearchHit>>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let query = req.query.trim();
+    if query.is_empty() {
+        return Ok(SuccessResponse {
+            status: "success".into(),
+            data: Vec::new(),
+        });
+    }
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let book_collection = db.collection::<BookTitle>("books");
+    let chapter_collection = db.collection::<Document>("chapters");
+
+    // Chapters carry no owner, so scope the text query to the user's live books.
+    let books: HashMap<ObjectId, String> = book_collection
+        .find(doc! { "user": user.id, "deletedAt": null })
+        .projection(doc! { "title": 1 })
+        .await?
+        .try_collect::<Vec<BookTitle>>()
+        .await?
+        .into_iter()
+        .map(|book| (book.id, book.title))
+        .collect();
+
+    if books.is_empty() {
+        return Ok(SuccessResponse {
+            status: "success".into(),
+            data: Vec::new(),
+        });
+    }
+
+    let book_ids = books.keys().cloned().collect::<Vec<ObjectId>>();
+    let limit = req.limit.unwrap_or(DEFAULT_RESULTS).clamp(1, MAX_RESULTS);
+
+    let chapters = chapter_collection
+        .find(doc! { "$text": { "$search": query }, "book_id": { "$in": book_ids } })
+        .projection(doc! {
+            "book_id": 1,
+            "title": 1,
+            "position": 1,
+            "markdown": 1,
+            "score": { "$meta": "textScore" },
+        })
+        .sort(doc! { "score": { "$meta": "textScore" } })
+        .limit(limit as i64)
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?;
+
+    let terms = search_terms(query);
+    let hits = chapters
+        .into_iter()
+        .filter_map(|chapter| {
+            let book_id = chapter.get_object_id("book_id").ok()?;
+            let markdown = chapter.get_str("markdown").unwrap_or_default();
+            Some(SearchHit {
+                book_id: book_id.to_hex(),
+                book_title: books.get(&book_id)?.clone(),
+                chapter_id: chapter.get_object_id("_id").ok()?.to_hex(),
+                chapter_title: chapter.get_str("title").unwrap_or_default().to_string(),
+                position: chapter
+                    .get("position")
+                    .and_then(|p| p.as_i64().or(p.as_i32().map(i64::from)))
+                    .unwrap_or(0) as u32,
+                score: chapter.get_f64("score").unwrap_or(0.0),
+                snippet: highlight_snippet(markdown, &terms, SNIPPET_RADIUS),
+            })
+        })
+        .collect();
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: hits,
+    })
+}
+
+/// Splits a text query into lowercase terms, ignoring quotes and negations.
+fn search_terms(query: &str) -> Vec<Vec<char>> {
+    query
+        .split_whitespace()
+        .filter(|term| !term.starts_with('-'))
+        .map(|term| {
+            term.trim_matches('"')
+                .chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect::<Vec<char>>()
+        })
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+/// Builds an HTML-escaped excerpt around the first matching term, with every
+/// match inside the excerpt wrapped in `<mark>`.
+fn highlight_snippet(text: &str, terms: &[Vec<char>], radius: usize) -> String {
+    let chars: Vec<char> = text
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+        .collect();
+    // Lowercase char by char so indices line up with `chars`.
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let match_len = |index: usize| {
+        terms
+            .iter()
+            .filter(|term| lower[index..].starts_with(term))
+            .map(|term| term.len())
+            .max()
+    };
+
+    let first = (0..lower.len()).find(|&i| match_len(i).is_some());
+    let (start, end) = match first {
+        Some(index) => (
+            index.saturating_sub(radius),
+            (index + radius).min(chars.len()),
+        ),
+        None => (0, (radius * 2).min(chars.len())),
+    };
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+
+    let mut index = start;
+    while index < end {
+        match match_len(index) {
+            Some(len) => {
+                let matched_end = (index + len).min(chars.len());
+                snippet.push_str("<mark>");
+                chars[index..matched_end]
+                    .iter()
+                    .for_each(|c| push_escaped(&mut snippet, *c));
+                snippet.push_str("</mark>");
+                index = matched_end;
+            }
+            None => {
+                push_escaped(&mut snippet, chars[index]);
+                index += 1;
+            }
+        }
+    }
+
+    // A match can run past `end`, so check where the loop actually stopped.
+    if index < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        _ => out.push(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(text: &str, query: &str, radius: usize) -> String {
+        highlight_snippet(text, &search_terms(query), radius)
+    }
+
+    #[test]
+    fn terms_skip_negations_and_quotes() {
+        let terms = search_terms("\"Borrow\" -unsafe CHECKER");
+        assert_eq!(
+            terms,
+            vec![
+                "borrow".chars().collect::<Vec<char>>(),
+                "checker".chars().collect()
+            ]
+        );
+    }
+
+    #[test]
+    fn highlights_a_match_at_the_start() {
+        assert_eq!(
+            snippet("Ownership rules keep memory safe", "ownership", 12),
+            "<mark>Ownership</mark> ru…"
+        );
+    }
+
+    #[test]
+    fn highlights_a_match_at_the_end() {
+        assert_eq!(
+            snippet("Memory is managed by the borrow checker", "checker", 4),
+            "…row <mark>checker</mark>"
+        );
+    }
+
+    #[test]
+    fn highlights_multibyte_text_case_insensitively() {
+        assert_eq!(
+            snippet("Café au lait, Crème brûlée", "crème", 100),
+            "Café au lait, <mark>Crème</mark> brûlée"
+        );
+        assert_eq!(
+            snippet("Ça va? Ça va.", "ça", 100),
+            "<mark>Ça</mark> va? <mark>Ça</mark> va."
+        );
+    }
+
+    #[test]
+    fn escapes_html_around_matches() {
+        assert_eq!(
+            snippet("Vec<T> & friends", "vec", 100),
+            "<mark>Vec</mark>&lt;T&gt; &amp; friends"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_opening_without_a_match() {
+        assert_eq!(
+            snippet("Lifetimes   and\nborrows", "traits", 5),
+            "Lifetimes …"
+        );
+    }
+}
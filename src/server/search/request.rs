@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchChaptersRequest {
+    pub token: String,
+    pub query: String,
+    pub limit: Option<u32>,
+}
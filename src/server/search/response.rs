@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SearchHit {
+    pub book_id: String,
+    pub book_title: String,
+    pub chapter_id: String,
+    pub chapter_title: String,
+    pub position: u32,
+    pub score: f64,
+    /// HTML-escaped excerpt with matched terms wrapped in `<mark>`.
+    pub snippet: String,
+}
@@ -0,0 +1,209 @@
+#![allow(unused)]
+#![allow(dead_code)]
+
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::prelude::*;
+use chrono::Duration;
+use dioxus::prelude::*;
+use futures_util::TryStreamExt;
+use std::collections::HashMap;
+
+use crate::server::auth::controller::auth;
+use crate::server::book::model::BookTitle;
+use crate::server::book::model::Chapter;
+use crate::server::common::response::SuccessResponse;
+use crate::server::quiz::model::Quiz;
+use crate::server::study::model::CardState;
+use crate::server::study::model::Schedule;
+use crate::server::study::request::ReviewCardRequest;
+use crate::server::study::request::StudyQueueRequest;
+use crate::server::study::response::ReviewResponse;
+use crate::server::study::response::StudyCard;
+use crate::server::study::response::StudyQueue;
+
+#[cfg(feature = "server")]
+use crate::db::get_client;
+
+const DEFAULT_QUEUE_SIZE: u32 = 50;
+/// Never-reviewed cards introduced per session, so a freshly generated book
+/// doesn't bury the reviews that are due.
+const NEW_CARDS_PER_SESSION: usize = 20;
+
+#[server]
+pub async fn get_study_queue(
+    req: StudyQueueRequest,
+) -> Result<SuccessResponse<StudyQueue>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+
+    let books: HashMap<ObjectId, String> = db
+        .collection::<BookTitle>("books")
+        .find(doc! { "user": user.id, "deletedAt": null })
+        .projection(doc! { "title": 1 })
+        .await?
+        .try_collect::<Vec<BookTitle>>()
+        .await?
+        .into_iter()
+        .map(|book| (book.id, book.title))
+        .collect();
+    let book_ids = books.keys().cloned().collect::<Vec<ObjectId>>();
+
+    let quizzes = db
+        .collection::<Quiz>("quizzes")
+        .find(doc! {
+            "user": user.id,
+            "book_id": { "$in": &book_ids },
+            "flashcards.0": { "$exists": true },
+        })
+        .sort(doc! { "createdAt": 1 })
+        .await?
+        .try_collect::<Vec<Quiz>>()
+        .await?;
+    let quiz_ids = quizzes.iter().map(|quiz| quiz.id).collect::<Vec<ObjectId>>();
+    let chapter_ids = quizzes
+        .iter()
+        .map(|quiz| quiz.chapter_id)
+        .collect::<Vec<ObjectId>>();
+
+    let chapters: HashMap<ObjectId, String> = db
+        .collection::<Chapter>("chapters")
+        .find(doc! { "_id": { "$in": chapter_ids } })
+        .await?
+        .try_collect::<Vec<Chapter>>()
+        .await?
+        .into_iter()
+        .map(|chapter| (chapter.id, chapter.title))
+        .collect();
+
+    let states: HashMap<(ObjectId, String), CardState> = db
+        .collection::<CardState>("card_states")
+        .find(doc! { "user": user.id, "quiz_id": { "$in": quiz_ids } })
+        .await?
+        .try_collect::<Vec<CardState>>()
+        .await?
+        .into_iter()
+        .map(|state| ((state.quiz_id, state.card_id.clone()), state))
+        .collect();
+
+    let now = Utc::now();
+    let mut due = Vec::new();
+    let mut new = Vec::new();
+    for quiz in quizzes {
+        let book_title = books.get(&quiz.book_id).cloned().unwrap_or_default();
+        let chapter_title = chapters.get(&quiz.chapter_id).cloned().unwrap_or_default();
+        for card in quiz.flashcards {
+            let state = states.get(&(quiz.id, card.id.clone()));
+            if state.is_some_and(|state| state.due > now) {
+                continue;
+            }
+            let study_card = StudyCard {
+                quiz_id: quiz.id.to_hex(),
+                card_id: card.id,
+                book_title: book_title.clone(),
+                chapter_title: chapter_title.clone(),
+                front: card.front,
+                back: card.back,
+                due: state.map(|state| state.due),
+            };
+            if state.is_some() {
+                due.push(study_card);
+            } else {
+                new.push(study_card);
+            }
+        }
+    }
+    due.sort_by_key(|card| card.due);
+
+    let due_count = due.len() as u64;
+    let new_count = new.len() as u64;
+    let limit = req.limit.unwrap_or(DEFAULT_QUEUE_SIZE) as usize;
+    let cards = due
+        .into_iter()
+        .chain(new.into_iter().take(NEW_CARDS_PER_SESSION))
+        .take(limit)
+        .collect();
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: StudyQueue {
+            cards,
+            due_count,
+            new_count,
+        },
+    })
+}
+
+#[server]
+pub async fn review_card(
+    req: ReviewCardRequest,
+) -> Result<SuccessResponse<ReviewResponse>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    if req.grade > 5 {
+        return Err(ServerFnError::new("Grade must be between 0 and 5"));
+    }
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let state_collection = db.collection::<CardState>("card_states");
+
+    let quiz_id =
+        ObjectId::parse_str(&req.quiz_id).map_err(|_| ServerFnError::new("Invalid quiz ID"))?;
+    let quiz = db
+        .collection::<Quiz>("quizzes")
+        .find_one(doc! { "_id": quiz_id, "user": user.id })
+        .await?
+        .ok_or(ServerFnError::new("Card not found"))?;
+    if !quiz.flashcards.iter().any(|card| card.id == req.card_id) {
+        return Err(ServerFnError::new("Card not found"));
+    }
+
+    let filter = doc! { "user": user.id, "quiz_id": quiz_id, "card_id": &req.card_id };
+    let schedule = state_collection
+        .find_one(filter.clone())
+        .await?
+        .map(|state| Schedule {
+            ease: state.ease,
+            interval: state.interval,
+            repetitions: state.repetitions,
+        })
+        .unwrap_or_default()
+        .review(req.grade);
+
+    let now = Utc::now();
+    let due = now + Duration::days(schedule.interval as i64);
+    state_collection
+        .update_one(
+            filter,
+            doc! {
+                "$set": {
+                    "book_id": quiz.book_id,
+                    "ease": schedule.ease,
+                    "interval": schedule.interval,
+                    "repetitions": schedule.repetitions,
+                    "due": due,
+                    "lastReviewedAt": now,
+                },
+                "$inc": { "lapses": if req.grade < 3 { 1 } else { 0 } },
+            },
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: ReviewResponse {
+            interval: schedule.interval,
+            due,
+        },
+    })
+}
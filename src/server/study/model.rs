@@ -0,0 +1,124 @@
+#![allow(non_snake_case)]
+
+use bson::{
+    oid::ObjectId,
+    serde_helpers::{chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional},
+};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_EASE: f64 = 2.5;
+pub const MIN_EASE: f64 = 1.3;
+
+/// A user's SM-2 schedule for one flashcard.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct CardState {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    pub book_id: ObjectId,
+    pub quiz_id: ObjectId,
+    pub card_id: String,
+    pub ease: f64,
+    /// Days until the next review.
+    pub interval: u32,
+    /// Successful reviews in a row.
+    pub repetitions: u32,
+    pub lapses: u32,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub due: DateTime<Utc>,
+    #[serde(
+        default,
+        with = "chrono_datetime_as_bson_datetime_optional",
+        rename = "lastReviewedAt"
+    )]
+    pub last_reviewed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub ease: f64,
+    pub interval: u32,
+    pub repetitions: u32,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            ease: DEFAULT_EASE,
+            interval: 0,
+            repetitions: 0,
+        }
+    }
+}
+
+impl Schedule {
+    /// Applies an SM-2 review. `grade` runs from 0 (blackout) to 5 (perfect);
+    /// anything below 3 restarts the card.
+    pub fn review(self, grade: u8) -> Schedule {
+        let grade = grade.min(5);
+        let (interval, repetitions) = if grade < 3 {
+            (1, 0)
+        } else {
+            let interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f64 * self.ease).round() as u32,
+            };
+            (interval, self.repetitions + 1)
+        };
+
+        let miss = (5 - grade) as f64;
+        let ease = (self.ease + (0.1 - miss * (0.08 + miss * 0.02))).max(MIN_EASE);
+
+        Schedule {
+            ease,
+            interval,
+            repetitions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(ease: f64, interval: u32, repetitions: u32) -> Schedule {
+        Schedule {
+            ease,
+            interval,
+            repetitions,
+        }
+    }
+
+    #[test]
+    fn reviews_follow_sm2() {
+        // (before, grade, interval, repetitions, ease)
+        let cases = [
+            (Schedule::default(), 5, 1, 1, 2.6),
+            (schedule(2.6, 1, 1), 4, 6, 2, 2.6),
+            (schedule(2.6, 6, 2), 3, 16, 3, 2.46),
+            (schedule(2.5, 16, 3), 2, 1, 0, 2.18),
+            (schedule(1.3, 10, 4), 0, 1, 0, MIN_EASE),
+            (schedule(1.4, 4, 2), 3, 6, 3, MIN_EASE),
+            (Schedule::default(), 9, 1, 1, 2.6),
+        ];
+
+        for (before, grade, interval, repetitions, ease) in cases {
+            let after = before.review(grade);
+            assert_eq!(after.interval, interval, "{:?} graded {}", before, grade);
+            assert_eq!(
+                after.repetitions, repetitions,
+                "{:?} graded {}",
+                before, grade
+            );
+            assert!(
+                (after.ease - ease).abs() < 1e-9,
+                "{:?} graded {} gave ease {}",
+                before,
+                grade,
+                after.ease
+            );
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StudyQueueRequest {
+    pub token: String,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewCardRequest {
+    pub token: String,
+    pub quiz_id: String,
+    pub card_id: String,
+    /// Self-assessed recall from 0 (forgot) to 5 (perfect).
+    pub grade: u8,
+}
@@ -0,0 +1,27 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StudyCard {
+    pub quiz_id: String,
+    pub card_id: String,
+    pub book_title: String,
+    pub chapter_title: String,
+    pub front: String,
+    pub back: String,
+    /// `None` for cards that have never been reviewed.
+    pub due: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct StudyQueue {
+    pub cards: Vec<StudyCard>,
+    pub due_count: u64,
+    pub new_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReviewResponse {
+    pub interval: u32,
+    pub due: DateTime<Utc>,
+}
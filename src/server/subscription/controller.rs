@@ -8,10 +8,13 @@ use dioxus::prelude::*;
 use crate::db::get_client;
 #[cfg(feature = "server")]
 use crate::pay::get_stripe;
+use crate::server::auth::controller::auth;
 use crate::server::auth::model::User;
 use crate::server::book::model::Book;
 use crate::server::common::response::SuccessResponse;
+use crate::server::entitlement::model::Plan;
 use crate::server::subscription::model::Subscription;
+use crate::server::subscription::request::BillingPortalRequest;
 use crate::server::subscription::request::StripeCancelRequest;
 use crate::server::subscription::request::StripePaymentRequest;
 use crate::server::subscription::request::SubscriptionDetailRequest;
@@ -21,44 +24,172 @@ use chrono::prelude::*;
 use std::str::FromStr;
 #[cfg(feature = "server")]
 use stripe::{
-    CheckoutSessionMode, CreateCheckoutSession, CreateCheckoutSessionLineItems,
-    Subscription as StripeSubscription, SubscriptionId,
+    BillingPortalSession, CreateBillingPortalSession, CreateCustomer, Customer, CustomerId,
 };
+#[cfg(feature = "server")]
+use {
+    crate::payment::{get_payment_provider, provider_subscription, Checkout, ProviderSubscription},
+    crate::server::catalog::controller::{active_plan, plan_for_price},
+    crate::server::subscription::model::PaymentMethod,
+    bson::oid::ObjectId,
+    dioxus_logger::tracing,
+    futures_util::TryStreamExt,
+    mongodb::Database,
+};
+
+/// The plan the user's newest active subscription pays for.
+#[cfg(feature = "server")]
+pub(crate) async fn subscribed_plan(db: &Database, user: ObjectId) -> Result<Plan, ServerFnError> {
+    Ok(db
+        .collection::<Subscription>("subscriptions")
+        .find_one(doc! { "user": user, "active": true })
+        .sort(doc! { "updatedAt": -1 })
+        .await?
+        .map(|subscription| Plan::from_role(&subscription.plan))
+        .unwrap_or_default())
+}
+
+/// Sets `User.role` from the user's active subscriptions, so it is right
+/// however many times and in whatever order it's called.
+#[cfg(feature = "server")]
+pub(crate) async fn refresh_user_plan(
+    db: &Database,
+    user: ObjectId,
+) -> Result<Plan, ServerFnError> {
+    let plan = subscribed_plan(db, user).await?;
+
+    db.collection::<User>("users")
+        .update_one(
+            doc! { "_id": user },
+            doc! { "$set": { "role": plan.role(), "updatedAt": Utc::now() } },
+        )
+        .await?;
+    Ok(plan)
+}
+
+/// Mirrors a provider's subscription into `subscriptions` and updates the
+/// owner's plan to match.
+#[cfg(feature = "server")]
+pub(crate) async fn sync_subscription(
+    db: &Database,
+    user: ObjectId,
+    method: PaymentMethod,
+    provider_sub: &ProviderSubscription,
+) -> Result<Plan, ServerFnError> {
+    let mut plan = None;
+    for price in &provider_sub.prices {
+        plan = plan_for_price(db, price).await?;
+        if plan.is_some() {
+            break;
+        }
+    }
+    let plan = plan.ok_or_else(|| {
+        ServerFnError::new(format!(
+            "Subscription {} has no known price",
+            provider_sub.id
+        ))
+    })?;
+
+    let now = Utc::now();
+    db.collection::<Subscription>("subscriptions")
+        .update_one(
+            doc! { "subId": &provider_sub.id },
+            doc! {
+                "$set": {
+                    "user": user,
+                    "sub": &provider_sub.customer,
+                    "plan": plan.role(),
+                    "method": method.as_str(),
+                    "active": provider_sub.active,
+                    "status": &provider_sub.status,
+                    "trialEnd": provider_sub.trial_end,
+                    "updatedAt": now,
+                },
+                "$setOnInsert": { "createdAt": now },
+            },
+        )
+        .upsert(true)
+        .await?;
+
+    refresh_user_plan(db, user).await
+}
+
+/// The user's Stripe customer, created on first use so every checkout and
+/// invoice lands on the same customer.
+#[cfg(feature = "server")]
+pub(crate) async fn ensure_stripe_customer(
+    db: &Database,
+    stripe_client: &stripe::Client,
+    user: &User,
+) -> Result<CustomerId, ServerFnError> {
+    if let Some(customer) = &user.stripe_customer {
+        return CustomerId::from_str(customer)
+            .map_err(|_| ServerFnError::new("Invalid Stripe customer ID"));
+    }
+
+    let user_id = user.id.to_hex();
+    let customer = Customer::create(
+        stripe_client,
+        CreateCustomer {
+            email: Some(&user.email),
+            name: Some(&user.name),
+            metadata: Some([("user_id".to_string(), user_id)].into_iter().collect()),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // Only the first of two racing checkouts gets to store its customer;
+    // the other one is deleted and the stored one reused.
+    let users = db.collection::<User>("users");
+    let stored = users
+        .update_one(
+            doc! { "_id": user.id, "stripeCustomer": null },
+            doc! { "$set": { "stripeCustomer": customer.id.as_str() } },
+        )
+        .await?;
+    if stored.modified_count == 1 {
+        return Ok(customer.id);
+    }
+    Customer::delete(stripe_client, &customer.id).await?;
+    users
+        .find_one(doc! { "_id": user.id })
+        .await?
+        .and_then(|user| user.stripe_customer)
+        .and_then(|customer| CustomerId::from_str(&customer).ok())
+        .ok_or(ServerFnError::new("Stripe customer not found"))
+}
 
 #[server]
 pub async fn get_subscription_detail(
     req: SubscriptionDetailRequest,
 ) -> Result<SuccessResponse<SubscriptionDetailResponse>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let client = get_client().await;
-    let stripe_client = get_stripe().await.lock().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let sub_collection = db.collection::<Subscription>("subscriptions");
 
-    if let Some(subscription) = sub_collection
-        .find_one(doc! { "user": req.user_id })
+    let subscription = sub_collection
+        .find_one(doc! { "user": user.id })
+        .sort(doc! { "active": -1, "updatedAt": -1 })
         .await?
-    {
-        // TODO: Change to enum val. Right now only stripe is allowed, might add paypal for the pal in the future
-        if subscription.method == "stripe" {
-            let subscription_id = SubscriptionId::from_str(subscription.sub_id.as_str())
-                .map_err(|_| ServerFnError::new("Invalid subscription ID"))?;
-            let stripe_sub =
-                StripeSubscription::retrieve(&stripe_client, &subscription_id, &[]).await?;
-
-            return Ok(SuccessResponse {
-                status: "success".into(),
-                data: SubscriptionDetailResponse {
-                    session: serde_json::to_string(&stripe_sub)?,
-                    method: subscription.method,
-                },
-            });
-        }
-    }
+        .ok_or(ServerFnError::new("Subscription not found"))?;
+
+    let provider_sub = provider_subscription(subscription.method, &subscription.sub_id)
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
 
-    Err(ServerFnError::new(
-        "Subscription not found or not a Stripe subscription",
-    ))
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: SubscriptionDetailResponse {
+            session: provider_sub.raw,
+            method: subscription.method,
+        },
+    })
 }
 
 #[server]
@@ -79,7 +210,7 @@ pub async fn update_subscription(
         user_collection
             .update_one(
                 doc! { "_id": sub.user },
-                doc! { "$set": { "role": "free" } },
+                doc! { "$set": { "role": Plan::Free.role() } },
             )
             .await?;
 
@@ -98,32 +229,48 @@ pub async fn update_subscription(
 pub async fn start_stripe_payment(
     req: StripePaymentRequest,
 ) -> Result<SuccessResponse<String>, ServerFnError> {
-    let stripe_client = get_stripe().await.lock().await;
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let plan = active_plan(&db, &req.plan_id)
+        .await?
+        .ok_or(ServerFnError::new("Plan not available"))?;
 
-    let mut session = CreateCheckoutSession::new();
     let success_url = format!(
         "{}/success",
         std::env::var("WEBSITE_URL").expect("WEBSITE_URL must be set.")
     );
-    session.success_url = Some(&success_url);
     let cancel_url = format!(
         "{}/failed",
         std::env::var("WEBSITE_URL").expect("WEBSITE_URL must be set.")
     );
-    session.cancel_url = Some(&cancel_url);
-    session.mode = Some(CheckoutSessionMode::Subscription);
-    session.line_items = vec![CreateCheckoutSessionLineItems {
-        price: Some(req.plan_id.clone()),
-        quantity: Some(1),
-        ..Default::default()
-    }]
-    .into();
-
-    let checkout_session = stripe::CheckoutSession::create(&stripe_client, session).await?;
+    let url = get_payment_provider(req.method)
+        .map_err(|_| ServerFnError::new("Payment method not available"))?
+        .checkout(
+            &db,
+            Checkout {
+                user: &user,
+                plan: &plan,
+                coupon: req
+                    .coupon
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|coupon| !coupon.is_empty())
+                    .map(String::from),
+                success_url,
+                cancel_url,
+            },
+        )
+        .await
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
 
     Ok(SuccessResponse {
         status: "success".into(),
-        data: checkout_session.url.unwrap_or_default(),
+        data: url,
     })
 }
 
@@ -131,25 +278,25 @@ pub async fn start_stripe_payment(
 pub async fn stripe_cancel(
     req: StripeCancelRequest,
 ) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
     let client = get_client().await;
-    let stripe_client = get_stripe().await.lock().await;
     let db =
         client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
     let sub_collection = db.collection::<Subscription>("subscriptions");
-    let user_collection = db.collection::<User>("users");
-
-    if let Some(subscription) = sub_collection.find_one(doc! { "subId": &req.id }).await? {
-        let subscription_id = SubscriptionId::from_str(req.id.as_str())
-            .map_err(|_| ServerFnError::new("Invalid subscription ID"))?;
-        let user_id = subscription.user;
 
-        StripeSubscription::delete(&stripe_client, &subscription_id).await?;
-
-        user_collection
-            .update_one(doc! { "_id": user_id }, doc! { "$set": { "type": "free" } })
-            .await?;
+    let filter = doc! { "subId": &req.id, "user": user.id };
+    if let Some(subscription) = sub_collection.find_one(filter.clone()).await? {
+        get_payment_provider(subscription.method)
+            .map_err(|err| ServerFnError::new(err.to_string()))?
+            .cancel(&subscription.sub_id)
+            .await
+            .map_err(|err| ServerFnError::new(err.to_string()))?;
 
-        sub_collection.delete_one(doc! { "subId": &req.id }).await?;
+        sub_collection.delete_one(filter).await?;
+        refresh_user_plan(&db, subscription.user).await?;
 
         return Ok(SuccessResponse {
             status: "success".into(),
@@ -159,3 +306,183 @@ pub async fn stripe_cancel(
 
     Err(ServerFnError::new("Subscription not found"))
 }
+
+/// Opens Stripe's customer portal, where users update cards, download
+/// invoices and cancel.
+#[server]
+pub async fn open_billing_portal(
+    req: BillingPortalRequest,
+) -> Result<SuccessResponse<String>, ServerFnError> {
+    let user = auth(req.token)
+        .await
+        .map_err(|_| ServerFnError::new("Not Authenticated"))?;
+
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    let stripe_client = get_stripe().await.lock().await;
+    let customer = ensure_stripe_customer(&db, &stripe_client, &user).await?;
+
+    let return_url = format!(
+        "{}/dashboard",
+        std::env::var("WEBSITE_URL").expect("WEBSITE_URL must be set.")
+    );
+    let mut params = CreateBillingPortalSession::new(customer);
+    params.return_url = Some(&return_url);
+    let session = BillingPortalSession::create(&stripe_client, params).await?;
+
+    Ok(SuccessResponse {
+        status: "success".into(),
+        data: session.url,
+    })
+}
+
+/// Re-reads subscriptions whose trial should be over, so one that ended
+/// unpaid stops granting its plan even if the provider's webhook was lost.
+/// Returns how many were checked.
+#[cfg(feature = "server")]
+pub(crate) async fn check_ended_trials() -> Result<u64, ServerFnError> {
+    let client = get_client().await;
+    let db =
+        client.database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    recheck_ended_trials(&db).await
+}
+
+#[cfg(feature = "server")]
+async fn recheck_ended_trials(db: &Database) -> Result<u64, ServerFnError> {
+    let ended = db
+        .collection::<Subscription>("subscriptions")
+        .find(doc! {
+            "active": true,
+            "status": "trialing",
+            "trialEnd": { "$lte": Utc::now() },
+        })
+        .await?
+        .try_collect::<Vec<Subscription>>()
+        .await?;
+
+    // One provider being down shouldn't hold up the other trials.
+    for subscription in &ended {
+        let provider_sub =
+            match provider_subscription(subscription.method, &subscription.sub_id).await {
+                Ok(provider_sub) => provider_sub,
+                Err(err) => {
+                    tracing::warn!(
+                        "couldn't check trial of subscription {}: {}",
+                        subscription.sub_id,
+                        err
+                    );
+                    continue;
+                }
+            };
+        if let Err(err) =
+            sync_subscription(db, subscription.user, subscription.method, &provider_sub).await
+        {
+            tracing::warn!(
+                "couldn't update subscription {} after its trial: {}",
+                subscription.sub_id,
+                err
+            );
+        }
+    }
+
+    Ok(ended.len() as u64)
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::payment::{fake_provider, PaymentProvider};
+    use crate::server::catalog::model::CatalogPlan;
+    use crate::testing::{insert_user, monthly_plan, test_db, user_role};
+
+    /// A trial through the fake provider, recorded as a webhook would.
+    async fn start_trial(db: &Database, plan: &CatalogPlan) -> (User, String) {
+        let user = insert_user(db).await;
+        let url = fake_provider()
+            .checkout(
+                db,
+                Checkout {
+                    user: &user,
+                    plan,
+                    coupon: None,
+                    success_url: String::new(),
+                    cancel_url: String::new(),
+                },
+            )
+            .await
+            .unwrap();
+        let id = url.rsplit('=').next().unwrap().to_string();
+        let provider_sub = provider_subscription(PaymentMethod::Fake, &id)
+            .await
+            .unwrap();
+        sync_subscription(db, user.id, PaymentMethod::Fake, &provider_sub)
+            .await
+            .unwrap();
+        (user, id)
+    }
+
+    async fn end_trial(db: &Database, subscription: &str) {
+        db.collection::<Subscription>("subscriptions")
+            .update_one(
+                doc! { "subId": subscription },
+                doc! { "$set": { "trialEnd": Utc::now() - chrono::Duration::minutes(1) } },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn unpaid_trials_lose_the_plan() {
+        let db = test_db().await;
+        let plan = monthly_plan(14);
+        db.collection::<CatalogPlan>("plans")
+            .insert_one(&plan)
+            .await
+            .unwrap();
+
+        let (user, id) = start_trial(&db, &plan).await;
+        assert_eq!(user_role(&db, user.id).await, "monthly");
+        assert_eq!(recheck_ended_trials(&db).await.unwrap(), 0);
+
+        // The trial ended without a card and the provider canceled it, but
+        // the webhook never came.
+        end_trial(&db, &id).await;
+        fake_provider().set_status(&id, "canceled", false).unwrap();
+        assert_eq!(recheck_ended_trials(&db).await.unwrap(), 1);
+
+        assert_eq!(user_role(&db, user.id).await, "free");
+        db.drop().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn one_failing_trial_doesnt_stop_the_rest() {
+        let db = test_db().await;
+        let plan = monthly_plan(14);
+        db.collection::<CatalogPlan>("plans")
+            .insert_one(&plan)
+            .await
+            .unwrap();
+
+        let (user, id) = start_trial(&db, &plan).await;
+        end_trial(&db, &id).await;
+        fake_provider().set_status(&id, "canceled", false).unwrap();
+
+        // A trial the provider no longer knows about.
+        let (_, lost) = start_trial(&db, &plan).await;
+        db.collection::<Subscription>("subscriptions")
+            .update_one(
+                doc! { "subId": &lost },
+                doc! { "$set": { "subId": "fake_sub_missing" } },
+            )
+            .await
+            .unwrap();
+        end_trial(&db, "fake_sub_missing").await;
+
+        assert_eq!(recheck_ended_trials(&db).await.unwrap(), 2);
+        assert_eq!(user_role(&db, user.id).await, "free");
+        db.drop().await.unwrap();
+    }
+}
@@ -1,21 +1,89 @@
-use bson::{oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime};
+use bson::{
+    oid::ObjectId,
+    serde_helpers::{chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional},
+};
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Who takes the payments for a subscription.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentMethod {
+    #[default]
+    Stripe,
+    Paypal,
+    /// In-memory payments for trying out the billing flow.
+    Fake,
+}
+
+impl PaymentMethod {
+    /// The value stored in `Subscription.method`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentMethod::Stripe => "stripe",
+            PaymentMethod::Paypal => "paypal",
+            PaymentMethod::Fake => "fake",
+        }
+    }
+}
+
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Subscription {
     #[serde(rename = "_id")]
     pub id: ObjectId,
     pub user: ObjectId,
+    /// The payment provider's customer id.
     pub sub: String,
     #[serde(rename = "subId")]
     pub sub_id: String,
     pub plan: String,
-    pub method: String,
+    pub method: PaymentMethod,
     pub active: bool,
+    /// The provider's own status, e.g. Stripe's `past_due`.
+    #[serde(default)]
+    pub status: String,
+    /// When the free trial ends. The plan applies during it; if it ends
+    /// unpaid the subscription goes inactive and the user back to free.
+    #[serde(
+        default,
+        with = "chrono_datetime_as_bson_datetime_optional",
+        rename = "trialEnd"
+    )]
+    pub trial_end: Option<DateTime<Utc>>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono_datetime_as_bson_datetime", rename = "updatedAt")]
     pub updated_at: DateTime<Utc>,
 }
+
+/// A webhook event that has been applied, so redeliveries are skipped.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ProcessedEvent {
+    /// The provider's event id.
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub kind: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// A correction made by reconciliation, when what we stored had drifted
+/// from the payment provider.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SubscriptionAudit {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub user: ObjectId,
+    /// Absent for role corrections.
+    #[serde(rename = "subId")]
+    pub sub_id: Option<String>,
+    /// `active`, `role`, or `subscription` for one that wasn't recorded.
+    pub field: String,
+    pub from: String,
+    pub to: String,
+    /// What ran the reconciliation: `schedule` or `admin`.
+    pub trigger: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime", rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
@@ -1,9 +1,10 @@
-use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
+use crate::server::subscription::model::PaymentMethod;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SubscriptionDetailRequest {
-    pub user_id: ObjectId,
+    pub token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -13,10 +14,25 @@ pub struct UpdateSubscriptionRequest {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StripePaymentRequest {
+    pub token: String,
+    /// A plan in the catalog, not a Stripe price.
     pub plan_id: String,
+    /// A Stripe coupon to apply. Without one, customers can enter a
+    /// promotion code at checkout instead.
+    #[serde(default)]
+    pub coupon: Option<String>,
+    /// Who takes the payments; Stripe unless the user picks another.
+    #[serde(default)]
+    pub method: PaymentMethod,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BillingPortalRequest {
+    pub token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StripeCancelRequest {
+    pub token: String,
     pub id: String,
 }
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use crate::server::subscription::model::PaymentMethod;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SubscriptionDetailResponse {
+    /// The provider's subscription record as JSON.
     pub session: String,
-    pub method: String,
+    pub method: PaymentMethod,
 }
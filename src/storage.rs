@@ -0,0 +1,118 @@
+use anyhow::{bail, Result};
+use axum::extract::Path;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use rand::RngCore;
+use std::path::PathBuf;
+
+/// URL prefix the media route is mounted under.
+pub const MEDIA_ROUTE: &str = "/media";
+
+/// Root directory for generated media (audio, covers, illustrations).
+fn media_dir() -> PathBuf {
+    PathBuf::from(std::env::var("MEDIA_DIR").unwrap_or_else(|_| "media".to_string()))
+}
+
+/// Keys are relative, slash-separated paths; anything that could escape the
+/// media directory is rejected.
+fn resolve(key: &str) -> Result<PathBuf> {
+    if key.is_empty()
+        || key.starts_with('/')
+        || key
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == "..")
+    {
+        bail!("invalid media key: {}", key);
+    }
+    Ok(media_dir().join(key))
+}
+
+/// A hard-to-guess file name stem, so media URLs can't be enumerated.
+pub fn random_name() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn url(key: &str) -> String {
+    format!("{}/{}", MEDIA_ROUTE, key)
+}
+
+/// Maps a URL produced by [`url`] back to its key.
+pub fn key_from_url(url: &str) -> Option<&str> {
+    url.strip_prefix(MEDIA_ROUTE)?.strip_prefix('/')
+}
+
+pub async fn put(key: &str, bytes: &[u8]) -> Result<String> {
+    let path = resolve(key)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&path, bytes).await?;
+    Ok(url(key))
+}
+
+pub async fn get(key: &str) -> Result<Vec<u8>> {
+    Ok(tokio::fs::read(resolve(key)?).await?)
+}
+
+pub async fn exists(key: &str) -> bool {
+    match resolve(key) {
+        Ok(path) => tokio::fs::try_exists(path).await.unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/// Removes a key, or every key under it when it names a directory.
+pub async fn delete(key: &str) -> Result<()> {
+    let path = resolve(key)?;
+    match tokio::fs::metadata(&path).await {
+        Ok(metadata) if metadata.is_dir() => tokio::fs::remove_dir_all(&path).await?,
+        Ok(_) => tokio::fs::remove_file(&path).await?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+    Ok(())
+}
+
+pub fn content_type(key: &str) -> &'static str {
+    match key.rsplit('.').next().unwrap_or_default() {
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "m4b" => "audio/mp4",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// File extension of a PNG, JPEG or WebP image, sniffed from its magic bytes.
+pub fn image_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("jpg")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else {
+        None
+    }
+}
+
+/// Axum handler for `GET /media/*key`.
+pub async fn serve_media(Path(key): Path<String>) -> Response {
+    match get(&key).await {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, content_type(&key)),
+                (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
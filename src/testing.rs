@@ -0,0 +1,97 @@
+//! Fixtures shared by the billing tests. Tests that touch the database are
+//! `#[ignore]`d and run against `MONGODB_TEST_URI` with
+//! `cargo test --features server -- --include-ignored`.
+
+use bson::oid::ObjectId;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use mongodb::{Client, Database};
+use sha2::Sha256;
+
+use crate::server::auth::model::User;
+use crate::server::catalog::model::{CatalogPlan, Interval};
+use crate::server::entitlement::model::Plan;
+
+pub const WEBHOOK_SECRET: &str = "whsec_test_secret";
+pub const MONTHLY_PRICE: &str = "price_test_monthly";
+
+/// A fresh database on the test server, so tests can run side by side.
+pub async fn test_db() -> Database {
+    let uri = std::env::var("MONGODB_TEST_URI")
+        .unwrap_or_else(|_| "mongodb://localhost:27017".to_string());
+    Client::with_uri_str(uri)
+        .await
+        .expect("MONGODB_TEST_URI must point at a MongoDB server.")
+        .database(&format!("aibook_test_{}", ObjectId::new().to_hex()))
+}
+
+/// A `Stripe-Signature` header for `body`, signed the way Stripe does.
+pub fn stripe_signature(body: &str, secret: &str) -> String {
+    let timestamp = Utc::now().timestamp();
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!(
+        "t={},v1={}",
+        timestamp,
+        hex::encode(&mac.finalize().into_bytes())
+    )
+}
+
+/// A fixture from `tests/fixtures/stripe` for this user and subscription.
+pub fn stripe_fixture(raw: &str, user: ObjectId, subscription: &str) -> String {
+    raw.replace("__USER__", &user.to_hex())
+        .replace("__SUBSCRIPTION__", subscription)
+        .replace("__PRICE__", MONTHLY_PRICE)
+}
+
+pub fn monthly_plan(trial_days: u32) -> CatalogPlan {
+    CatalogPlan {
+        id: "monthly".into(),
+        name: "Monthly".into(),
+        description: String::new(),
+        features: Vec::new(),
+        plan: Plan::Monthly,
+        price_id: Some(MONTHLY_PRICE.into()),
+        paypal_plan_id: None,
+        amount: 200,
+        currency: "usd".into(),
+        interval: Some(Interval::Month),
+        trial_days,
+        highlight: false,
+        position: 1,
+        active: true,
+    }
+}
+
+pub fn test_user() -> User {
+    User {
+        id: ObjectId::new(),
+        name: "Reader".into(),
+        email: "reader@example.com".into(),
+        password: String::new(),
+        role: Plan::Free.role().into(),
+        photo: String::new(),
+        verified: true,
+        stripe_customer: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+pub async fn insert_user(db: &Database) -> User {
+    let user = test_user();
+    db.collection::<User>("users")
+        .insert_one(&user)
+        .await
+        .unwrap();
+    user
+}
+
+pub async fn user_role(db: &Database, user: ObjectId) -> String {
+    db.collection::<User>("users")
+        .find_one(bson::doc! { "_id": user })
+        .await
+        .unwrap()
+        .unwrap()
+        .role
+}
@@ -0,0 +1,249 @@
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use crate::server::common::language::language_tag;
+
+/// A speech engine turning plain text into a WAV file.
+///
+/// Implementations may block; callers run them on the blocking pool.
+pub trait TextToSpeech: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn synthesize(&self, text: &str, language: &str) -> Result<Vec<u8>>;
+}
+
+/// Shells out to `espeak-ng`, which writes a WAV to stdout.
+pub struct EspeakTts {
+    pub binary: String,
+    /// Overrides the voice derived from the chapter language.
+    pub voice: Option<String>,
+}
+
+impl TextToSpeech for EspeakTts {
+    fn name(&self) -> &'static str {
+        "espeak-ng"
+    }
+
+    fn synthesize(&self, text: &str, language: &str) -> Result<Vec<u8>> {
+        let voice = self
+            .voice
+            .clone()
+            .unwrap_or_else(|| language_tag(language).to_string());
+        run(
+            Command::new(&self.binary).args(["--stdin", "--stdout", "-v", &voice]),
+            text,
+        )
+    }
+}
+
+/// Shells out to `piper` with a local voice model.
+pub struct PiperTts {
+    pub binary: String,
+    pub model: String,
+}
+
+impl TextToSpeech for PiperTts {
+    fn name(&self) -> &'static str {
+        "piper"
+    }
+
+    fn synthesize(&self, text: &str, _language: &str) -> Result<Vec<u8>> {
+        // Piper picks the language from its model and only writes WAV files
+        // to disk.
+        let output = std::env::temp_dir().join(format!("piper-{}.wav", crate::storage::random_name()));
+        let result = run(
+            Command::new(&self.binary)
+                .arg("--model")
+                .arg(&self.model)
+                .arg("--output_file")
+                .arg(&output),
+            text,
+        )
+        .and_then(|_| std::fs::read(&output).context("piper produced no audio"));
+        let _ = std::fs::remove_file(&output);
+        result
+    }
+}
+
+/// Produces silence instead of speech: a few seconds per chapter, so the
+/// pipeline can run without an engine installed.
+pub struct SilentTts;
+
+const SILENT_SAMPLE_RATE: u32 = 8_000;
+const SILENT_MAX_SECS: u32 = 10;
+
+impl TextToSpeech for SilentTts {
+    fn name(&self) -> &'static str {
+        "silent"
+    }
+
+    fn synthesize(&self, text: &str, _language: &str) -> Result<Vec<u8>> {
+        // Roughly the reading time at 150 words per minute, capped.
+        let words = text.split_whitespace().count() as u32;
+        let seconds = (words * 60 / 150).clamp(1, SILENT_MAX_SECS);
+        Ok(silent_wav(seconds * SILENT_SAMPLE_RATE))
+    }
+}
+
+fn run(command: &mut Command, text: &str) -> Result<Vec<u8>> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to start the speech engine")?;
+    // Feed stdin from another thread so a full stdout pipe can't deadlock us.
+    let mut stdin = child.stdin.take().context("speech engine has no stdin")?;
+    let text = text.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(text.as_bytes()));
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| anyhow::anyhow!("speech engine input thread panicked"))??;
+    if !output.status.success() {
+        bail!(
+            "speech engine exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// An 8-bit mono WAV of the given number of silent samples.
+fn silent_wav(samples: u32) -> Vec<u8> {
+    let mut wav = Vec::with_capacity(44 + samples as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + samples).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SILENT_SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&SILENT_SAMPLE_RATE.to_le_bytes()); // byte rate
+    wav.extend_from_slice(&1u16.to_le_bytes()); // block align
+    wav.extend_from_slice(&8u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&samples.to_le_bytes());
+    // 8-bit PCM is unsigned; 128 is the zero line.
+    wav.resize(44 + samples as usize, 128);
+    wav
+}
+
+/// Playing time of a WAV file in milliseconds, read from its header.
+pub fn wav_duration_ms(wav: &[u8]) -> Option<u64> {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut byte_rate = None;
+    let mut offset = 12;
+    while offset + 8 <= wav.len() {
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = offset + 8;
+        if id == b"fmt " && body + 12 <= wav.len() {
+            byte_rate = Some(u32::from_le_bytes(wav[body + 8..body + 12].try_into().ok()?) as u64);
+        } else if id == b"data" {
+            // Streamed WAVs (espeak's stdout) leave the size unset.
+            let size = if size == 0 || size == u32::MAX as usize || body + size > wav.len() {
+                wav.len() - body
+            } else {
+                size
+            };
+            return byte_rate
+                .filter(|rate| *rate > 0)
+                .map(|rate| size as u64 * 1000 / rate);
+        }
+        offset = body + size + (size & 1);
+    }
+    None
+}
+
+/// The engine selected by `TTS_BACKEND`: `espeak` (default), `piper` or
+/// `silent`.
+pub fn get_tts() -> Arc<dyn TextToSpeech> {
+    match std::env::var("TTS_BACKEND").unwrap_or_default().as_str() {
+        "piper" => Arc::new(PiperTts {
+            binary: std::env::var("PIPER_BIN").unwrap_or_else(|_| "piper".to_string()),
+            model: std::env::var("PIPER_MODEL").expect("PIPER_MODEL must be set."),
+        }),
+        "silent" => Arc::new(SilentTts),
+        _ => Arc::new(EspeakTts {
+            binary: std::env::var("ESPEAK_BIN").unwrap_or_else(|_| "espeak-ng".to_string()),
+            voice: std::env::var("ESPEAK_VOICE").ok(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16-bit mono 16 kHz WAV header with `data_size` bytes of samples
+    /// announced in the header and `samples` bytes actually present.
+    fn wav(data_size: u32, samples: usize) -> Vec<u8> {
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&data_size.saturating_add(36).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&16_000u32.to_le_bytes());
+        wav.extend_from_slice(&32_000u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_size.to_le_bytes());
+        wav.resize(wav.len() + samples, 0);
+        wav
+    }
+
+    #[test]
+    fn silent_tts_scales_with_the_text_and_is_capped() {
+        let short = SilentTts.synthesize("One two three", "English").unwrap();
+        assert_eq!(wav_duration_ms(&short), Some(1_000));
+
+        let paragraph = "word ".repeat(10);
+        let medium = SilentTts.synthesize(&paragraph, "English").unwrap();
+        assert_eq!(wav_duration_ms(&medium), Some(4_000));
+
+        let book = "word ".repeat(10_000);
+        let long = SilentTts.synthesize(&book, "English").unwrap();
+        assert_eq!(wav_duration_ms(&long), Some(SILENT_MAX_SECS as u64 * 1_000));
+    }
+
+    #[test]
+    fn duration_comes_from_the_data_chunk_and_byte_rate() {
+        assert_eq!(wav_duration_ms(&wav(64_000, 64_000)), Some(2_000));
+    }
+
+    #[test]
+    fn streamed_data_chunk_falls_back_to_the_bytes_present() {
+        // espeak writes to a pipe and leaves the sizes at 0 or u32::MAX.
+        assert_eq!(wav_duration_ms(&wav(0, 16_000)), Some(500));
+        assert_eq!(wav_duration_ms(&wav(u32::MAX, 32_000)), Some(1_000));
+        // A header promising more than was written is truncated the same way.
+        assert_eq!(wav_duration_ms(&wav(64_000, 8_000)), Some(250));
+    }
+
+    #[test]
+    fn skips_chunks_before_the_data() {
+        let mut wav = wav(32_000, 32_000);
+        let list = [b"LIST".as_slice(), &3u32.to_le_bytes(), b"abc", &[0]].concat();
+        wav.splice(36..36, list);
+        assert_eq!(wav_duration_ms(&wav), Some(1_000));
+    }
+
+    #[test]
+    fn rejects_anything_but_a_wav() {
+        assert_eq!(wav_duration_ms(b""), None);
+        assert_eq!(wav_duration_ms(b"ID3\x04\x00\x00\x00\x00WAVE"), None);
+        // No fmt chunk, so no byte rate to divide by.
+        let mut wav = wav(16_000, 16_000);
+        wav.drain(12..36);
+        assert_eq!(wav_duration_ms(&wav), None);
+    }
+}
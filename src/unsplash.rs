@@ -1,17 +1,198 @@
-use http_api_isahc_client::IsahcClient;
-use tokio::sync::{Mutex, OnceCell};
+use anyhow::{bail, Result};
+use dioxus_logger::tracing;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tokio::sync::OnceCell;
 
-static UNSPLASH: OnceCell<Mutex<IsahcClient>> = OnceCell::const_new();
+use crate::redis::ApiBudget;
+use crate::server::cover::model::CoverCredit;
 
-async fn init_unsplash() -> &'static Mutex<IsahcClient> {
-    UNSPLASH
+const API_URL: &str = "https://api.unsplash.com";
+
+/// Requests held back from searches so choosing a photo can still report
+/// its download.
+const SEARCH_RESERVE: u64 = 5;
+
+/// Unsplash quotas reset every hour.
+const QUOTA_WINDOW_SECS: u64 = 60 * 60;
+
+/// Appended to links back to Unsplash, as its attribution guidelines ask.
+const REFERRAL: &str = "utm_source=aibook&utm_medium=referral";
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    results: Vec<ApiPhoto>,
+}
+
+#[derive(Deserialize)]
+struct ApiPhoto {
+    urls: ApiUrls,
+    links: ApiPhotoLinks,
+    user: ApiUser,
+}
+
+#[derive(Deserialize)]
+struct ApiUrls {
+    regular: String,
+}
+
+#[derive(Deserialize)]
+struct ApiPhotoLinks {
+    html: String,
+    download_location: String,
+}
+
+#[derive(Deserialize)]
+struct ApiUser {
+    name: String,
+    links: ApiUserLinks,
+}
+
+#[derive(Deserialize)]
+struct ApiUserLinks {
+    html: String,
+}
+
+/// A search hit: where to fetch the image and whom to credit.
+#[derive(Clone)]
+pub struct UnsplashPhoto {
+    pub image_url: String,
+    pub credit: CoverCredit,
+}
+
+fn with_referral(url: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", url, separator, REFERRAL)
+}
+
+static BUDGET: OnceCell<Option<ApiBudget>> = OnceCell::const_new();
+
+/// The shared quota, or `None` when Redis isn't configured and requests go
+/// unbudgeted.
+async fn budget() -> Option<&'static ApiBudget> {
+    BUDGET
         .get_or_init(|| async {
-            let client = IsahcClient::new().unwrap();
-            Mutex::new(client)
+            let redis_url = std::env::var("REDIS_URL").ok()?;
+            ApiBudget::new(&redis_url, "unsplash")
+                .map_err(|err| tracing::warn!("Unsplash budget unavailable: {}", err))
+                .ok()
         })
         .await
+        .as_ref()
+}
+
+/// Takes one request from the shared hourly quota. Redis being down
+/// shouldn't take covers with it, so errors let the request through.
+async fn take_budget(reserve: u64) -> bool {
+    let Some(budget) = budget().await else {
+        return true;
+    };
+    match budget.take(reserve).await {
+        Ok(allowed) => allowed,
+        Err(err) => {
+            tracing::warn!("Unsplash budget unavailable: {}", err);
+            true
+        }
+    }
+}
+
+/// Stores the quota left according to the response headers.
+async fn record_budget(status: StatusCode, headers: &HeaderMap) {
+    let remaining = if status == StatusCode::TOO_MANY_REQUESTS {
+        Some(0)
+    } else {
+        headers
+            .get("X-Ratelimit-Remaining")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    if let (Some(remaining), Some(budget)) = (remaining, budget().await) {
+        if let Err(err) = budget.record(remaining, QUOTA_WINDOW_SECS).await {
+            tracing::warn!("recording Unsplash budget failed: {}", err);
+        }
+    }
+}
+
+async fn get(access_key: &str, url: &str, query: &[(&str, String)]) -> Result<reqwest::Response> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .query(query)
+        .header("Authorization", format!("Client-ID {}", access_key))
+        .header("Accept-Version", "v1")
+        .send()
+        .await?;
+    record_budget(response.status(), response.headers()).await;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        bail!("Unsplash rate limit reached");
+    }
+    if !response.status().is_success() {
+        bail!("Unsplash returned {}", response.status());
+    }
+    Ok(response)
+}
+
+/// Portrait photos matching `query`.
+pub async fn search_photos(
+    access_key: &str,
+    query: &str,
+    count: usize,
+) -> Result<Vec<UnsplashPhoto>> {
+    if !take_budget(SEARCH_RESERVE).await {
+        bail!("Unsplash hourly quota is used up");
+    }
+    let response = get(
+        access_key,
+        &format!("{}/search/photos", API_URL),
+        &[
+            ("query", query.to_string()),
+            ("per_page", count.to_string()),
+            ("orientation", "portrait".to_string()),
+        ],
+    )
+    .await?;
+
+    Ok(response
+        .json::<SearchResponse>()
+        .await?
+        .results
+        .into_iter()
+        .map(|photo| UnsplashPhoto {
+            image_url: photo.urls.regular,
+            credit: CoverCredit {
+                photographer: photo.user.name,
+                profile_url: with_referral(&photo.user.links.html),
+                photo_url: with_referral(&photo.links.html),
+                download_location: photo.links.download_location,
+            },
+        })
+        .collect())
+}
+
+/// Reports that a photo was picked, which Unsplash requires for every use.
+pub async fn track_download(access_key: &str, credit: &CoverCredit) -> Result<()> {
+    if !credit
+        .download_location
+        .starts_with(&format!("{}/", API_URL))
+    {
+        bail!("not an Unsplash download location");
+    }
+    if !take_budget(0).await {
+        bail!("Unsplash hourly quota is used up");
+    }
+    get(access_key, &credit.download_location, &[]).await?;
+    Ok(())
 }
 
-pub async fn get_unsplash_client() -> &'static Mutex<IsahcClient> {
-    init_unsplash().await
+/// Fetches image bytes from the Unsplash CDN, which doesn't count towards
+/// the API quota.
+pub async fn download_image(url: &str) -> Result<Vec<u8>> {
+    if !url.starts_with("https://images.unsplash.com/") {
+        bail!("not an Unsplash image");
+    }
+    let response = reqwest::get(url).await?;
+    if !response.status().is_success() {
+        bail!("Unsplash image returned {}", response.status());
+    }
+    Ok(response.bytes().await?.to_vec())
 }
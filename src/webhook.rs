@@ -0,0 +1,306 @@
+use anyhow::{anyhow, Result};
+use axum::http::{HeaderMap, StatusCode};
+use bson::doc;
+use bson::oid::ObjectId;
+use chrono::Utc;
+use dioxus_logger::tracing;
+use mongodb::Database;
+
+use crate::db::get_client;
+use crate::payment::{get_payment_provider, PaymentProvider, WebhookEvent};
+use crate::server::auth::model::User;
+use crate::server::subscription::controller::sync_subscription;
+use crate::server::subscription::model::{PaymentMethod, ProcessedEvent, Subscription};
+
+pub const STRIPE_WEBHOOK_ROUTE: &str = "/webhooks/stripe";
+pub const PAYPAL_WEBHOOK_ROUTE: &str = "/webhooks/paypal";
+
+pub async fn stripe_webhook(headers: HeaderMap, body: String) -> StatusCode {
+    receive(PaymentMethod::Stripe, headers, body).await
+}
+
+pub async fn paypal_webhook(headers: HeaderMap, body: String) -> StatusCode {
+    receive(PaymentMethod::Paypal, headers, body).await
+}
+
+/// Anything but a 2xx makes the provider retry the delivery, so only
+/// deliveries that fail verification are refused outright. A provider that
+/// isn't configured has no webhook.
+async fn receive(method: PaymentMethod, headers: HeaderMap, body: String) -> StatusCode {
+    let provider = match get_payment_provider(method) {
+        Ok(provider) => provider,
+        Err(err) => {
+            tracing::warn!("{} webhook unavailable: {}", method.as_str(), err);
+            return StatusCode::NOT_FOUND;
+        }
+    };
+    let event = match provider.parse_webhook(&headers, &body).await {
+        Ok(event) => event,
+        Err(err) => {
+            tracing::warn!("rejected {} webhook: {}", method.as_str(), err);
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    let db = get_client()
+        .await
+        .database(&std::env::var("MONGODB_DB_NAME").expect("MONGODB_DB_NAME must be set."));
+    match handle_event(&db, provider.as_ref(), event).await {
+        Ok(()) => StatusCode::OK,
+        Err(err) => {
+            tracing::error!("handling {} webhook failed: {}", method.as_str(), err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Applies an event once. The subscription is re-read from the provider,
+/// so events arriving late or out of order can't roll a plan back.
+pub(crate) async fn handle_event(
+    db: &Database,
+    provider: &dyn PaymentProvider,
+    event: WebhookEvent,
+) -> Result<()> {
+    let events = db.collection::<ProcessedEvent>("processed_events");
+    if events.find_one(doc! { "_id": &event.id }).await?.is_some() {
+        return Ok(());
+    }
+
+    if let Some(subscription) = &event.subscription {
+        subscription_changed(db, provider, subscription, &event).await?;
+    }
+
+    events
+        .insert_one(ProcessedEvent {
+            id: event.id,
+            kind: event.kind,
+            created_at: Utc::now(),
+        })
+        .await?;
+    Ok(())
+}
+
+/// The user a subscription belongs to: whoever it's recorded for, or for a
+/// new one, the user checkout was started for, falling back to the email
+/// the customer paid with.
+async fn subscription_owner(
+    db: &Database,
+    subscription: &str,
+    event: &WebhookEvent,
+) -> Result<Option<ObjectId>> {
+    if let Some(recorded) = db
+        .collection::<Subscription>("subscriptions")
+        .find_one(doc! { "subId": subscription })
+        .await?
+    {
+        return Ok(Some(recorded.user));
+    }
+
+    let users = db.collection::<User>("users");
+    let user = match event
+        .user
+        .as_deref()
+        .and_then(|id| ObjectId::parse_str(id).ok())
+    {
+        Some(id) => users.find_one(doc! { "_id": id }).await?,
+        None => match &event.email {
+            Some(email) => {
+                users
+                    .find_one(doc! { "email": email.to_lowercase() })
+                    .await?
+            }
+            None => None,
+        },
+    };
+    Ok(user.map(|user| user.id))
+}
+
+async fn subscription_changed(
+    db: &Database,
+    provider: &dyn PaymentProvider,
+    subscription: &str,
+    event: &WebhookEvent,
+) -> Result<()> {
+    // Retrying won't make the user appear, so don't ask the provider to.
+    let Some(user) = subscription_owner(db, subscription, event).await? else {
+        tracing::info!(
+            "ignoring {} for subscription {} with no known user",
+            event.kind,
+            subscription
+        );
+        return Ok(());
+    };
+
+    let provider_sub = provider.subscription(subscription).await?;
+    sync_subscription(db, user, provider.method(), &provider_sub)
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::{parse_stripe_event, Checkout, FakeProvider};
+    use crate::server::catalog::model::CatalogPlan;
+    use crate::testing::{
+        insert_user, monthly_plan, stripe_fixture, stripe_signature, test_db, user_role,
+        WEBHOOK_SECRET,
+    };
+
+    const CHECKOUT_COMPLETED: &str =
+        include_str!("../tests/fixtures/stripe/checkout_session_completed.json");
+    const SUBSCRIPTION_UPDATED: &str =
+        include_str!("../tests/fixtures/stripe/customer_subscription_updated.json");
+    const SUBSCRIPTION_DELETED: &str =
+        include_str!("../tests/fixtures/stripe/customer_subscription_deleted.json");
+    const PAYMENT_FAILED: &str =
+        include_str!("../tests/fixtures/stripe/invoice_payment_failed.json");
+
+    fn signed_event(fixture: &str, user: ObjectId, subscription: &str) -> WebhookEvent {
+        let body = stripe_fixture(fixture, user, subscription);
+        parse_stripe_event(
+            &body,
+            &stripe_signature(&body, WEBHOOK_SECRET),
+            WEBHOOK_SECRET,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_signed_stripe_events() {
+        let user = ObjectId::new();
+
+        let event = signed_event(CHECKOUT_COMPLETED, user, "sub_1");
+        assert_eq!(event.id, "evt_checkout_completed");
+        assert_eq!(event.kind, "checkout.session.completed");
+        assert_eq!(event.subscription.as_deref(), Some("sub_1"));
+        assert_eq!(event.user, Some(user.to_hex()));
+        assert_eq!(event.email.as_deref(), Some("reader@example.com"));
+
+        for (fixture, kind) in [
+            (SUBSCRIPTION_UPDATED, "customer.subscription.updated"),
+            (SUBSCRIPTION_DELETED, "customer.subscription.deleted"),
+            (PAYMENT_FAILED, "invoice.payment_failed"),
+        ] {
+            let event = signed_event(fixture, user, "sub_1");
+            assert_eq!(event.kind, kind);
+            assert_eq!(event.subscription.as_deref(), Some("sub_1"));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_signatures() {
+        let body = stripe_fixture(CHECKOUT_COMPLETED, ObjectId::new(), "sub_1");
+
+        let wrong_secret = stripe_signature(&body, "whsec_someone_else");
+        assert!(parse_stripe_event(&body, &wrong_secret, WEBHOOK_SECRET).is_err());
+
+        let signature = stripe_signature(&body, WEBHOOK_SECRET);
+        let tampered = body.replace("reader@example.com", "attacker@example.com");
+        assert!(parse_stripe_event(&tampered, &signature, WEBHOOK_SECRET).is_err());
+
+        assert!(parse_stripe_event(&body, "t=1,v1=00", WEBHOOK_SECRET).is_err());
+    }
+
+    async fn subscribe(db: &Database, provider: &FakeProvider, plan: &CatalogPlan) -> User {
+        db.collection::<CatalogPlan>("plans")
+            .insert_one(plan)
+            .await
+            .unwrap();
+        let user = insert_user(db).await;
+        provider
+            .checkout(
+                db,
+                Checkout {
+                    user: &user,
+                    plan,
+                    coupon: None,
+                    success_url: String::new(),
+                    cancel_url: String::new(),
+                },
+            )
+            .await
+            .unwrap();
+        user
+    }
+
+    async fn stored(db: &Database, subscription: &str) -> Subscription {
+        db.collection::<Subscription>("subscriptions")
+            .find_one(doc! { "subId": subscription })
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn checkout_grants_the_plan() {
+        let db = test_db().await;
+        let provider = FakeProvider::default();
+        let user = subscribe(&db, &provider, &monthly_plan(0)).await;
+
+        let event = signed_event(CHECKOUT_COMPLETED, user.id, "fake_sub_1");
+        handle_event(&db, &provider, event).await.unwrap();
+
+        let subscription = stored(&db, "fake_sub_1").await;
+        assert_eq!(subscription.user, user.id);
+        assert_eq!(subscription.plan, "monthly");
+        assert!(subscription.active);
+        assert_eq!(user_role(&db, user.id).await, "monthly");
+        db.drop().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn redelivered_events_apply_once() {
+        let db = test_db().await;
+        let provider = FakeProvider::default();
+        let user = subscribe(&db, &provider, &monthly_plan(0)).await;
+
+        let event = signed_event(CHECKOUT_COMPLETED, user.id, "fake_sub_1");
+        handle_event(&db, &provider, event.clone()).await.unwrap();
+
+        // Had the redelivery been applied, it would pick up the cancellation.
+        provider
+            .set_status("fake_sub_1", "canceled", false)
+            .unwrap();
+        handle_event(&db, &provider, event).await.unwrap();
+
+        assert!(stored(&db, "fake_sub_1").await.active);
+        assert_eq!(user_role(&db, user.id).await, "monthly");
+        let processed = db
+            .collection::<ProcessedEvent>("processed_events")
+            .count_documents(doc! {})
+            .await
+            .unwrap();
+        assert_eq!(processed, 1);
+        db.drop().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs MongoDB at MONGODB_TEST_URI"]
+    async fn lapsed_subscriptions_lose_the_plan() {
+        for (fixture, status) in [
+            (SUBSCRIPTION_UPDATED, "past_due"),
+            (SUBSCRIPTION_DELETED, "canceled"),
+            (PAYMENT_FAILED, "past_due"),
+        ] {
+            let db = test_db().await;
+            let provider = FakeProvider::default();
+            let user = subscribe(&db, &provider, &monthly_plan(0)).await;
+            let event = signed_event(CHECKOUT_COMPLETED, user.id, "fake_sub_1");
+            handle_event(&db, &provider, event).await.unwrap();
+
+            provider.set_status("fake_sub_1", status, false).unwrap();
+            let event = signed_event(fixture, user.id, "fake_sub_1");
+            handle_event(&db, &provider, event).await.unwrap();
+
+            let subscription = stored(&db, "fake_sub_1").await;
+            assert!(!subscription.active);
+            assert_eq!(subscription.status, status);
+            assert_eq!(user_role(&db, user.id).await, "free");
+            db.drop().await.unwrap();
+        }
+    }
+}